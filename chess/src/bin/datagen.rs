@@ -0,0 +1,172 @@
+//! `datagen [options]`: play self-play games and write their quiet
+//! positions, labelled with search score and result, for tuning and
+//! training.
+//!
+//! Options:
+//!   --games N           games to play (100)
+//!   --threads N         games played in parallel (all cores)
+//!   --nodes N           node budget per move (5000)
+//!   --random-plies N    random moves after the book position (8)
+//!   --book FILE         opening FENs or EPDs, one per line
+//!   --format F          text or binary (text)
+//!   --output FILE       where to write (datagen.txt / datagen.bin)
+//!   --seed N            seed for the random openings (0)
+//!
+//! `datagen --dump FILE` prints a binary file in the text format.
+
+extern crate chesslib;
+use chesslib::datagen::{self, DatagenConfig, Format};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+use std::time::Instant;
+
+const USAGE: &str = "usage: datagen [--games N] [--threads N] [--nodes N] [--random-plies N] \
+                     [--book FILE] [--format text|binary] [--output FILE] [--seed N]\n       \
+                     datagen --dump FILE";
+
+struct Args {
+    games: usize,
+    threads: usize,
+    format: Format,
+    output: Option<String>,
+    dump: Option<String>,
+    config: DatagenConfig,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args {
+        games: 100,
+        threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        format: Format::Text,
+        output: None,
+        dump: None,
+        config: DatagenConfig::default(),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{arg} needs a value"))?;
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("{arg}: not a number: {value}"))
+        };
+        match arg.as_str() {
+            "--games" => parsed.games = number()? as usize,
+            "--threads" => parsed.threads = number()?.max(1) as usize,
+            "--nodes" => parsed.config.nodes = number()?.max(1),
+            "--random-plies" => parsed.config.random_plies = number()? as usize,
+            "--seed" => parsed.config.seed = number()?,
+            "--book" => parsed.config.book = read_book(value)?,
+            "--format" => {
+                parsed.format = match value.as_str() {
+                    "text" => Format::Text,
+                    "binary" => Format::Binary,
+                    _ => return Err(format!("unknown format {value}")),
+                }
+            }
+            "--output" => parsed.output = Some(value.clone()),
+            "--dump" => parsed.dump = Some(value.clone()),
+            _ => return Err(format!("unknown option {arg}")),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Book lines are FENs, or EPDs with their four position fields.
+fn read_book(path: &str) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    let book: Vec<String> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().take(6).collect();
+            let is_fen = fields.len() == 6 && fields[4..].iter().all(|f| f.parse::<u32>().is_ok());
+            if is_fen {
+                fields.join(" ")
+            } else {
+                format!("{} 0 1", fields[..fields.len().min(4)].join(" "))
+            }
+        })
+        .collect();
+    if book.is_empty() {
+        return Err(format!("{path}: no positions"));
+    }
+    Ok(book)
+}
+
+fn dump(path: &str) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| format!("{path}: {e}"))?;
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let written = datagen::read_binary(file)
+        .try_for_each(|sample| writeln!(out, "{}", sample?.to_text()).map_err(|e| e.to_string()));
+    match written.and_then(|()| out.flush().map_err(|e| e.to_string())) {
+        // Piped into `head` and the like.
+        Err(e) if e.contains("Broken pipe") => Ok(()),
+        written => written,
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    if let Some(path) = &args.dump {
+        return dump(path);
+    }
+    let output = args.output.clone().unwrap_or_else(|| {
+        match args.format {
+            Format::Text => "datagen.txt",
+            Format::Binary => "datagen.bin",
+        }
+        .to_string()
+    });
+    let file = File::create(&output).map_err(|e| format!("{output}: {e}"))?;
+    let mut out = BufWriter::new(file);
+    eprintln!(
+        "Playing {} games on {} threads at {} nodes per move",
+        args.games, args.threads, args.config.nodes
+    );
+
+    let start = Instant::now();
+    let summary = datagen::generate(
+        args.games,
+        args.threads,
+        &args.config,
+        args.format,
+        &mut out,
+        |summary| {
+            if summary.games.is_multiple_of(10) || summary.games == args.games {
+                let seconds = start.elapsed().as_secs_f64();
+                eprintln!(
+                    "{} games, {} positions ({:.0}/s), +{} ={} -{}",
+                    summary.games,
+                    summary.positions,
+                    summary.positions as f64 / seconds.max(1e-9),
+                    summary.white_wins,
+                    summary.draws,
+                    summary.black_wins
+                );
+            }
+        },
+    )?;
+    eprintln!("Wrote {} positions to {output}", summary.positions);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args = match parse_args(&args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("datagen: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! `tbgen [--output DIR] SIGNATURE...`: solve small endgames and write
+//! their Syzygy `.rtbw` and `.rtbz` files, in the order given.
+//!
+//! Options:
+//!   --output DIR        where to write, and where the tables a
+//!                       capture or promotion leads to are read from (.)
+//!
+//! `tbgen KQvK KRvK KBvK KNvK KPvK` writes the test fixtures' tables.
+
+extern crate chesslib;
+use chesslib::tbgen;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Instant;
+
+const USAGE: &str = "usage: tbgen [--output DIR] SIGNATURE...";
+
+fn parse_args(args: &[String]) -> Result<(PathBuf, Vec<String>), String> {
+    let mut output = PathBuf::from(".");
+    let mut signatures = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => {
+                let value = args.next().ok_or_else(|| format!("{arg} needs a value"))?;
+                output = PathBuf::from(value);
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ => signatures.push(arg.clone()),
+        }
+    }
+    if signatures.is_empty() {
+        return Err("no tables named".into());
+    }
+    Ok((output, signatures))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (output, signatures) = match parse_args(&args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for signature in signatures {
+        let start = Instant::now();
+        match tbgen::generate(&signature, &output) {
+            Ok(summary) => eprintln!(
+                "{}: {} positions, +{} ={} -{}, longest DTZ {} plies ({:.1}s)",
+                summary.signature,
+                summary.positions,
+                summary.wins,
+                summary.draws,
+                summary.losses,
+                summary.max_dtz,
+                start.elapsed().as_secs_f64()
+            ),
+            Err(e) => {
+                eprintln!("tbgen: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,128 @@
+//! `tune <positions> [options]`: Texel-tune the evaluation weights on a
+//! file of labelled quiet positions and write the result out.
+//!
+//! Options:
+//!   --params FILE         start from these weights instead of the defaults
+//!   --output FILE         where to write the tuned weights (tuned.toml;
+//!                         a .json name writes JSON)
+//!   --iterations N        gradient descent steps (2000)
+//!   --learning-rate R     step size in centipawns (1.0)
+//!   --k K                 use this sigmoid scale instead of fitting it
+
+extern crate chesslib;
+use chesslib::eval_params::EvalParams;
+use chesslib::tuner::{self, TuneOptions, TuningPosition};
+use std::process::ExitCode;
+use std::time::Instant;
+
+const USAGE: &str = "usage: tune <positions> [--params FILE] [--output FILE] \
+                     [--iterations N] [--learning-rate R] [--k K]";
+
+struct Args {
+    positions: String,
+    params: Option<String>,
+    output: String,
+    k: Option<f64>,
+    options: TuneOptions,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args {
+        positions: String::new(),
+        params: None,
+        output: "tuned.toml".to_string(),
+        k: None,
+        options: TuneOptions::default(),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{arg} needs a value"))
+        };
+        let number = |text: String| {
+            text.parse::<f64>()
+                .map_err(|_| format!("{arg}: not a number: {text}"))
+        };
+        match arg.as_str() {
+            "--params" => parsed.params = Some(value()?),
+            "--output" => parsed.output = value()?,
+            "--iterations" => {
+                let text = value()?;
+                parsed.options.iterations = text
+                    .parse()
+                    .map_err(|_| format!("{arg}: not a count: {text}"))?;
+            }
+            "--learning-rate" => parsed.options.learning_rate = number(value()?)?,
+            "--k" => parsed.k = Some(number(value()?)?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ if parsed.positions.is_empty() => parsed.positions = arg.clone(),
+            _ => return Err(format!("unexpected argument {arg}")),
+        }
+    }
+    if parsed.positions.is_empty() {
+        return Err("no positions file given".to_string());
+    }
+    Ok(parsed)
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let params = match &args.params {
+        Some(path) => EvalParams::load(path)?,
+        None => EvalParams::default(),
+    };
+    let (boards, skipped) = tuner::load_training_file(&args.positions)?;
+    if boards.is_empty() {
+        return Err(format!("{}: no usable positions", args.positions));
+    }
+    println!(
+        "Loaded {} positions ({skipped} in check skipped)",
+        boards.len()
+    );
+
+    let start = Instant::now();
+    let (names, values): (Vec<String>, Vec<i64>) = tuner::flatten(&params).into_iter().unzip();
+    let probes = tuner::probes(&params);
+    let positions: Vec<TuningPosition> = boards
+        .iter()
+        .map(|(board, result)| TuningPosition::extract(board, *result, &params, &probes))
+        .collect();
+    println!(
+        "Extracted coefficients for {} weights in {:.1}s",
+        names.len(),
+        start.elapsed().as_secs_f64()
+    );
+
+    let base: Vec<f64> = values.iter().map(|&v| v as f64).collect();
+    let k = args.k.unwrap_or_else(|| tuner::fit_k(&positions, &base));
+    println!(
+        "K = {k:.4}, initial error {:.6}",
+        tuner::mean_squared_error(&positions, &base, &base, k)
+    );
+
+    let tuned = tuner::tune(&positions, &base, k, args.options, |iteration, error| {
+        println!("iteration {iteration}: error {error:.6}");
+    });
+    let rounded: Vec<f64> = tuned.iter().map(|w| w.round()).collect();
+    println!(
+        "Final error {:.6}",
+        tuner::mean_squared_error(&positions, &rounded, &base, k)
+    );
+
+    tuner::unflatten(&params, &names, &tuned).save(&args.output)?;
+    println!("Wrote {}", args.output);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = parse_args(&args).and_then(|args| run(args).map_err(|e| format!("tune: {e}")));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -3,7 +3,18 @@ use chesslib::{handle_uci_command, log_to_file};
 use std::io::{self, BufRead, Write};
 
 fn main() {
+    // `chess bench [depth] [threads] [hash]`: run the benchmark and
+    // exit instead of speaking UCI.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("bench") {
+        let result = chesslib::bench::run_bench_command(args[1..].iter().map(String::as_str));
+        println!("{result}");
+        return;
+    }
+
     chesslib::logger::set_log_path("/home/dgrant/git_personal/rust/chess/engine.log");
+    // Solve the KPK bitbase now, not in the middle of a search.
+    chesslib::kpk::init();
 
     let stdin = io::stdin();
     let mut input = String::new();
@@ -26,8 +37,12 @@ fn main() {
             break; // Exit on "quit" command
         }
 
-        println!("{response}");
-        io::stdout().flush().unwrap();
+        // Commands like 'go ponder' answer later, from the search
+        // thread; there's nothing to print for them now.
+        if !response.is_empty() {
+            println!("{response}");
+            io::stdout().flush().unwrap();
+        }
     }
 
     println!("Exiting chess engine.");
@@ -1,7 +1,8 @@
 //! Search benchmark.
 //!
-//! Times `find_best_move(depth)` on a small set of positions
-//! representative of different search regimes:
+//! Times `find_best_move_with_stats(depth)` and counts the nodes it
+//! visits on a small set of positions representative of different
+//! search regimes:
 //!
 //!   - opening    — many quiet moves, lots of branches
 //!   - middlegame — tactics + structure, moderately busy move list
@@ -12,10 +13,18 @@
 //!
 //! `CHESS_DETERMINISTIC=1` is required for stable timing — without it
 //! the engine breaks score ties randomly and the search tree explored
-//! at any given depth varies between runs.
+//! at any given depth varies between runs. With it, node counts are
+//! exactly reproducible, which makes them the number to compare when
+//! judging a pruning or ordering change: time is noisy, nodes aren't.
+//!
+//! Built with the `stats` feature, each row is followed by the
+//! search's `SearchStats` (table hit rate, first-move cutoff rate,
+//! PVS re-search rate, pruning counts):
+//!     CHESS_DETERMINISTIC=1 cargo run --release --features stats --example bench_search
 
 use chesslib::fen::load_fen;
 use chesslib::search::Searcher;
+use chesslib::stats::SearchStats;
 use std::time::Instant;
 
 /// Each fixture: (label, FEN, depth-to-search).
@@ -42,26 +51,29 @@ const FIXTURES: &[(&str, &str, i32)] = &[
 ];
 
 fn main() {
-    println!("Search benchmark — find_best_move(depth) per position");
+    println!("Search benchmark — find_best_move_with_stats(depth) per position");
     println!(
-        "{:<48} {:>5} {:>10} {:>14}",
-        "position", "depth", "secs", "best move"
+        "{:<48} {:>5} {:>10} {:>10} {:>14}",
+        "position", "depth", "nodes", "secs", "best move"
     );
-    println!("{}", "-".repeat(80));
+    println!("{}", "-".repeat(91));
 
     let mut total_secs = 0.0;
+    let mut total_nodes = 0;
     for &(label, fen, depth) in FIXTURES {
         let mut board = load_fen(fen).expect("FEN should parse");
         let start = Instant::now();
-        let (best, score) = Searcher::new().find_best_move(&mut board, depth);
+        let mut searcher = Searcher::new();
+        let (best, score, stats) = searcher.find_best_move_with_stats(&mut board, depth);
         let elapsed = start.elapsed().as_secs_f64();
         total_secs += elapsed;
+        total_nodes += stats.nodes;
 
         let best_str = best
             .map(|m| format!("{m} ({score:+})"))
             .unwrap_or_else(|| "<none>".into());
         println!(
-            "{:<48} {:>5} {:>10.3} {:>14}",
+            "{:<48} {:>5} {:>10} {:>10.3} {:>14}",
             // Truncate long labels so the table stays aligned.
             if label.len() > 47 {
                 &label[..47]
@@ -69,10 +81,19 @@ fn main() {
                 label
             },
             depth,
+            stats.nodes,
             elapsed,
             best_str
         );
+        if SearchStats::enabled() {
+            for line in stats.to_string().lines() {
+                println!("    {line}");
+            }
+        }
     }
-    println!("{}", "-".repeat(80));
-    println!("{:<48} {:>5} {:>10.3}", "total", "", total_secs);
+    println!("{}", "-".repeat(91));
+    println!(
+        "{:<48} {:>5} {:>10} {:>10.3}",
+        "total", "", total_nodes, total_secs
+    );
 }
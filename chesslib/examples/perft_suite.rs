@@ -0,0 +1,63 @@
+//! EPD perft-suite runner.
+//!
+//! Counts every position in an EPD perft suite (lines of
+//! `<fen> ;D1 20 ;D2 400 ...`) to each listed depth and reports the
+//! ones that disagree with the expected count. Exits non-zero on any
+//! mismatch, so it can gate a move-generator change.
+//!
+//! Run with:
+//!     cargo run --release --example perft_suite [file] [max depth] [threads]
+//!
+//! The file defaults to the bundled `tests/data/perft_suite.epd`, the
+//! maximum depth to 4 and the thread count to the machine's
+//! parallelism. Any failing position is worth a `go perft N` divide
+//! against another engine to find the broken move.
+
+use chesslib::perft::PerftSuiteEntry;
+use std::time::Instant;
+
+const DEFAULT_SUITE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/perft_suite.epd");
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let path = args.first().map_or(DEFAULT_SUITE, String::as_str);
+    let max_depth = args.get(1).and_then(|v| v.parse().ok()).unwrap_or(4);
+    let threads = args
+        .get(2)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{path}: {e}"));
+    let suite = PerftSuiteEntry::parse_suite(&text).unwrap_or_else(|e| panic!("{e}"));
+    println!(
+        "Perft suite {path}: {} positions, depth <= {max_depth}, {threads} threads",
+        suite.len()
+    );
+
+    let start = Instant::now();
+    let mut failures = 0;
+    for entry in &suite {
+        let mismatches = entry.check(max_depth, threads);
+        if mismatches.is_empty() {
+            println!("ok    {}", entry.fen);
+        } else {
+            failures += 1;
+            println!("FAIL  {}", entry.fen);
+            for m in mismatches {
+                println!(
+                    "      depth {}: expected {}, got {}",
+                    m.depth, m.expected, m.actual
+                );
+            }
+        }
+    }
+    println!(
+        "{} of {} positions passed in {:.3}s",
+        suite.len() - failures,
+        suite.len(),
+        start.elapsed().as_secs_f64()
+    );
+    if failures > 0 {
+        std::process::exit(1);
+    }
+}
@@ -5,6 +5,7 @@ use crate::move_generation::{
     w_pawns_attack_targets,
 };
 use crate::types::{Color, Move, Piece, PieceType, Square, SPACE};
+use crate::zobrist;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct BoardState {
@@ -19,6 +20,9 @@ pub struct BoardState {
     pub rook_castle_move: Option<Move>, // Stores the rook's move during castling
     pub captured_piece: Option<Piece>,
     pub captured_piece_square: Option<Square>,
+    /// Zobrist key of the position before this move, restored verbatim
+    /// on undo.
+    pub hash: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -74,6 +78,22 @@ pub struct Board {
     pub move_history: Vec<BoardState>,
 
     pub piece_map: [Option<Piece>; 64],
+
+    /// Zobrist key of the current position (see `zobrist.rs`). Kept
+    /// up to date by `apply_move` / `undo_last_move`; code that edits
+    /// the fields above directly must recompute it with
+    /// `compute_hash()`.
+    pub hash: u64,
+
+    /// Zobrist key of the pawns alone, for the pawn-structure cache
+    /// (see `pawns.rs`). Maintained alongside `hash`; recompute with
+    /// `compute_pawn_hash()`.
+    pub pawn_hash: u64,
+
+    /// NNUE accumulators (see `nnue.rs`), updated alongside `hash` by
+    /// the mutation helpers.
+    #[cfg(feature = "nnue")]
+    pub nnue: crate::nnue::Accumulator,
 }
 
 impl Default for Board {
@@ -205,6 +225,12 @@ impl Board {
     /// the other in a single step.
     #[inline]
     fn xor_piece(&mut self, piece: Piece, bits: u64) {
+        #[cfg(feature = "nnue")]
+        {
+            let present = self.piece_bb(piece.piece_type(), piece.color());
+            self.nnue.update(piece, bits & present, bits & !present);
+        }
+        self.toggle_piece_keys(piece, bits);
         self.pieces[piece.piece_type().idx()] ^= bits;
         self.colors[piece.color().idx()] ^= bits;
     }
@@ -214,6 +240,10 @@ impl Board {
     /// on the promotion square).
     #[inline]
     fn set_piece(&mut self, piece: Piece, bits: u64) {
+        let present = self.piece_bb(piece.piece_type(), piece.color());
+        #[cfg(feature = "nnue")]
+        self.nnue.update(piece, 0, bits & !present);
+        self.toggle_piece_keys(piece, bits & !present);
         self.pieces[piece.piece_type().idx()] |= bits;
         self.colors[piece.color().idx()] |= bits;
     }
@@ -222,10 +252,25 @@ impl Board {
     /// a piece (capture, or pawn vanishing during promotion).
     #[inline]
     fn clear_piece(&mut self, piece: Piece, bits: u64) {
+        let present = self.piece_bb(piece.piece_type(), piece.color());
+        #[cfg(feature = "nnue")]
+        self.nnue.update(piece, bits & present, 0);
+        self.toggle_piece_keys(piece, bits & present);
         self.pieces[piece.piece_type().idx()] &= !bits;
         self.colors[piece.color().idx()] &= !bits;
     }
 
+    /// XOR the key terms for `piece` on `bits` into `hash`, and into
+    /// `pawn_hash` too for a pawn.
+    #[inline]
+    fn toggle_piece_keys(&mut self, piece: Piece, bits: u64) {
+        let keys = zobrist::piece_keys(piece, bits);
+        self.hash ^= keys;
+        if piece.piece_type() == PieceType::Pawn {
+            self.pawn_hash ^= keys;
+        }
+    }
+
     /// Replace the entire bitboard for a `(PieceType, Color)` kind with
     /// a new value: every square currently occupied by that kind is
     /// cleared first, then `new_bb` is set. Both `pieces[]` and
@@ -375,7 +420,11 @@ impl Board {
             rook_castle_move: None, // Initialize as None, will be updated if castling
             captured_piece,
             captured_piece_square,
+            hash: self.hash,
         };
+        // Castling/en-passant/side-to-move part of the key before the
+        // move; swapped for the post-move value at the end.
+        let old_state_hash = self.state_hash();
 
         // Store the state before making any changes
         self.move_history.push(current_state);
@@ -492,8 +541,8 @@ impl Board {
         }
 
         // Also remove castling rights if a rook is captured
-        if target_piece.is_some() {
-            match target_piece.unwrap() {
+        if let Some(captured) = target_piece {
+            match captured {
                 Piece::WhiteRook => {
                     // TODO: use Square enum instead of hardcoded indices
                     if target_idx == 7 {
@@ -590,6 +639,11 @@ impl Board {
             Color::White => Color::Black,
             Color::Black => Color::White,
         };
+
+        // Piece terms were updated by the mutation helpers as we went.
+        self.hash ^= old_state_hash ^ self.state_hash();
+        #[cfg(feature = "nnue")]
+        self.sync_accumulator();
     }
 
     /// Recompute `colors[]` from the `pieces[]` bitboards using the
@@ -652,6 +706,11 @@ impl Board {
         moves.map(|mv| Move::try_from(mv.as_str()))
     }
 
+    /// Legal moves ranked by the static evaluation one ply ahead, best
+    /// first for the side to move; the first `n`, or all of them for a
+    /// negative `n`. Cheap, but blind to anything the opponent can do
+    /// in reply; [`crate::search::Searcher::analyse_root`] gives a
+    /// searched ranking instead.
     pub fn get_raw_moves(&mut self, n: i32) -> Vec<Move> {
         let mut possible_moves = Vec::new();
         self.get_all_raw_moves_append(&mut possible_moves);
@@ -1514,6 +1573,7 @@ impl Board {
             self.en_passant_target = state.en_passant_target;
             self.halfmove_clock = state.halfmove_clock;
             self.fullmove_number = state.fullmove_number;
+            self.hash = state.hash;
 
             // Side-to-move flips back. (No composite-bitboard cache to
             // refresh in the new layout — `pieces`/`colors` are the
@@ -1523,6 +1583,8 @@ impl Board {
                 Color::Black => Color::White,
             };
             self.update_check_state();
+            #[cfg(feature = "nnue")]
+            self.sync_accumulator();
         }
     }
 
@@ -1550,6 +1612,50 @@ impl Board {
         legal_moves.is_empty() && !self.black_king_in_check && !self.white_king_in_check
     }
 
+    /// Returns true if the current position, with the same side to
+    /// move, occurred earlier in the game. A twofold repetition rather
+    /// than the threefold the rules require: the search treats the
+    /// first repeat as a draw, since whatever one side could do about
+    /// it the first time it can do the second time too.
+    ///
+    /// Only positions since the last capture or pawn move (the
+    /// halfmove clock) can repeat, so only those are compared.
+    pub fn is_repetition(&self) -> bool {
+        let window = (self.halfmove_clock as usize).min(self.move_history.len());
+        self.move_history
+            .iter()
+            .rev()
+            .take(window)
+            .skip(1)
+            .step_by(2)
+            .any(|state| state.hash == self.hash)
+    }
+
+    /// Returns true once 50 moves (100 plies) have passed without a
+    /// capture or pawn move.
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    /// Returns true if neither side has enough material left to
+    /// checkmate: bare kings, a single minor piece, or only bishops
+    /// that all stand on squares of one colour.
+    pub fn is_insufficient_material(&self) -> bool {
+        let heavy = self.pieces[PieceType::Pawn.idx()]
+            | self.pieces[PieceType::Rook.idx()]
+            | self.pieces[PieceType::Queen.idx()];
+        if heavy != 0 {
+            return false;
+        }
+        let knights = self.pieces[PieceType::Knight.idx()];
+        let bishops = self.pieces[PieceType::Bishop.idx()];
+        if (knights | bishops).count_ones() <= 1 {
+            return true;
+        }
+        const LIGHT_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+        knights == 0 && (bishops & LIGHT_SQUARES == 0 || bishops & !LIGHT_SQUARES == 0)
+    }
+
     /// Gets a complete debug state of the board including bitboards and move history
     pub fn get_debug_state(&self) -> String {
         let mut output = String::new();
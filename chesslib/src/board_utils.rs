@@ -54,6 +54,10 @@ pub fn get_empty_board() -> Board {
         fullmove_number: 1,
         move_history: Vec::with_capacity(10),
         piece_map: [None; 64],
+        hash: 0,
+        pawn_hash: 0,
+        #[cfg(feature = "nnue")]
+        nnue: Default::default(),
     }
 }
 
@@ -113,10 +117,18 @@ pub fn get_starting_board() -> Board {
         fullmove_number: 1,
         move_history: Vec::new(),
         piece_map: [None; 64],
+        hash: 0,
+        pawn_hash: 0,
+        #[cfg(feature = "nnue")]
+        nnue: Default::default(),
     };
     // The bitboards above are correct; the mailbox `piece_map` mirrors
     // them so the per-square lookup `get_piece_at_square_fast` works.
     board.rebuild_piece_map();
+    board.hash = board.compute_hash();
+    board.pawn_hash = board.compute_pawn_hash();
+    #[cfg(feature = "nnue")]
+    board.refresh_accumulator();
     board
 }
 
@@ -0,0 +1,535 @@
+//! Training data generation: self-play games whose positions are
+//! labelled with the search score and the final result.
+//!
+//! Each game starts from a randomised opening, a position drawn from
+//! an optional book followed by a few uniformly random legal plies,
+//! and is then played out by fixed-node searches. Games end by the
+//! rules (mate, stalemate, repetition, fifty moves, insufficient
+//! material) or by adjudication: a win once the score has stayed past
+//! [`DatagenConfig::win_score`] for [`DatagenConfig::win_plies`] plies
+//! in a row, a draw once it has stayed within
+//! [`DatagenConfig::draw_score`] of zero for
+//! [`DatagenConfig::draw_plies`] plies after
+//! [`DatagenConfig::draw_min_ply`].
+//!
+//! Only quiet positions are kept: not in check, not a mate score, and
+//! the searched best move isn't a capture or a promotion. In noisy
+//! positions the static evaluation is about to be wrong no matter how
+//! good it is, so they only add noise to whatever learns from them.
+//!
+//! # Formats
+//!
+//! Text, one position per line, the format [`crate::tuner`] reads:
+//!
+//! ```text
+//! <fen> | <score> | <result>
+//! ```
+//!
+//! `score` is the search score in centipawns and `result` the game
+//! result (`1.0`, `0.5`, `0.0`), both from White's point of view.
+//!
+//! Binary, 32 bytes per position, integers little-endian:
+//!
+//! | Bytes | Field                                                    |
+//! |-------|----------------------------------------------------------|
+//! | 0–7   | occupancy, u64, bit 0 = a1                               |
+//! | 8–23  | one nibble per occupied square, a1 upwards, low nibble first: colour · 6 + piece (pawn, knight, bishop, rook, queen, king) |
+//! | 24–25 | score, i16                                               |
+//! | 26    | result: 0 Black won, 1 draw, 2 White won                 |
+//! | 27    | bit 0 Black to move; bits 1–4 castling rights KQkq       |
+//! | 28    | en passant square, 64 for none                           |
+//! | 29    | halfmove clock, capped at 255                            |
+//! | 30–31 | fullmove number, u16                                     |
+//!
+//! [`read_binary`] turns a binary file back into [`Sample`]s.
+
+use crate::board::Board;
+use crate::board_utils::get_starting_board;
+use crate::fen::load_fen;
+use crate::search::{SearchLimits, Searcher, MATE_THRESHOLD};
+use crate::types::{Color, Move, Piece, PieceType};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Size of one position in the binary format.
+pub const RECORD_BYTES: usize = 32;
+
+/// How a game ended, from White's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    BlackWin,
+    Draw,
+    WhiteWin,
+}
+
+impl GameResult {
+    /// 1.0 for a White win, 0.5 for a draw, 0.0 for a loss.
+    pub fn score(self) -> f64 {
+        match self {
+            GameResult::BlackWin => 0.0,
+            GameResult::Draw => 0.5,
+            GameResult::WhiteWin => 1.0,
+        }
+    }
+
+    /// A win for `color`.
+    pub fn win_for(color: Color) -> Self {
+        match color {
+            Color::White => GameResult::WhiteWin,
+            Color::Black => GameResult::BlackWin,
+        }
+    }
+}
+
+/// One labelled position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    pub fen: String,
+    /// Search score, White's point of view, in centipawns.
+    pub score: i16,
+    pub result: GameResult,
+}
+
+impl Sample {
+    /// The text format's line, without the newline.
+    pub fn to_text(&self) -> String {
+        format!("{} | {} | {:.1}", self.fen, self.score, self.result.score())
+    }
+
+    /// The binary format's record.
+    pub fn to_bytes(&self) -> Result<[u8; RECORD_BYTES], String> {
+        let board = load_fen(&self.fen).map_err(|e| format!("{e}: {}", self.fen))?;
+        let mut record = [0u8; RECORD_BYTES];
+        let occupied = board.colors[0] | board.colors[1];
+        record[0..8].copy_from_slice(&occupied.to_le_bytes());
+        let mut bits = occupied;
+        let mut n = 0;
+        while bits != 0 {
+            if n == 32 {
+                return Err(format!("more than 32 pieces: {}", self.fen));
+            }
+            let sq = bits.trailing_zeros() as u8;
+            bits &= bits - 1;
+            let piece = board.get_piece_at_square_fast(sq).expect("occupied square");
+            record[8 + n / 2] |= piece_code(piece) << (4 * (n % 2));
+            n += 1;
+        }
+        record[24..26].copy_from_slice(&self.score.to_le_bytes());
+        record[26] = match self.result {
+            GameResult::BlackWin => 0,
+            GameResult::Draw => 1,
+            GameResult::WhiteWin => 2,
+        };
+        record[27] = u8::from(board.side_to_move == Color::Black)
+            | u8::from(board.white_kingside_castle_rights) << 1
+            | u8::from(board.white_queenside_castle_rights) << 2
+            | u8::from(board.black_kingside_castle_rights) << 3
+            | u8::from(board.black_queenside_castle_rights) << 4;
+        record[28] = board.en_passant_target.map_or(64, |sq| sq.to_bit_index());
+        record[29] = board.halfmove_clock.min(255) as u8;
+        let fullmove = board.fullmove_number.min(u16::MAX as u32) as u16;
+        record[30..32].copy_from_slice(&fullmove.to_le_bytes());
+        Ok(record)
+    }
+
+    /// Decode a binary record.
+    pub fn from_bytes(record: &[u8; RECORD_BYTES]) -> Result<Self, String> {
+        let occupied = u64::from_le_bytes(record[0..8].try_into().unwrap());
+        if occupied.count_ones() > 32 {
+            return Err("more than 32 pieces".to_string());
+        }
+        let mut squares = [None; 64];
+        let mut bits = occupied;
+        let mut n = 0;
+        while bits != 0 {
+            let sq = bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+            let code = (record[8 + n / 2] >> (4 * (n % 2))) & 0xF;
+            squares[sq] = Some(piece_from_code(code).ok_or(format!("bad piece code {code}"))?);
+            n += 1;
+        }
+
+        let mut fen = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match squares[rank * 8 + file] {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push_str(piece.to_fen());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+        let flags = record[27];
+        fen.push_str(if flags & 1 != 0 { " b " } else { " w " });
+        let castling: String = [(1, 'K'), (2, 'Q'), (3, 'k'), (4, 'q')]
+            .iter()
+            .filter(|&&(bit, _)| flags & (1 << bit) != 0)
+            .map(|&(_, c)| c)
+            .collect();
+        fen.push_str(if castling.is_empty() { "-" } else { &castling });
+        match record[28] {
+            64 => fen.push_str(" -"),
+            sq @ 0..=63 => fen.push_str(&format!(" {}{}", (b'a' + sq % 8) as char, sq / 8 + 1)),
+            sq => return Err(format!("bad en passant square {sq}")),
+        }
+        let fullmove = u16::from_le_bytes([record[30], record[31]]);
+        fen.push_str(&format!(" {} {fullmove}", record[29]));
+
+        let result = match record[26] {
+            0 => GameResult::BlackWin,
+            1 => GameResult::Draw,
+            2 => GameResult::WhiteWin,
+            r => return Err(format!("bad result {r}")),
+        };
+        Ok(Self {
+            fen,
+            score: i16::from_le_bytes([record[24], record[25]]),
+            result,
+        })
+    }
+}
+
+fn piece_code(piece: Piece) -> u8 {
+    let kind = match piece.piece_type() {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    };
+    piece.color().idx() as u8 * 6 + kind
+}
+
+fn piece_from_code(code: u8) -> Option<Piece> {
+    let kind = match code % 6 {
+        0 => PieceType::Pawn,
+        1 => PieceType::Knight,
+        2 => PieceType::Bishop,
+        3 => PieceType::Rook,
+        4 => PieceType::Queen,
+        _ => PieceType::King,
+    };
+    let color = match code / 6 {
+        0 => Color::White,
+        1 => Color::Black,
+        _ => return None,
+    };
+    Some(Piece::from_type_and_color(kind, color))
+}
+
+/// Every record of a binary file, in order.
+pub fn read_binary(reader: impl Read) -> impl Iterator<Item = Result<Sample, String>> {
+    let mut reader = io::BufReader::new(reader);
+    std::iter::from_fn(move || {
+        let mut record = [0u8; RECORD_BYTES];
+        match reader.read_exact(&mut record) {
+            Ok(()) => Some(Sample::from_bytes(&record)),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(e) => Some(Err(e.to_string())),
+        }
+    })
+}
+
+/// Output format for [`generate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Binary,
+}
+
+impl Format {
+    pub fn write(self, out: &mut impl Write, sample: &Sample) -> Result<(), String> {
+        match self {
+            Format::Text => writeln!(out, "{}", sample.to_text()).map_err(|e| e.to_string()),
+            Format::Binary => out
+                .write_all(&sample.to_bytes()?)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Settings for self-play.
+#[derive(Debug, Clone)]
+pub struct DatagenConfig {
+    /// Node budget per move.
+    pub nodes: u64,
+    /// Uniformly random plies played after the book position.
+    pub random_plies: usize,
+    /// Opening FENs to start from, one picked per game; the starting
+    /// position if empty.
+    pub book: Vec<String>,
+    /// Openings the first search scores past this are thrown away and
+    /// drawn again: a random opening that just loses a piece teaches
+    /// nothing.
+    pub max_opening_score: i64,
+    pub win_score: i64,
+    pub win_plies: usize,
+    pub draw_score: i64,
+    pub draw_plies: usize,
+    pub draw_min_ply: usize,
+    /// Games still going after this many plies are drawn.
+    pub max_plies: usize,
+    pub seed: u64,
+}
+
+impl Default for DatagenConfig {
+    fn default() -> Self {
+        Self {
+            nodes: 5000,
+            random_plies: 8,
+            book: Vec::new(),
+            max_opening_score: 400,
+            win_score: 1000,
+            win_plies: 6,
+            draw_score: 15,
+            draw_plies: 12,
+            draw_min_ply: 80,
+            max_plies: 400,
+            seed: 0,
+        }
+    }
+}
+
+/// A finished game's positions, and how it ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub samples: Vec<Sample>,
+    pub result: GameResult,
+    pub plies: usize,
+}
+
+fn legal_moves(board: &mut Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    board.get_all_raw_moves_append(&mut moves);
+    moves
+}
+
+fn in_check(board: &Board) -> bool {
+    match board.side_to_move {
+        Color::White => board.white_king_in_check,
+        Color::Black => board.black_king_in_check,
+    }
+}
+
+/// A capture, en passant included, or a promotion.
+fn is_noisy(board: &Board, mv: &Move) -> bool {
+    let target = mv.target.to_bit_index();
+    let en_passant = board.en_passant_target == Some(mv.target)
+        && board
+            .get_piece_at_square_fast(mv.src.to_bit_index())
+            .is_some_and(|p| p.piece_type() == PieceType::Pawn);
+    mv.promotion.is_some() || en_passant || board.get_piece_at_square_fast(target).is_some()
+}
+
+fn search(searcher: &mut Searcher, board: &mut Board, nodes: u64) -> (Option<Move>, i64) {
+    let limits = SearchLimits {
+        nodes: Some(nodes),
+        ..SearchLimits::default()
+    };
+    let result = searcher.search(board, limits, 1);
+    (result.best_move(), result.score)
+}
+
+/// A random opening: a book position (or the start) and
+/// `config.random_plies` random legal moves, redrawn until it has
+/// legal moves left and a playable score.
+fn opening(searcher: &mut Searcher, rng: &mut StdRng, config: &DatagenConfig) -> Board {
+    loop {
+        let mut board = match config.book.len() {
+            0 => get_starting_board(),
+            n => match load_fen(&config.book[rng.gen_range(0..n)]) {
+                Ok(board) => board,
+                Err(_) => continue,
+            },
+        };
+        let mut playable = true;
+        for _ in 0..config.random_plies {
+            let moves = legal_moves(&mut board);
+            if moves.is_empty() {
+                playable = false;
+                break;
+            }
+            board.apply_move(&moves[rng.gen_range(0..moves.len())]);
+        }
+        if !playable || legal_moves(&mut board).is_empty() {
+            continue;
+        }
+        let (_, score) = search(searcher, &mut board, config.nodes);
+        if score.abs() <= config.max_opening_score {
+            // Start the game itself from a clean slate: the opening's
+            // moves are no part of it.
+            board.move_history.clear();
+            return board;
+        }
+    }
+}
+
+/// Play one game. The searcher is a fresh one, with no table entries
+/// or move-ordering history from earlier games, so the game depends
+/// only on `rng` and `config`.
+pub fn play_game(rng: &mut StdRng, config: &DatagenConfig) -> Game {
+    let searcher = &mut Searcher::new_deterministic();
+    let mut board = opening(searcher, rng, config);
+    let mut positions: Vec<(String, i16)> = Vec::new();
+    let (mut win_streak, mut draw_streak) = (0, 0);
+    let mut last_winner = None;
+    let mut plies = 0;
+
+    let result = loop {
+        let moves = legal_moves(&mut board);
+        if moves.is_empty() {
+            break match in_check(&board) {
+                true => GameResult::win_for(board.side_to_move.opponent()),
+                false => GameResult::Draw,
+            };
+        }
+        if board.is_repetition()
+            || board.is_fifty_move_draw()
+            || board.is_insufficient_material()
+            || plies >= config.max_plies
+        {
+            break GameResult::Draw;
+        }
+
+        let (best, score) = search(searcher, &mut board, config.nodes);
+        let best = best.unwrap_or(moves[0]);
+        if !in_check(&board) && !is_noisy(&board, &best) && score.abs() < MATE_THRESHOLD {
+            positions.push((board.to_fen(), score.clamp(-32000, 32000) as i16));
+        }
+
+        // Adjudication on the score of the position before the move.
+        let winner = if score >= config.win_score {
+            Some(Color::White)
+        } else if score <= -config.win_score {
+            Some(Color::Black)
+        } else {
+            None
+        };
+        win_streak = if winner.is_some() && winner == last_winner {
+            win_streak + 1
+        } else {
+            usize::from(winner.is_some())
+        };
+        last_winner = winner;
+        if let (Some(color), true) = (winner, win_streak >= config.win_plies) {
+            break GameResult::win_for(color);
+        }
+        draw_streak = if plies >= config.draw_min_ply && score.abs() <= config.draw_score {
+            draw_streak + 1
+        } else {
+            0
+        };
+        if draw_streak >= config.draw_plies {
+            break GameResult::Draw;
+        }
+
+        board.apply_move(&best);
+        plies += 1;
+    };
+
+    Game {
+        samples: positions
+            .into_iter()
+            .map(|(fen, score)| Sample { fen, score, result })
+            .collect(),
+        result,
+        plies,
+    }
+}
+
+/// Totals from [`generate`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DatagenSummary {
+    pub games: usize,
+    pub positions: usize,
+    pub white_wins: usize,
+    pub draws: usize,
+    pub black_wins: usize,
+}
+
+/// Play `games` games on `threads` threads, writing every kept
+/// position to `out` game by game, in game order: the output for a
+/// seed is the same on any number of threads. `progress` hears about
+/// each game written with the running totals.
+pub fn generate(
+    games: usize,
+    threads: usize,
+    config: &DatagenConfig,
+    format: Format,
+    out: &mut impl Write,
+    mut progress: impl FnMut(&DatagenSummary),
+) -> Result<DatagenSummary, String> {
+    let next_game = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel::<(usize, Game)>();
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            let sender = sender.clone();
+            let next_game = &next_game;
+            scope.spawn(move || loop {
+                let game = next_game.fetch_add(1, Ordering::Relaxed);
+                if game >= games {
+                    break;
+                }
+                // One generator per game, so a game's moves don't
+                // depend on which thread happened to play it.
+                let seed = config.seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ game as u64;
+                let mut rng = StdRng::seed_from_u64(seed);
+                if sender.send((game, play_game(&mut rng, config))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Games finish out of order; hold each one back until the
+        // games before it are written.
+        let mut summary = DatagenSummary::default();
+        let mut finished = BTreeMap::new();
+        for (index, game) in receiver {
+            finished.insert(index, game);
+            while let Some(game) = finished.remove(&summary.games) {
+                write_game(&game, format, out, &mut summary)?;
+                progress(&summary);
+            }
+        }
+        out.flush().map_err(|e| e.to_string())?;
+        Ok(summary)
+    })
+}
+
+fn write_game(
+    game: &Game,
+    format: Format,
+    out: &mut impl Write,
+    summary: &mut DatagenSummary,
+) -> Result<(), String> {
+    for sample in &game.samples {
+        format.write(out, sample)?;
+    }
+    summary.games += 1;
+    summary.positions += game.samples.len();
+    match game.result {
+        GameResult::WhiteWin => summary.white_wins += 1,
+        GameResult::Draw => summary.draws += 1,
+        GameResult::BlackWin => summary.black_wins += 1,
+    }
+    Ok(())
+}
@@ -0,0 +1,342 @@
+//! Endgame knowledge, keyed by material signature.
+//!
+//! The general evaluation counts material, so on its own it thinks a
+//! lone knight is worth three pawns and has no idea which corner a
+//! bishop-and-knight mate happens in. This module knows a handful of
+//! endgames by their material, the [`MaterialKey`]:
+//!
+//! - Mating material against a lone king (KQK, KRK and anything
+//!   bigger): a bonus for driving the lone king to the edge and
+//!   bringing the other king up, so the search has a gradient to
+//!   follow towards a mate it can't yet see.
+//! - KBNK: the same, but towards a corner of the bishop's colour, the
+//!   only place the mate works.
+//! - Material that can't win: the rule draws of
+//!   [`Board::is_insufficient_material`], KNNK, a bishop with rook
+//!   pawns of the wrong colour against a king in the corner, and a
+//!   pawnless side whose extra material is at most a minor piece.
+//! - Opposite-coloured bishops with nothing else but pawns.
+//! - KPK, looked up in the [`kpk`] bitbase: a win gets a flat bonus on
+//!   top of the pawn's worth, a draw scores zero.
+//!
+//! Mating bonuses are an extra [`EvalTerm::Endgame`](crate::eval_trace::EvalTerm)
+//! term. Drawish material gives a scale factor out of
+//! [`SCALE_NORMAL`] that shrinks the endgame half of the tapered
+//! evaluation; the middlegame half, already faded out by this much
+//! material coming off, is left alone. [`SCALE_DRAW`] is the exception:
+//! a dead draw zeroes the whole score.
+
+use crate::board::Board;
+use crate::kpk;
+use crate::types::{Color, PieceType, BISHOP_VALUE, KNIGHT_VALUE, QUEEN_VALUE, ROOK_VALUE};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The endgame half of the evaluation counts in full.
+pub const SCALE_NORMAL: i32 = 64;
+/// A dead draw: the evaluation is zero.
+pub const SCALE_DRAW: i32 = 0;
+/// Opposite-coloured bishops and pawns, at most one pawn apart.
+pub const SCALE_OPPOSITE_BISHOPS: i32 = 16;
+/// ... and further apart.
+pub const SCALE_OPPOSITE_BISHOPS_PAWNS: i32 = 32;
+/// No pawns and a minor piece ahead, against at most a minor (KRKB,
+/// KRKN).
+pub const SCALE_PAWNLESS_MINOR: i32 = 4;
+/// No pawns and a minor piece ahead, against more (KRBKR).
+pub const SCALE_PAWNLESS: i32 = 14;
+
+/// Per square the lone king stands from the centre.
+pub(crate) const EDGE_PUSH: i64 = 20;
+/// Per square the kings are closer than the width of the board.
+pub(crate) const KING_PROXIMITY: i64 = 10;
+/// KBNK: per square the lone king is closer to a corner of the
+/// bishop's colour.
+pub(crate) const CORNER_PUSH: i64 = 40;
+/// KPK, when the bitbase says the pawn queens.
+pub(crate) const KPK_WIN: i64 = 500;
+
+/// a1, c1, ... b2, d2, ...: the squares of a1's colour.
+const DARK_SQUARES: u64 = 0xAA55_AA55_AA55_AA55;
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_H: u64 = FILE_A << 7;
+
+/// Signature order: strongest piece first, as in "KQRvK".
+const SIGNATURE: [(PieceType, char); 6] = [
+    (PieceType::King, 'K'),
+    (PieceType::Queen, 'Q'),
+    (PieceType::Rook, 'R'),
+    (PieceType::Bishop, 'B'),
+    (PieceType::Knight, 'N'),
+    (PieceType::Pawn, 'P'),
+];
+
+/// How many of each piece each side has: four bits per colour and
+/// piece type, so two positions with the same material have the same
+/// key whatever the squares. Displays as a signature like `KBNvK`,
+/// White first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaterialKey(u64);
+
+impl MaterialKey {
+    fn shift(color: Color, piece_type: PieceType) -> u32 {
+        4 * (color.idx() * 6 + piece_type.idx()) as u32
+    }
+
+    /// The material on `board`. Counts past 15 (impossible without
+    /// more than eight promotions) saturate.
+    pub fn of(board: &Board) -> Self {
+        let mut key = 0;
+        for color in [Color::White, Color::Black] {
+            for (piece_type, _) in SIGNATURE {
+                let count = board.piece_bb(piece_type, color).count_ones().min(15) as u64;
+                key |= count << Self::shift(color, piece_type);
+            }
+        }
+        Self(key)
+    }
+
+    /// Parse a signature such as `KBNvK` or `KRPvKR`, White first.
+    pub fn parse(signature: &str) -> Result<Self, String> {
+        let (white, black) = signature
+            .split_once('v')
+            .ok_or_else(|| format!("no 'v' in material signature {signature:?}"))?;
+        let mut key = 0;
+        for (color, side) in [(Color::White, white), (Color::Black, black)] {
+            if !side.starts_with('K') || side.matches('K').count() != 1 {
+                return Err(format!("each side needs one king: {signature:?}"));
+            }
+            for c in side.chars() {
+                let &(piece_type, _) = SIGNATURE
+                    .iter()
+                    .find(|&&(_, letter)| letter == c)
+                    .ok_or_else(|| format!("bad piece {c:?} in {signature:?}"))?;
+                let shift = Self::shift(color, piece_type);
+                if (key >> shift) & 0xF == 15 {
+                    return Err(format!("too many pieces in {signature:?}"));
+                }
+                key += 1 << shift;
+            }
+        }
+        Ok(Self(key))
+    }
+
+    /// How many `piece_type`s `color` has.
+    pub fn count(self, color: Color, piece_type: PieceType) -> u32 {
+        ((self.0 >> Self::shift(color, piece_type)) & 0xF) as u32
+    }
+
+    /// The same material with the colours swapped.
+    pub fn flipped(self) -> Self {
+        let half = 4 * 6;
+        Self((self.0 >> half) | ((self.0 & ((1 << half) - 1)) << half))
+    }
+
+    /// `color` has nothing but its king.
+    pub fn is_lone_king(self, color: Color) -> bool {
+        (self.0 >> (4 * 6 * color.idx())) & 0xFF_FFFF
+            == 1 << Self::shift(Color::White, PieceType::King)
+    }
+
+    /// `color`'s knights, bishops, rooks and queens at their nominal
+    /// values.
+    pub fn non_pawn_material(self, color: Color) -> i64 {
+        self.count(color, PieceType::Knight) as i64 * KNIGHT_VALUE
+            + self.count(color, PieceType::Bishop) as i64 * BISHOP_VALUE
+            + self.count(color, PieceType::Rook) as i64 * ROOK_VALUE
+            + self.count(color, PieceType::Queen) as i64 * QUEEN_VALUE
+    }
+}
+
+impl fmt::Display for MaterialKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, color) in [Color::White, Color::Black].into_iter().enumerate() {
+            if i == 1 {
+                write!(f, "v")?;
+            }
+            for (piece_type, letter) in SIGNATURE {
+                for _ in 0..self.count(color, piece_type) {
+                    write!(f, "{letter}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An endgame this module knows by its material.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endgame {
+    /// A queen or rook (and maybe more) against a lone king: drive it
+    /// to the edge.
+    Kxk,
+    /// Bishop and knight against a lone king: drive it to a corner of
+    /// the bishop's colour.
+    Kbnk,
+    /// King and pawn against king: won or drawn as the KPK bitbase
+    /// says.
+    Kpk,
+    /// Can't be won by force, though it isn't a draw by rule: KNNK.
+    Drawn,
+}
+
+lazy_static::lazy_static! {
+    /// Signatures with the stronger side as White; each is entered
+    /// for both colours.
+    static ref ENDGAMES: HashMap<MaterialKey, (Endgame, Color)> = {
+        let mut table = HashMap::new();
+        for (signature, endgame) in [
+            ("KQvK", Endgame::Kxk),
+            ("KRvK", Endgame::Kxk),
+            ("KBNvK", Endgame::Kbnk),
+            ("KNNvK", Endgame::Drawn),
+            ("KPvK", Endgame::Kpk),
+        ] {
+            let key = MaterialKey::parse(signature).unwrap();
+            table.insert(key, (endgame, Color::White));
+            table.insert(key.flipped(), (endgame, Color::Black));
+        }
+        table
+    };
+}
+
+/// The known endgame for `key`, and its stronger side. Signatures
+/// missing from the table still count as [`Endgame::Kxk`] when one
+/// side has a lone king and the other a queen or rook.
+pub fn lookup(key: MaterialKey) -> Option<(Endgame, Color)> {
+    let weak = [Color::White, Color::Black]
+        .into_iter()
+        .find(|&color| key.is_lone_king(color))?;
+    if let Some(&known) = ENDGAMES.get(&key) {
+        return Some(known);
+    }
+    let strong = weak.opponent();
+    let heavy = key.count(strong, PieceType::Queen) + key.count(strong, PieceType::Rook);
+    (heavy > 0).then_some((Endgame::Kxk, strong))
+}
+
+/// King moves between two squares.
+fn distance(a: u8, b: u8) -> i64 {
+    let files = (a % 8) as i64 - (b % 8) as i64;
+    let ranks = (a / 8) as i64 - (b / 8) as i64;
+    files.abs().max(ranks.abs())
+}
+
+/// Files plus ranks from the centre: 0 on d4, e4, d5 and e5, 6 in a
+/// corner.
+fn centre_distance(sq: u8) -> i64 {
+    let from_centre = |x: i64| (3 - x).max(x - 4);
+    from_centre((sq % 8) as i64) + from_centre((sq / 8) as i64)
+}
+
+fn manhattan(a: u8, b: u8) -> i64 {
+    ((a % 8) as i64 - (b % 8) as i64).abs() + ((a / 8) as i64 - (b / 8) as i64).abs()
+}
+
+fn king_square(board: &Board, color: Color) -> u8 {
+    board.piece_bb(PieceType::King, color).trailing_zeros() as u8
+}
+
+/// The mating-net bonus for the stronger side of a known mate, from
+/// its own point of view.
+pub fn mating_bonus(board: &Board, key: MaterialKey) -> Option<(Color, i64)> {
+    let (endgame, strong) = lookup(key)?;
+    let winner = king_square(board, strong);
+    let loser = king_square(board, strong.opponent());
+    let proximity = KING_PROXIMITY * (7 - distance(winner, loser));
+    let bonus = match endgame {
+        Endgame::Kxk => EDGE_PUSH * centre_distance(loser) + proximity,
+        Endgame::Kbnk => {
+            let dark = board.piece_bb(PieceType::Bishop, strong) & DARK_SQUARES != 0;
+            let corners = if dark { [0, 63] } else { [7, 56] };
+            let to_corner = corners.iter().map(|&c| manhattan(loser, c)).min().unwrap();
+            CORNER_PUSH * (7 - to_corner) + proximity
+        }
+        Endgame::Kpk if kpk::probe(board)? => KPK_WIN,
+        Endgame::Kpk | Endgame::Drawn => return None,
+    };
+    Some((strong, bonus))
+}
+
+/// How much of the endgame half of the evaluation counts, out of
+/// [`SCALE_NORMAL`]. `strong` is the side the unscaled evaluation
+/// favours.
+pub fn scale_factor(board: &Board, key: MaterialKey, strong: Color) -> i32 {
+    let drawn = match lookup(key) {
+        Some((Endgame::Drawn, _)) => true,
+        Some((Endgame::Kpk, _)) => kpk::probe(board) == Some(false),
+        _ => false,
+    };
+    if drawn
+        || board.is_insufficient_material()
+        || [Color::White, Color::Black]
+            .into_iter()
+            .any(|color| wrong_rook_pawn(board, key, color))
+    {
+        return SCALE_DRAW;
+    }
+
+    let weak = strong.opponent();
+    if key.count(strong, PieceType::Pawn) == 0 {
+        let (ours, theirs) = (key.non_pawn_material(strong), key.non_pawn_material(weak));
+        if ours - theirs <= BISHOP_VALUE {
+            return if ours < ROOK_VALUE {
+                SCALE_DRAW
+            } else if theirs <= BISHOP_VALUE {
+                SCALE_PAWNLESS_MINOR
+            } else {
+                SCALE_PAWNLESS
+            };
+        }
+    }
+
+    if opposite_bishops_only(board, key) {
+        let difference = key.count(Color::White, PieceType::Pawn) as i64
+            - key.count(Color::Black, PieceType::Pawn) as i64;
+        return if difference.abs() <= 1 {
+            SCALE_OPPOSITE_BISHOPS
+        } else {
+            SCALE_OPPOSITE_BISHOPS_PAWNS
+        };
+    }
+    SCALE_NORMAL
+}
+
+/// `color` has a bishop and pawns all on one rook file, the bishop
+/// can't cover the promotion square, and the lone enemy king holds the
+/// corner.
+fn wrong_rook_pawn(board: &Board, key: MaterialKey, color: Color) -> bool {
+    let weak = color.opponent();
+    let pawns = board.piece_bb(PieceType::Pawn, color);
+    let only_bishop =
+        key.count(color, PieceType::Bishop) == 1 && key.non_pawn_material(color) == BISHOP_VALUE;
+    if !key.is_lone_king(weak) || !only_bishop || pawns == 0 {
+        return false;
+    }
+    let file = if pawns & !FILE_A == 0 {
+        0
+    } else if pawns & !FILE_H == 0 {
+        7
+    } else {
+        return false;
+    };
+    let promotion = match color {
+        Color::White => 56 + file,
+        Color::Black => file,
+    };
+    let bishop_dark = board.piece_bb(PieceType::Bishop, color) & DARK_SQUARES != 0;
+    let promotion_dark = (1u64 << promotion) & DARK_SQUARES != 0;
+    bishop_dark != promotion_dark && distance(king_square(board, weak), promotion) <= 1
+}
+
+/// One bishop each, on opposite colours, and nothing else but pawns.
+fn opposite_bishops_only(board: &Board, key: MaterialKey) -> bool {
+    let bishops_only = [Color::White, Color::Black].into_iter().all(|color| {
+        key.count(color, PieceType::Bishop) == 1 && key.non_pawn_material(color) == BISHOP_VALUE
+    });
+    if !bishops_only {
+        return false;
+    }
+    let dark = board.pieces[PieceType::Bishop.idx()] & DARK_SQUARES;
+    dark.count_ones() == 1
+}
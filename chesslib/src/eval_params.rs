@@ -0,0 +1,314 @@
+//! Every weight of the static evaluation, as data.
+//!
+//! [`EvalParams::default`] reproduces the built-in evaluation exactly:
+//! its values are the constants documented in `evaluation.rs`,
+//! `pawns.rs`, `king_safety.rs`, `pieces.rs` and the material values in
+//! `types.rs`. A tuning experiment can instead load a TOML or JSON file
+//! ([`EvalParams::load`]) or change single weights by path
+//! ([`EvalParams::set`], e.g. `pawns.doubled.mg`), and write the result
+//! back out ([`EvalParams::to_toml`], [`EvalParams::to_json`]) to share
+//! it. Files may list only the weights they change; everything missing
+//! keeps its default.
+//!
+//! [`Board::evaluate`](crate::board::Board::evaluate) uses the
+//! process-wide *active* parameters, replaced with
+//! [`EvalParams::set_active`]. Each thread keeps its own handle to
+//! them and only re-reads the shared copy when a generation counter
+//! says they've changed, so the hot path costs one atomic load.
+//! [`Board::evaluate_with`](crate::board::Board::evaluate_with)
+//! evaluates under any other set without touching the active one.
+//!
+//! Over UCI, `setoption name EvalParams value <file>` loads a file and
+//! `setoption name <path> value <n>` (any name containing a dot) sets
+//! one weight.
+
+use crate::evaluation::{self, Tapered};
+use crate::{king_safety, pawns, pieces, types};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// All evaluation weights, grouped the way the evaluation is.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EvalParams {
+    pub material: MaterialParams,
+    pub positional: PositionalParams,
+    pub pawns: PawnParams,
+    pub king_safety: KingSafetyParams,
+    pub pieces: PieceParams,
+}
+
+/// Piece values for the evaluation's material count. Static exchange
+/// evaluation and move ordering keep the fixed values in `types.rs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaterialParams {
+    pub pawn: i64,
+    pub knight: i64,
+    pub bishop: i64,
+    pub rook: i64,
+    pub queen: i64,
+}
+
+impl Default for MaterialParams {
+    fn default() -> Self {
+        Self {
+            pawn: types::PAWN_VALUE,
+            knight: types::KNIGHT_VALUE,
+            bishop: types::BISHOP_VALUE,
+            rook: types::ROOK_VALUE,
+            queen: types::QUEEN_VALUE,
+        }
+    }
+}
+
+/// The untapered terms of `evaluation.rs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PositionalParams {
+    /// Per piece on the centre; half as much on the extended centre.
+    pub center_control: i64,
+    /// Per square a knight, bishop or rook can move to.
+    pub mobility: i64,
+    pub bishop_pair: i64,
+    /// Per castling right still held.
+    pub castling_rights: i64,
+    /// For giving check.
+    pub check: i64,
+}
+
+impl Default for PositionalParams {
+    fn default() -> Self {
+        Self {
+            center_control: evaluation::CENTER_CONTROL_BONUS,
+            mobility: evaluation::MOBILITY_BONUS,
+            bishop_pair: evaluation::BISHOP_PAIR_BONUS,
+            castling_rights: evaluation::CASTLING_RIGHTS_BONUS,
+            check: evaluation::CHECK_BONUS,
+        }
+    }
+}
+
+/// Pawn-structure weights; see `pawns.rs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PawnParams {
+    pub doubled: Tapered,
+    pub isolated: Tapered,
+    pub backward: Tapered,
+    /// By relative rank, both phases.
+    pub connected: [i64; 8],
+    /// By relative rank.
+    pub passed: [Tapered; 8],
+    /// Endgame only.
+    pub unstoppable: i64,
+}
+
+impl Default for PawnParams {
+    fn default() -> Self {
+        Self {
+            doubled: pawns::DOUBLED,
+            isolated: pawns::ISOLATED,
+            backward: pawns::BACKWARD,
+            connected: pawns::CONNECTED,
+            passed: pawns::PASSED,
+            unstoppable: pawns::UNSTOPPABLE,
+        }
+    }
+}
+
+/// King-safety weights; see `king_safety.rs`. All middlegame, except
+/// that a quarter of the attack penalty also applies in the endgame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KingSafetyParams {
+    /// By distance in front of the king, 1–3.
+    pub shield: [i64; 4],
+    pub shield_missing: i64,
+    /// By the stormer's distance from the king.
+    pub storm: [i64; 8],
+    pub semi_open_file: i64,
+    pub open_file: i64,
+    /// By the king's file, a–h.
+    pub king_file: [i64; 8],
+    /// Attack units per zone square hit, by `PieceType::idx()`.
+    pub attack_weight: [i64; 6],
+    /// Penalty by attack units; units past the end use the last entry.
+    pub safety_table: Vec<i64>,
+}
+
+impl Default for KingSafetyParams {
+    fn default() -> Self {
+        Self {
+            shield: king_safety::SHIELD,
+            shield_missing: king_safety::SHIELD_MISSING,
+            storm: king_safety::STORM,
+            semi_open_file: king_safety::SEMI_OPEN_FILE,
+            open_file: king_safety::OPEN_FILE,
+            king_file: king_safety::KING_FILE,
+            attack_weight: king_safety::ATTACK_WEIGHT,
+            safety_table: king_safety::SAFETY_TABLE.to_vec(),
+        }
+    }
+}
+
+/// Piece-specific weights; see `pieces.rs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PieceParams {
+    pub rook_open_file: Tapered,
+    pub rook_semi_open_file: Tapered,
+    pub rook_on_seventh: Tapered,
+    pub knight_outpost: Tapered,
+    pub bad_bishop_pawn: Tapered,
+    pub early_queen: Tapered,
+    pub trapped_bishop: Tapered,
+    pub trapped_rook: Tapered,
+    pub threat_by_pawn: Tapered,
+    pub threat_by_minor: Tapered,
+    pub threat_by_rook: Tapered,
+    pub threat_by_king: Tapered,
+}
+
+impl Default for PieceParams {
+    fn default() -> Self {
+        Self {
+            rook_open_file: pieces::ROOK_OPEN_FILE,
+            rook_semi_open_file: pieces::ROOK_SEMI_OPEN_FILE,
+            rook_on_seventh: pieces::ROOK_ON_SEVENTH,
+            knight_outpost: pieces::KNIGHT_OUTPOST,
+            bad_bishop_pawn: pieces::BAD_BISHOP_PAWN,
+            early_queen: pieces::EARLY_QUEEN,
+            trapped_bishop: pieces::TRAPPED_BISHOP,
+            trapped_rook: pieces::TRAPPED_ROOK,
+            threat_by_pawn: pieces::THREAT_BY_PAWN,
+            threat_by_minor: pieces::THREAT_BY_MINOR,
+            threat_by_rook: pieces::THREAT_BY_ROOK,
+            threat_by_king: pieces::THREAT_BY_KING,
+        }
+    }
+}
+
+/// Bumped on every [`EvalParams::set_active`]; threads compare it with
+/// the generation of their cached handle.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+lazy_static::lazy_static! {
+    static ref ACTIVE: RwLock<Arc<EvalParams>> = RwLock::new(Arc::default());
+}
+
+thread_local! {
+    /// This thread's handle to the active parameters, and the
+    /// generation it was taken at.
+    static LOCAL: RefCell<(u64, Arc<EvalParams>)> = {
+        let active = ACTIVE.read().unwrap();
+        RefCell::new((GENERATION.load(Ordering::Acquire), active.clone()))
+    };
+}
+
+impl EvalParams {
+    /// Parse TOML.
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Parse JSON.
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("EvalParams serializes to TOML")
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("EvalParams serializes to JSON")
+    }
+
+    /// Read a `.json` file as JSON and anything else as TOML.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        if is_json(path) {
+            Self::from_json(&text)
+        } else {
+            Self::from_toml(&text)
+        }
+        .map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Write to `path`, as JSON for a `.json` file and TOML otherwise.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let text = if is_json(path) {
+            self.to_json()
+        } else {
+            self.to_toml()
+        };
+        std::fs::write(path, text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Set the single weight at the dotted `path` — a field name per
+    /// level, an index into arrays — to the integer `value`: for
+    /// example `pawns.doubled.mg`, `pieces.trapped_rook.eg`,
+    /// `king_safety.safety_table.10`.
+    pub fn set(&mut self, path: &str, value: &str) -> Result<(), String> {
+        let value: i64 = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("{path}: not an integer: {value}"))?;
+        let mut tree = serde_json::to_value(&*self).expect("EvalParams serializes to JSON");
+        let mut node = &mut tree;
+        for key in path.split('.') {
+            node = match node {
+                Value::Object(fields) => fields.get_mut(key),
+                Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get_mut(i)),
+                _ => None,
+            }
+            .ok_or_else(|| format!("no evaluation parameter {path}"))?;
+        }
+        if !node.is_i64() {
+            return Err(format!("{path} is not a single weight"));
+        }
+        *node = value.into();
+        *self = serde_json::from_value(tree).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// The parameters `Board::evaluate` currently uses.
+    pub fn active() -> Arc<EvalParams> {
+        ACTIVE.read().unwrap().clone()
+    }
+
+    /// Make `params` the ones `Board::evaluate` uses, in every thread.
+    pub fn set_active(params: EvalParams) {
+        let mut active = ACTIVE.write().unwrap();
+        *active = Arc::new(params);
+        GENERATION.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Run `f` with this thread's handle to the active parameters and
+    /// its generation, refreshing the handle first if it's stale.
+    pub(crate) fn with_active<R>(f: impl FnOnce(&EvalParams, u64) -> R) -> R {
+        LOCAL.with(|local| {
+            let generation = GENERATION.load(Ordering::Acquire);
+            if local.borrow().0 != generation {
+                // Read under the lock the generation was bumped in, so
+                // the handle and the number always agree.
+                let active = ACTIVE.read().unwrap();
+                *local.borrow_mut() = (GENERATION.load(Ordering::Acquire), active.clone());
+            }
+            let local = local.borrow();
+            f(&local.1, local.0)
+        })
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
@@ -0,0 +1,173 @@
+//! A breakdown of the static evaluation, term by term.
+//!
+//! [`Board::evaluate_trace`](crate::board::Board::evaluate_trace)
+//! reports every term for each side as a middlegame/endgame pair, plus
+//! the game phase they're blended at. The trace *is* the evaluation —
+//! `evaluate()` returns its `score` — so what it shows can never drift
+//! from what the search sees.
+//!
+//! `Display` prints a table in the style of Stockfish's `eval`
+//! command, which the UCI `eval` extension sends back.
+//!
+//! There is no piece-square table term, because the evaluation has
+//! none. Where a piece stands counts only through [`EvalTerm::Center`]
+//! and the mobility, king safety, pawn and piece terms.
+
+use crate::endgame::SCALE_NORMAL;
+use crate::evaluation::{Tapered, MAX_PHASE};
+use crate::types::Color;
+use std::fmt;
+
+/// One line of the evaluation. The discriminant indexes
+/// [`EvalTrace::terms`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EvalTerm {
+    Material,
+    BishopPair,
+    /// Pieces on and around the four centre squares: the evaluation's
+    /// only flat placement bonus, in place of piece-square tables.
+    Center,
+    Mobility,
+    CastlingRights,
+    Pawns,
+    KingSafety,
+    Pieces,
+    Check,
+    Endgame,
+}
+
+impl EvalTerm {
+    /// Every term, in table order.
+    pub const ALL: [EvalTerm; 10] = [
+        EvalTerm::Material,
+        EvalTerm::BishopPair,
+        EvalTerm::Center,
+        EvalTerm::Mobility,
+        EvalTerm::CastlingRights,
+        EvalTerm::Pawns,
+        EvalTerm::KingSafety,
+        EvalTerm::Pieces,
+        EvalTerm::Check,
+        EvalTerm::Endgame,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EvalTerm::Material => "Material",
+            EvalTerm::BishopPair => "Bishop pair",
+            EvalTerm::Center => "Center",
+            EvalTerm::Mobility => "Mobility",
+            EvalTerm::CastlingRights => "Castling rights",
+            EvalTerm::Pawns => "Pawns",
+            EvalTerm::KingSafety => "King safety",
+            EvalTerm::Pieces => "Pieces",
+            EvalTerm::Check => "Check",
+            EvalTerm::Endgame => "Endgame",
+        }
+    }
+}
+
+/// The evaluation of one position, split into its terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalTrace {
+    /// Each term for each side, from that side's point of view:
+    /// `terms[term as usize][color.idx()]`.
+    pub terms: [[Tapered; 2]; EvalTerm::ALL.len()],
+    /// The phase the terms were blended at, 0 (endgame) to `MAX_PHASE`.
+    pub phase: i32,
+    /// How much of the endgame half counts, out of
+    /// [`SCALE_NORMAL`]: less in drawish endgames, nothing at all in
+    /// dead draws. See [`crate::endgame`].
+    pub scale: i32,
+    /// The final score from White's point of view: each term's White
+    /// minus Black, tapered by `phase` and scaled by `scale`, summed.
+    pub score: i64,
+}
+
+impl EvalTrace {
+    pub fn new(terms: [[Tapered; 2]; EvalTerm::ALL.len()], phase: i32, scale: i32) -> Self {
+        let score = terms
+            .iter()
+            .map(|[white, black]| (*white - *black).taper_scaled(phase, scale))
+            .sum();
+        Self {
+            terms,
+            phase,
+            scale,
+            score,
+        }
+    }
+
+    /// `term` for `color`, from `color`'s point of view.
+    pub fn side(&self, term: EvalTerm, color: Color) -> Tapered {
+        self.terms[term as usize][color.idx()]
+    }
+
+    /// `term` from White's point of view: White's share minus Black's.
+    pub fn net(&self, term: EvalTerm) -> Tapered {
+        let [white, black] = self.terms[term as usize];
+        white - black
+    }
+
+    /// `term`'s contribution to `score`, after tapering and scaling.
+    pub fn blended(&self, term: EvalTerm) -> i64 {
+        self.net(term).taper_scaled(self.phase, self.scale)
+    }
+
+    /// Every term summed, before tapering.
+    pub fn total(&self) -> Tapered {
+        EvalTerm::ALL
+            .iter()
+            .fold(Tapered::default(), |sum, &term| sum + self.net(term))
+    }
+}
+
+impl fmt::Display for EvalTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const RULE: &str = "-----------------+-------------+-------------+-------------+-------";
+        let pair = |t: Tapered| format!("{:>5} {:>5}", t.mg, t.eg);
+
+        writeln!(
+            f,
+            "{:>16} | {:^11} | {:^11} | {:^11} | {:>5}",
+            "Term", "White", "Black", "Total", "Blend"
+        )?;
+        writeln!(
+            f,
+            "{:>16} | {:>5} {:>5} | {:>5} {:>5} | {:>5} {:>5} |",
+            "", "MG", "EG", "MG", "EG", "MG", "EG"
+        )?;
+        writeln!(f, "{RULE}")?;
+        for term in EvalTerm::ALL {
+            writeln!(
+                f,
+                "{:>16} | {} | {} | {} | {:>5}",
+                term.name(),
+                pair(self.side(term, Color::White)),
+                pair(self.side(term, Color::Black)),
+                pair(self.net(term)),
+                self.blended(term)
+            )?;
+        }
+        writeln!(f, "{RULE}")?;
+        writeln!(
+            f,
+            "{:>16} | {:>11} | {:>11} | {} | {:>5}",
+            "Total",
+            "",
+            "",
+            pair(self.total()),
+            self.score
+        )?;
+        writeln!(f)?;
+        writeln!(f, "Phase: {}/{MAX_PHASE}", self.phase)?;
+        if self.scale != SCALE_NORMAL {
+            writeln!(f, "Endgame scale: {}/{SCALE_NORMAL}", self.scale)?;
+        }
+        write!(
+            f,
+            "Final evaluation: {} cp (White's point of view)",
+            self.score
+        )
+    }
+}
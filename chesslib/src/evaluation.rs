@@ -1,18 +1,99 @@
 // Material values (PAWN_VALUE etc.) live in types.rs alongside Piece so
 // they have a single home shared with Piece::material_value().
 
-/// Positional bonuses in centipawns
-const CENTER_CONTROL_BONUS: i64 = 10; // Bonus for controlling center squares
-const CHECK_BONUS: i64 = 50; // Bonus for giving check
-const BISHOP_PAIR_BONUS: i64 = 25; // Bonus for having both bishops
-const CASTLED_BONUS: i64 = 75; // Bonus for having castled (king safety)
-const CASTLING_RIGHTS_BONUS: i64 = 20; // Bonus for each available castling right
-const MOBILITY_BONUS: i64 = 5; // Bonus per available move for piece mobility
+/// Positional bonuses in centipawns: the defaults of
+/// [`PositionalParams`](crate::eval_params::PositionalParams)
+pub(crate) const CENTER_CONTROL_BONUS: i64 = 10; // Bonus for controlling center squares
+pub(crate) const CHECK_BONUS: i64 = 50; // Bonus for giving check
+pub(crate) const BISHOP_PAIR_BONUS: i64 = 25; // Bonus for having both bishops
+pub(crate) const CASTLING_RIGHTS_BONUS: i64 = 20; // Bonus for each available castling right
+pub(crate) const MOBILITY_BONUS: i64 = 5; // Bonus per available move for piece mobility
+
+/// Game phase of the starting position: knights and bishops count 1,
+/// rooks 2, queens 4. See [`Board::game_phase`].
+pub const MAX_PHASE: i32 = 24;
 
 use crate::board::Board;
+use crate::endgame::{self, MaterialKey, SCALE_DRAW, SCALE_NORMAL};
+use crate::eval_params::{EvalParams, MaterialParams, PositionalParams};
+use crate::eval_trace::{EvalTerm, EvalTrace};
 use crate::move_generation::{bishop_moves, knight_legal_moves, rook_moves};
-use crate::types::{BISHOP_VALUE, KNIGHT_VALUE, PAWN_VALUE, QUEEN_VALUE, ROOK_VALUE};
-use crate::Square;
+use crate::types::{Color, PieceType};
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A middlegame/endgame pair of scores for one evaluation term. The
+/// two are blended by [`Tapered::taper`] according to how much
+/// material is left, so a term can matter more in one phase than the
+/// other (a passed pawn is worth far more once the pieces are off).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tapered {
+    pub mg: i64,
+    pub eg: i64,
+}
+
+impl Tapered {
+    pub const fn new(mg: i64, eg: i64) -> Self {
+        Self { mg, eg }
+    }
+
+    /// Interpolate between `mg` (at `MAX_PHASE`) and `eg` (at 0).
+    pub fn taper(self, phase: i32) -> i64 {
+        self.taper_scaled(phase, SCALE_NORMAL)
+    }
+
+    /// [`Tapered::taper`] with the endgame half scaled by
+    /// `scale / SCALE_NORMAL`; a scale of [`SCALE_DRAW`] is a dead draw
+    /// and gives 0. See [`crate::endgame`].
+    pub fn taper_scaled(self, phase: i32, scale: i32) -> i64 {
+        if scale == SCALE_DRAW {
+            return 0;
+        }
+        let phase = phase.clamp(0, MAX_PHASE) as i64;
+        let eg = self.eg * (MAX_PHASE as i64 - phase) * scale as i64 / SCALE_NORMAL as i64;
+        (self.mg * phase + eg) / MAX_PHASE as i64
+    }
+}
+
+impl Add for Tapered {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl Sub for Tapered {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Neg for Tapered {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.mg, -self.eg)
+    }
+}
+
+impl Mul<i64> for Tapered {
+    type Output = Self;
+    fn mul(self, factor: i64) -> Self {
+        Self::new(self.mg * factor, self.eg * factor)
+    }
+}
+
+impl AddAssign for Tapered {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Tapered {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
 
 impl Board {
     /// Evaluates the current position from White's perspective.
@@ -23,177 +104,172 @@ impl Board {
     /// search couldn't tell mate-in-1 from mate-in-N (both would score the
     /// same). The search instead checks `moves.is_empty()` at every node
     /// and returns a ply-aware mate score there.
+    ///
+    /// With the `nnue` feature and the NNUE evaluator selected, this is
+    /// the network's score instead (see `nnue.rs`).
     pub fn evaluate(&self) -> i64 {
-        let mut score = 0;
-
-        // Material evaluation
-        score += self.evaluate_material();
-
-        // Position evaluation (with reduced weights)
-        score += self.evaluate_position();
-
-        // Check bonus
-        if self.black_king_in_check {
-            score += CHECK_BONUS;
+        #[cfg(feature = "nnue")]
+        if let Some(score) = self.nnue_evaluate() {
+            return score;
         }
-        if self.white_king_in_check {
-            score -= CHECK_BONUS;
-        }
-
-        score
+        EvalParams::with_active(|params, generation| self.trace(params, Some(generation)).score)
     }
 
-    /// Evaluates material balance
-    fn evaluate_material(&self) -> i64 {
-        let mut score = 0;
+    /// [`Board::evaluate`] under `params` instead of the active
+    /// parameters.
+    pub fn evaluate_with(&self, params: &EvalParams) -> i64 {
+        self.trace(params, None).score
+    }
 
-        // Count white pieces
-        score += (self.white_pawns().count_ones() * PAWN_VALUE as u32) as i64;
-        score += (self.white_knights().count_ones() * KNIGHT_VALUE as u32) as i64;
-        score += (self.white_bishops().count_ones() * BISHOP_VALUE as u32) as i64;
-        score += (self.white_rooks().count_ones() * ROOK_VALUE as u32) as i64;
-        score += (self.white_queen().count_ones() * QUEEN_VALUE as u32) as i64;
+    /// Every term of [`Board::evaluate`] for each side, middlegame and
+    /// endgame, with the phase they were blended at. `score` is exactly
+    /// what `evaluate()` returns.
+    pub fn evaluate_trace(&self) -> EvalTrace {
+        EvalParams::with_active(|params, generation| self.trace(params, Some(generation)))
+    }
 
-        // Subtract black pieces
-        score -= (self.black_pawns().count_ones() * PAWN_VALUE as u32) as i64;
-        score -= (self.black_knights().count_ones() * KNIGHT_VALUE as u32) as i64;
-        score -= (self.black_bishops().count_ones() * BISHOP_VALUE as u32) as i64;
-        score -= (self.black_rooks().count_ones() * ROOK_VALUE as u32) as i64;
-        score -= (self.black_queen().count_ones() * QUEEN_VALUE as u32) as i64;
+    /// [`Board::evaluate_trace`] under `params`.
+    pub fn evaluate_trace_with(&self, params: &EvalParams) -> EvalTrace {
+        self.trace(params, None)
+    }
 
-        // Bishop pair bonus
-        if self.white_bishops().count_ones() >= 2 {
-            score += BISHOP_PAIR_BONUS;
-            // Add a bonus for check (50 centipawns)
-        }
-        if self.black_bishops().count_ones() >= 2 {
-            score -= BISHOP_PAIR_BONUS;
+    /// The evaluation under `params`. `generation` is the active
+    /// parameters' generation when `params` are the active ones, which
+    /// lets the pawn table cache their results; `None` bypasses it.
+    fn trace(&self, params: &EvalParams, generation: Option<u64>) -> EvalTrace {
+        let mut terms = [[Tapered::default(); 2]; EvalTerm::ALL.len()];
+        let pawns = self.pawn_scores_with(&params.pawns, generation);
+        let positional = &params.positional;
+        // Terms with no middlegame/endgame split count the same in both
+        // halves.
+        let flat = |v: i64| Tapered::new(v, v);
+        for color in [Color::White, Color::Black] {
+            let i = color.idx();
+            terms[EvalTerm::Material as usize][i] = flat(self.material(color, &params.material));
+            terms[EvalTerm::BishopPair as usize][i] = flat(self.bishop_pair(color, positional));
+            terms[EvalTerm::Center as usize][i] = flat(self.center_control(color, positional));
+            terms[EvalTerm::Mobility as usize][i] = flat(self.mobility(color, positional));
+            terms[EvalTerm::CastlingRights as usize][i] =
+                flat(self.castling_rights(color, positional));
+            terms[EvalTerm::Pawns as usize][i] = pawns[i];
+            terms[EvalTerm::KingSafety as usize][i] =
+                self.king_safety_with(color, &params.king_safety);
+            terms[EvalTerm::Pieces as usize][i] = self.piece_terms_with(color, &params.pieces);
+            terms[EvalTerm::Check as usize][i] = flat(self.check_bonus(color, positional));
         }
 
-        score
+        let key = MaterialKey::of(self);
+        if let Some((color, bonus)) = endgame::mating_bonus(self, key) {
+            terms[EvalTerm::Endgame as usize][color.idx()] = flat(bonus);
+        }
+        let eg: i64 = terms.iter().map(|[white, black]| white.eg - black.eg).sum();
+        let strong = if eg >= 0 { Color::White } else { Color::Black };
+        EvalTrace::new(
+            terms,
+            self.game_phase(),
+            endgame::scale_factor(self, key, strong),
+        )
     }
 
-    /// Evaluates positional factors
-    fn evaluate_position(&self) -> i64 {
-        let mut score = 0;
-
-        // Center control (e4, e5, d4, d5)
-        let center_squares = 0x0000001818000000u64;
-        score += ((self.any_white() & center_squares).count_ones() as i64) * CENTER_CONTROL_BONUS;
-        score -= ((self.any_black() & center_squares).count_ones() as i64) * CENTER_CONTROL_BONUS;
-
-        // Extended center control (e3, e6, d3, d6, c4, c5, f4, f5)
-        let extended_center = 0x00003C3C3C3C0000u64;
-        score +=
-            ((self.any_white() & extended_center).count_ones() as i64) * (CENTER_CONTROL_BONUS / 2);
-        score -=
-            ((self.any_black() & extended_center).count_ones() as i64) * (CENTER_CONTROL_BONUS / 2);
-
-        // Mobility evaluation for pieces
-        score += self.evaluate_piece_mobility();
-
-        // Castling evaluation
-        score += self.evaluate_castling();
-
-        score
+    /// How much non-pawn material is left, from `MAX_PHASE` (all of
+    /// it: the opening) down to 0 (kings and pawns only). Promotions
+    /// can push the raw count past `MAX_PHASE`, so it's capped there.
+    pub fn game_phase(&self) -> i32 {
+        let minors = (self.pieces[PieceType::Knight.idx()] | self.pieces[PieceType::Bishop.idx()])
+            .count_ones() as i32;
+        let rooks = self.pieces[PieceType::Rook.idx()].count_ones() as i32;
+        let queens = self.pieces[PieceType::Queen.idx()].count_ones() as i32;
+        (minors + 2 * rooks + 4 * queens).min(MAX_PHASE)
     }
 
-    /// Evaluates castling-related factors including both castled position and available rights
-    fn evaluate_castling(&self) -> i64 {
-        let mut score = 0;
-
-        // Evaluate castling rights
-        if self.white_kingside_castle_rights {
-            score += CASTLING_RIGHTS_BONUS;
-        }
-        if self.white_queenside_castle_rights {
-            score += CASTLING_RIGHTS_BONUS;
-        }
-        if self.black_kingside_castle_rights {
-            score -= CASTLING_RIGHTS_BONUS;
-        }
-        if self.black_queenside_castle_rights {
-            score -= CASTLING_RIGHTS_BONUS;
-        }
+    /// Material of `color`'s pawns and pieces
+    fn material(&self, color: Color, values: &MaterialParams) -> i64 {
+        [
+            (PieceType::Pawn, values.pawn),
+            (PieceType::Knight, values.knight),
+            (PieceType::Bishop, values.bishop),
+            (PieceType::Rook, values.rook),
+            (PieceType::Queen, values.queen),
+        ]
+        .iter()
+        .map(|&(piece, value)| self.piece_bb(piece, color).count_ones() as i64 * value)
+        .sum()
+    }
 
-        // Check if kings have moved to typical castled positions
-        let white_king_kingside = self.white_king() & Square::G1.to_bitboard(); // g1
-        let white_king_queenside = self.white_king() & Square::C1.to_bitboard(); // c1
-        let black_king_kingside = self.black_king() & Square::G8.to_bitboard(); // g8
-        let black_king_queenside = self.black_king() & Square::C8.to_bitboard(); // c8
-
-        // Evaluate actual castled positions
-        // We check if the king is in a castled position AND we've lost the corresponding castling right
-        // This ensures we're detecting actual castling rather than just a king walk
-        if (white_king_kingside != 0 && !self.white_kingside_castle_rights)
-            || (white_king_queenside != 0 && !self.white_queenside_castle_rights)
-        {
-            score += CASTLED_BONUS;
+    fn bishop_pair(&self, color: Color, params: &PositionalParams) -> i64 {
+        if self.piece_bb(PieceType::Bishop, color).count_ones() >= 2 {
+            params.bishop_pair
+        } else {
+            0
         }
-        if (black_king_kingside != 0 && !self.black_kingside_castle_rights)
-            || (black_king_queenside != 0 && !self.black_queenside_castle_rights)
-        {
-            score -= CASTLED_BONUS;
-        }
-
-        score
     }
 
-    /// Evaluates mobility (number of legal moves) for pieces
-    fn evaluate_piece_mobility(&self) -> i64 {
-        let mut score = 0;
+    /// Pieces of `color` on the centre (e4, e5, d4, d5) and, at half
+    /// weight, the extended centre (c3-f6)
+    fn center_control(&self, color: Color, params: &PositionalParams) -> i64 {
+        let center_squares = 0x0000001818000000u64;
+        let extended_center = 0x00003C3C3C3C0000u64;
+        let own = self.colors[color.idx()];
+        (own & center_squares).count_ones() as i64 * params.center_control
+            + (own & extended_center).count_ones() as i64 * (params.center_control / 2)
+    }
 
-        // Knights
-        let mut white_knights = self.white_knights();
-        while white_knights != 0 {
-            let pos = white_knights.trailing_zeros() as u8;
-            let moves = knight_legal_moves(1u64 << pos, self.any_white());
-            score += (moves.count_ones() as i64) * MOBILITY_BONUS;
-            white_knights &= white_knights - 1;
-        }
+    /// Keeping the option to castle is worth something until it's used
+    /// (castling itself pays off through king safety)
+    fn castling_rights(&self, color: Color, params: &PositionalParams) -> i64 {
+        let (kingside, queenside) = match color {
+            Color::White => (
+                self.white_kingside_castle_rights,
+                self.white_queenside_castle_rights,
+            ),
+            Color::Black => (
+                self.black_kingside_castle_rights,
+                self.black_queenside_castle_rights,
+            ),
+        };
+        (kingside as i64 + queenside as i64) * params.castling_rights
+    }
 
-        let mut black_knights = self.black_knights();
-        while black_knights != 0 {
-            let pos = black_knights.trailing_zeros() as u8;
-            let moves = knight_legal_moves(1u64 << pos, self.any_black());
-            score -= (moves.count_ones() as i64) * MOBILITY_BONUS;
-            black_knights &= black_knights - 1;
+    /// `color` has the enemy king in check
+    fn check_bonus(&self, color: Color, params: &PositionalParams) -> i64 {
+        let in_check = match color {
+            Color::White => self.black_king_in_check,
+            Color::Black => self.white_king_in_check,
+        };
+        if in_check {
+            params.check
+        } else {
+            0
         }
+    }
 
-        // Bishops
-        let mut white_bishops = self.white_bishops();
-        while white_bishops != 0 {
-            let pos = white_bishops.trailing_zeros() as u8;
-            let moves = bishop_moves(1u64 << pos, self.any_white(), self.any_black());
-            score += (moves.count_ones() as i64) * MOBILITY_BONUS;
-            white_bishops &= white_bishops - 1;
-        }
+    /// Squares `color`'s knights, bishops and rooks can move to
+    fn mobility(&self, color: Color, params: &PositionalParams) -> i64 {
+        let own = self.colors[color.idx()];
+        let enemy = self.colors[color.opponent().idx()];
+        let mut moves = 0;
 
-        let mut black_bishops = self.black_bishops();
-        while black_bishops != 0 {
-            let pos = black_bishops.trailing_zeros() as u8;
-            let moves = bishop_moves(1u64 << pos, self.any_black(), self.any_white());
-            score -= (moves.count_ones() as i64) * MOBILITY_BONUS;
-            black_bishops &= black_bishops - 1;
+        let mut knights = self.piece_bb(PieceType::Knight, color);
+        while knights != 0 {
+            let knight = knights & knights.wrapping_neg();
+            moves += knight_legal_moves(knight, own).count_ones();
+            knights &= knights - 1;
         }
 
-        // Rooks
-        let mut white_rooks = self.white_rooks();
-        while white_rooks != 0 {
-            let pos = white_rooks.trailing_zeros() as u8;
-            let moves = rook_moves(1u64 << pos, self.any_white(), self.any_black());
-            score += (moves.count_ones() as i64) * MOBILITY_BONUS;
-            white_rooks &= white_rooks - 1;
+        let mut bishops = self.piece_bb(PieceType::Bishop, color);
+        while bishops != 0 {
+            let bishop = bishops & bishops.wrapping_neg();
+            moves += bishop_moves(bishop, own, enemy).count_ones();
+            bishops &= bishops - 1;
         }
 
-        let mut black_rooks = self.black_rooks();
-        while black_rooks != 0 {
-            let pos = black_rooks.trailing_zeros() as u8;
-            let moves = rook_moves(1u64 << pos, self.any_black(), self.any_white());
-            score -= (moves.count_ones() as i64) * MOBILITY_BONUS;
-            black_rooks &= black_rooks - 1;
+        let mut rooks = self.piece_bb(PieceType::Rook, color);
+        while rooks != 0 {
+            let rook = rooks & rooks.wrapping_neg();
+            moves += rook_moves(rook, own, enemy).count_ones();
+            rooks &= rooks - 1;
         }
 
-        score
+        moves as i64 * params.mobility
     }
 }
@@ -188,6 +188,10 @@ pub fn load_fen(fen: &str) -> Result<Board, &'static str> {
     board.rebuild_piece_map();
     board.update_composite_bitboards();
     board.update_check_state();
+    board.hash = board.compute_hash();
+    board.pawn_hash = board.compute_pawn_hash();
+    #[cfg(feature = "nnue")]
+    board.refresh_accumulator();
 
     Ok(board)
 }
@@ -0,0 +1,180 @@
+//! King safety: the pawn cover in front of each king, enemy pawns
+//! storming towards it, open files beside it, and how heavily the
+//! enemy pieces bear on the squares around it.
+//!
+//! Nothing here rewards castling as such. A castled king scores well
+//! because of what castling gets it: an intact pawn shield, no open
+//! files, and a zone the enemy pieces can't easily reach. A king left
+//! in the centre loses the same points as soon as its central pawns
+//! advance or get exchanged.
+//!
+//! Attacks are counted as "attack units": every enemy knight, bishop,
+//! rook or queen that hits the king zone adds its weight for each zone
+//! square it hits. The total indexes a safety table, which grows
+//! slowly at first and steeply later, so a lone attacker barely
+//! registers while a coordinated assault dominates the evaluation.
+//!
+//! All terms are middlegame-heavy [`Tapered`] scores; with the queens
+//! and rooks off, the king is meant to come out and fight.
+//!
+//! The constants here are the default weights; the evaluation reads
+//! them from [`KingSafetyParams`].
+
+use crate::board::Board;
+use crate::eval_params::{EvalParams, KingSafetyParams};
+use crate::evaluation::Tapered;
+use crate::move_generation::{bishop_moves, king_moves, knight_moves, rook_moves};
+use crate::pawns::{forward_ranks, relative_rank, stop_square, FILE_A};
+use crate::types::{Color, PieceType};
+
+/// Shield pawn on the king's file or a neighbouring one, by how many
+/// ranks it stands in front of the king (1 = directly ahead).
+pub(crate) const SHIELD: [i64; 4] = [0, 30, 15, 5];
+/// One of those files has no friendly pawn within three ranks.
+pub(crate) const SHIELD_MISSING: i64 = -25;
+/// The closest enemy pawn storming one of those files, by how many
+/// ranks it is from the king. Halved if a friendly pawn blocks it.
+pub(crate) const STORM: [i64; 8] = [0, 10, 35, 20, 10, 5, 0, 0];
+/// A file next to (or on) the king with no friendly pawn on it.
+pub(crate) const SEMI_OPEN_FILE: i64 = -12;
+/// ... and no enemy pawn either.
+pub(crate) const OPEN_FILE: i64 = -25;
+
+/// Where the king stands, by file. The centre files are the first to
+/// open, so a king left on them is a target; tucked away on the wing
+/// it is not.
+pub(crate) const KING_FILE: [i64; 8] = [15, 25, 10, -5, -10, -5, 25, 15];
+
+/// Attack units per king-zone square hit, by attacker type. Indexed by
+/// `PieceType::idx()`; pawns and kings don't count.
+pub(crate) const ATTACK_WEIGHT: [i64; 6] = [0, 3, 2, 2, 5, 0];
+
+/// Penalty by attack units. Flat for the first few units, then
+/// roughly quadratic, then capped.
+pub(crate) const SAFETY_TABLE: [i64; 64] = [
+    0, 0, 1, 2, 3, 5, 7, 9, 12, 15, 18, 22, 26, 30, 35, 39, 44, 50, 56, 62, 68, 75, 82, 85, 89, 97,
+    105, 113, 122, 131, 140, 150, 169, 180, 191, 202, 213, 225, 237, 248, 260, 272, 283, 295, 307,
+    319, 330, 342, 354, 366, 377, 389, 401, 412, 424, 436, 448, 459, 471, 483, 494, 500, 500, 500,
+];
+
+impl Board {
+    /// King safety from White's point of view, before tapering.
+    pub fn evaluate_king_safety(&self) -> Tapered {
+        self.king_safety(Color::White) - self.king_safety(Color::Black)
+    }
+
+    /// Safety of `color`'s king, from `color`'s point of view.
+    pub fn king_safety(&self, color: Color) -> Tapered {
+        EvalParams::with_active(|params, _| self.king_safety_with(color, &params.king_safety))
+    }
+
+    /// Safety of `color`'s king under `params`.
+    pub(crate) fn king_safety_with(&self, color: Color, params: &KingSafetyParams) -> Tapered {
+        let king = self.piece_bb(PieceType::King, color);
+        if king == 0 {
+            return Tapered::default();
+        }
+        let sq = king.trailing_zeros() as u8;
+        let cover = params.king_file[(sq % 8) as usize] + self.pawn_cover(color, sq, params);
+        let table = &params.safety_table;
+        let danger = match table.len() {
+            0 => 0,
+            len => table[self.attack_units(color, sq, params).min(len - 1)],
+        };
+        Tapered::new(cover - danger, -danger / 4)
+    }
+
+    /// Shield, storm and open-file terms over the king's file and its
+    /// neighbours.
+    fn pawn_cover(&self, color: Color, king_sq: u8, params: &KingSafetyParams) -> i64 {
+        let pawns = self.pieces[PieceType::Pawn.idx()];
+        let own = pawns & self.colors[color.idx()];
+        let enemy = pawns & self.colors[color.opponent().idx()];
+        let king_rank = relative_rank(color, king_sq);
+        let ahead = forward_ranks(color, king_sq);
+        let king_file = king_sq % 8;
+
+        let mut score = 0;
+        for file in king_file.saturating_sub(1)..=(king_file + 1).min(7) {
+            let file_mask = FILE_A << file;
+
+            match closest(color, own & ahead & file_mask) {
+                Some(pawn) if relative_rank(color, pawn) - king_rank < params.shield.len() => {
+                    score += params.shield[relative_rank(color, pawn) - king_rank];
+                }
+                _ => score += params.shield_missing,
+            }
+
+            if let Some(stormer) = closest(color, enemy & ahead & file_mask) {
+                let distance = relative_rank(color, stormer) - king_rank;
+                let blocked = own & stop_square(color.opponent(), stormer) != 0;
+                let storm = params.storm[distance.min(params.storm.len() - 1)];
+                score -= if blocked { storm / 2 } else { storm };
+            }
+
+            if own & file_mask == 0 {
+                score += if enemy & file_mask == 0 {
+                    params.open_file
+                } else {
+                    params.semi_open_file
+                };
+            }
+        }
+        score
+    }
+
+    /// Attack units the enemy pieces put on `color`'s king zone: the
+    /// king's square, the squares around it, and the row beyond those
+    /// towards the enemy. Counted only with two or more attackers, so
+    /// a single piece eyeing the king isn't mistaken for an attack.
+    fn attack_units(&self, color: Color, king_sq: u8, params: &KingSafetyParams) -> usize {
+        let king = 1u64 << king_sq;
+        let ring = king | king_moves(king);
+        let zone = ring
+            | match color {
+                Color::White => ring << 8,
+                Color::Black => ring >> 8,
+            };
+        let occupied = self.colors[0] | self.colors[1];
+        let enemy = self.colors[color.opponent().idx()];
+
+        let mut attackers = 0;
+        let mut units = 0;
+        for piece in [
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+        ] {
+            let mut bits = self.pieces[piece.idx()] & enemy;
+            while bits != 0 {
+                let from = bits & bits.wrapping_neg();
+                bits &= bits - 1;
+                let attacks = match piece {
+                    PieceType::Knight => knight_moves(from),
+                    PieceType::Bishop => bishop_moves(from, 0, occupied),
+                    PieceType::Rook => rook_moves(from, 0, occupied),
+                    _ => bishop_moves(from, 0, occupied) | rook_moves(from, 0, occupied),
+                } & zone;
+                if attacks != 0 {
+                    attackers += 1;
+                    units += params.attack_weight[piece.idx()] * attacks.count_ones() as i64;
+                }
+            }
+        }
+        if attackers < 2 {
+            0
+        } else {
+            units.max(0) as usize
+        }
+    }
+}
+
+/// The pawn in `pawns` nearest `color`'s back rank.
+fn closest(color: Color, pawns: u64) -> Option<u8> {
+    match (pawns, color) {
+        (0, _) => None,
+        (_, Color::White) => Some(pawns.trailing_zeros() as u8),
+        (_, Color::Black) => Some(63 - pawns.leading_zeros() as u8),
+    }
+}
@@ -0,0 +1,211 @@
+//! KPK bitbase: whether king and pawn beat a lone king, for every
+//! position, with best play.
+//!
+//! One bit per position, generated by retrograde analysis the first
+//! time it's needed (or up front with [`init`]; the UCI loop does that
+//! at startup). Positions are normalised so the pawn is White's and on
+//! files a–d, which leaves 2 × 24 × 64 × 64 = 196 608 of them: side
+//! to move, pawn square, and the two king squares. That's 24 KB, and
+//! about a dozen passes over it to solve.
+//!
+//! The analysis starts from what is known outright:
+//!
+//! - illegal positions (kings touching, a piece on the pawn's square,
+//!   the black king in check with White to move);
+//! - wins: White to move with the pawn on the seventh and a safe queening
+//!   square;
+//! - draws: Black to move and stalemated, or able to take an
+//!   undefended pawn.
+//!
+//! and then repeatedly resolves the rest from their successors: a
+//! position is a win with White to move if any move reaches a win, and
+//! with Black to move if every move does. Whatever is still open when
+//! nothing changes is a draw. Promotions that don't win at once are
+//! never tried: the new queen (or rook) would be lost on the spot.
+
+use crate::board::Board;
+use crate::move_generation::king_moves;
+use crate::types::{Color, PieceType};
+
+const MAX_INDEX: usize = 2 * 24 * 64 * 64;
+
+// Analysis states, as bit flags so a position's successors can be
+// or-ed together.
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+lazy_static::lazy_static! {
+    static ref BITBASE: Vec<u64> = generate();
+}
+
+/// Build the bitbase now rather than on the first probe.
+pub fn init() {
+    lazy_static::initialize(&BITBASE);
+}
+
+/// For a position with nothing but the kings and one pawn: does the
+/// side with the pawn win? `None` for any other material.
+pub fn probe(board: &Board) -> Option<bool> {
+    let occupied = board.colors[0] | board.colors[1];
+    let pawns = board.pieces[PieceType::Pawn.idx()];
+    if occupied.count_ones() != 3 || pawns.count_ones() != 1 {
+        return None;
+    }
+    let strong = if pawns & board.colors[Color::White.idx()] != 0 {
+        Color::White
+    } else {
+        Color::Black
+    };
+    let square = |bits: u64| bits.trailing_zeros() as u8;
+    let mut strong_king = square(board.piece_bb(PieceType::King, strong));
+    let mut weak_king = square(board.piece_bb(PieceType::King, strong.opponent()));
+    let mut pawn = square(pawns);
+    // Seen from the pawn's side, with the pawn on the queenside.
+    if strong == Color::Black {
+        (strong_king, weak_king, pawn) = (strong_king ^ 56, weak_king ^ 56, pawn ^ 56);
+    }
+    if pawn % 8 >= 4 {
+        (strong_king, weak_king, pawn) = (strong_king ^ 7, weak_king ^ 7, pawn ^ 7);
+    }
+    let stm = usize::from(board.side_to_move != strong);
+    let idx = index(stm, weak_king, strong_king, pawn);
+    Some(BITBASE[idx / 64] & (1 << (idx % 64)) != 0)
+}
+
+/// `stm` is 0 with White (the pawn's side) to move. The pawn is on
+/// files a–d, ranks 2–7.
+fn index(stm: usize, black_king: u8, white_king: u8, pawn: u8) -> usize {
+    white_king as usize
+        | (black_king as usize) << 6
+        | stm << 12
+        | (pawn as usize % 8) << 13
+        | (6 - pawn as usize / 8) << 15
+}
+
+fn distance(a: u8, b: u8) -> u8 {
+    let files = (a % 8).abs_diff(b % 8);
+    let ranks = (a / 8).abs_diff(b / 8);
+    files.max(ranks)
+}
+
+fn king_attacks(sq: u8) -> u64 {
+    king_moves(1 << sq)
+}
+
+/// Squares a white pawn on `pawn` attacks.
+fn pawn_attacks(pawn: u8) -> u64 {
+    let bit = 1u64 << pawn;
+    let not_a = !0x0101_0101_0101_0101u64;
+    let not_h = !0x8080_8080_8080_8080u64;
+    ((bit & not_a) << 7) | ((bit & not_h) << 9)
+}
+
+struct Position {
+    stm: usize,
+    white_king: u8,
+    black_king: u8,
+    pawn: u8,
+}
+
+impl Position {
+    fn decode(idx: usize) -> Self {
+        let file = (idx >> 13) & 3;
+        let rank = 6 - ((idx >> 15) & 7);
+        Self {
+            stm: (idx >> 12) & 1,
+            white_king: (idx & 63) as u8,
+            black_king: ((idx >> 6) & 63) as u8,
+            pawn: (rank * 8 + file) as u8,
+        }
+    }
+
+    /// What's known without looking at any moves.
+    fn classify_initial(&self) -> u8 {
+        let (wk, bk, pawn) = (self.white_king, self.black_king, self.pawn);
+        let white_to_move = self.stm == 0;
+        if distance(wk, bk) <= 1
+            || wk == pawn
+            || bk == pawn
+            || (white_to_move && pawn_attacks(pawn) & (1 << bk) != 0)
+        {
+            return INVALID;
+        }
+        let queening = pawn + 8;
+        if white_to_move
+            && pawn / 8 == 6
+            && wk != queening
+            && (distance(bk, queening) > 1 || distance(wk, queening) == 1)
+        {
+            return WIN;
+        }
+        let black_moves = king_attacks(bk);
+        let guarded = king_attacks(wk) | pawn_attacks(pawn);
+        if !white_to_move
+            && (black_moves & !guarded == 0 || black_moves & !king_attacks(wk) & (1 << pawn) != 0)
+        {
+            return DRAW;
+        }
+        UNKNOWN
+    }
+
+    /// Resolve from the successors' states.
+    fn classify(&self, db: &[u8]) -> u8 {
+        let (wk, bk, pawn) = (self.white_king, self.black_king, self.pawn);
+        let mut successors = INVALID;
+        if self.stm == 0 {
+            let mut moves = king_attacks(wk);
+            while moves != 0 {
+                let to = moves.trailing_zeros() as u8;
+                moves &= moves - 1;
+                successors |= db[index(1, bk, to, pawn)];
+            }
+            if pawn / 8 < 6 {
+                successors |= db[index(1, bk, wk, pawn + 8)];
+            }
+            if pawn / 8 == 1 && pawn + 8 != wk && pawn + 8 != bk {
+                successors |= db[index(1, bk, wk, pawn + 16)];
+            }
+        } else {
+            let mut moves = king_attacks(bk);
+            while moves != 0 {
+                let to = moves.trailing_zeros() as u8;
+                moves &= moves - 1;
+                successors |= db[index(0, to, wk, pawn)];
+            }
+        }
+        let (good, bad) = if self.stm == 0 {
+            (WIN, DRAW)
+        } else {
+            (DRAW, WIN)
+        };
+        if successors & good != 0 {
+            good
+        } else if successors & UNKNOWN != 0 {
+            UNKNOWN
+        } else {
+            bad
+        }
+    }
+}
+
+fn generate() -> Vec<u64> {
+    let positions: Vec<Position> = (0..MAX_INDEX).map(Position::decode).collect();
+    let mut db: Vec<u8> = positions.iter().map(Position::classify_initial).collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (idx, position) in positions.iter().enumerate() {
+            if db[idx] == UNKNOWN {
+                db[idx] = position.classify(&db);
+                changed |= db[idx] != UNKNOWN;
+            }
+        }
+    }
+    let mut bits = vec![0u64; MAX_INDEX / 64];
+    for (idx, _) in db.iter().enumerate().filter(|&(_, &state)| state == WIN) {
+        bits[idx / 64] |= 1 << (idx % 64);
+    }
+    bits
+}
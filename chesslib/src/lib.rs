@@ -1,12 +1,33 @@
+pub mod bench;
 pub mod board;
 pub mod board_utils;
+pub mod datagen;
+pub mod endgame;
+pub mod eval_params;
+pub mod eval_trace;
 pub mod evaluation;
 pub mod fen;
+pub mod king_safety;
+pub mod kpk;
 pub mod logger;
 pub mod move_generation;
+#[cfg(feature = "nnue")]
+pub mod nnue;
+pub mod pawns;
+pub mod perft;
+pub mod pieces;
 pub mod search;
+pub mod see;
+pub mod stats;
+pub mod strength;
+pub mod syzygy;
+pub mod tablebase;
+pub mod tbgen;
+pub mod tt;
+pub mod tuner;
 pub mod types;
 pub mod uci;
+pub mod zobrist;
 
 pub use logger::log_to_file;
 pub use types::Square;
@@ -136,7 +136,7 @@ pub fn bishop_moves(bishops: u64, friendly_pieces: u64, enemy_pieces: u64) -> u6
 
         // Southwest diagonal
         pos = bishop_pos;
-        while pos % 8 != 0 && pos >= 8 {
+        while !pos.is_multiple_of(8) && pos >= 8 {
             // While not on a-file and not on rank 1
             pos -= 9;
             let target = 1u64 << pos;
@@ -151,7 +151,7 @@ pub fn bishop_moves(bishops: u64, friendly_pieces: u64, enemy_pieces: u64) -> u6
 
         // Northwest diagonal
         pos = bishop_pos;
-        while pos % 8 != 0 && pos < 56 {
+        while !pos.is_multiple_of(8) && pos < 56 {
             // While not on a-file and not on rank 8
             pos += 7;
             let target = 1u64 << pos;
@@ -227,7 +227,7 @@ pub fn rook_moves_impl1(rooks: u64, friendly_pieces: u64, enemy_pieces: u64) ->
 
         // West (left)
         pos = rook_pos;
-        while pos % 8 != 0 {
+        while !pos.is_multiple_of(8) {
             // While not on a-file
             pos -= 1;
             let target = 1u64 << pos;
@@ -0,0 +1,471 @@
+//! NNUE: an efficiently updatable neural network evaluation, built
+//! with the `nnue` feature.
+//!
+//! The network is the simple (768→N)×2→1 shape. The input is one
+//! feature per (piece colour, piece type, square), seen from each
+//! side's point of view: "my knight on f3" from White's side is "my
+//! knight on f6" from Black's. Both views go through the same N-wide
+//! feature layer into two *accumulators*, which are clipped to
+//! `[0, QA]` (clipped ReLU), concatenated side to move first, and
+//! reduced to one number by the output layer.
+//!
+//! The feature layer is the expensive part, and it's linear: a move
+//! only adds and subtracts the columns of the two to four features it
+//! changes. The board keeps both accumulators in [`Board::nnue`] and
+//! updates them in the same mutation helpers that update the Zobrist
+//! keys, so `apply_move` and `undo_last_move` keep them current for
+//! free. Loading a different network (or switching evaluators) bumps a
+//! generation counter, and the first move after that rebuilds the
+//! accumulators from scratch.
+//!
+//! Everything is quantised to `i16`: feature weights and biases by
+//! `QA`, output weights by `QB`, the output bias by `QA·QB`. The output
+//! layer has a scalar implementation and an AVX2 one, picked at run
+//! time; they agree exactly.
+//!
+//! # Weight file format
+//!
+//! All integers little-endian, no padding:
+//!
+//! | Field           | Type  | Count    |
+//! |-----------------|-------|----------|
+//! | magic `"NNUE"`  | bytes | 4        |
+//! | version, 1      | u32   | 1        |
+//! | hidden size N   | u32   | 1        |
+//! | feature weights | i16   | 768 × N  |
+//! | feature biases  | i16   | N        |
+//! | output weights  | i16   | 2 × N    |
+//! | output bias     | i16   | 1        |
+//!
+//! Feature weights are stored feature by feature, N values each. The
+//! feature index, from one side's point of view, is
+//! `side · 384 + piece · 64 + square`: `side` is 0 for that side's own
+//! pieces and 1 for the opponent's, `piece` runs pawn, knight, bishop,
+//! rook, queen, king, and `square` is a1 = 0 … h8 = 63, flipped
+//! vertically (`square ^ 56`) for Black. The first N output weights
+//! apply to the side to move's accumulator, the other N to the
+//! opponent's. The evaluation in centipawns, for the side to move, is
+//! `(Σ clamp(acc, 0, QA) · w + bias) · SCALE / (QA · QB)`.
+//!
+//! The switch between evaluators is [`set_evaluator`], or over UCI
+//! `setoption name EvalFile value <file>` and `setoption name Use NNUE
+//! value true`. The classical evaluation stays the default, and is
+//! used whenever no network is loaded.
+
+use crate::board::Board;
+use crate::types::{Color, Piece, PieceType};
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Inputs per point of view: 2 sides × 6 piece types × 64 squares.
+pub const FEATURES: usize = 768;
+/// Quantisation of the feature layer, and the clipped ReLU's ceiling.
+pub const QA: i32 = 255;
+/// Quantisation of the output weights.
+pub const QB: i32 = 64;
+/// Centipawns per unit of network output.
+pub const SCALE: i32 = 400;
+
+const MAGIC: &[u8; 4] = b"NNUE";
+const VERSION: u32 = 1;
+const HEADER_BYTES: usize = 12;
+
+/// A quantised network. See the module docs for the layout.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Network {
+    hidden: usize,
+    feature_weights: Vec<i16>,
+    feature_biases: Vec<i16>,
+    output_weights: Vec<i16>,
+    output_bias: i16,
+}
+
+impl fmt::Debug for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Network(768→{})×2→1", self.hidden)
+    }
+}
+
+impl Network {
+    /// A network from its quantised weights, in file order.
+    pub fn new(
+        feature_weights: Vec<i16>,
+        feature_biases: Vec<i16>,
+        output_weights: Vec<i16>,
+        output_bias: i16,
+    ) -> Result<Self, String> {
+        let hidden = feature_biases.len();
+        if hidden == 0 {
+            return Err("network has no hidden neurons".to_string());
+        }
+        if feature_weights.len() != FEATURES * hidden {
+            return Err(format!(
+                "expected {} feature weights, got {}",
+                FEATURES * hidden,
+                feature_weights.len()
+            ));
+        }
+        if output_weights.len() != 2 * hidden {
+            return Err(format!(
+                "expected {} output weights, got {}",
+                2 * hidden,
+                output_weights.len()
+            ));
+        }
+        Ok(Self {
+            hidden,
+            feature_weights,
+            feature_biases,
+            output_weights,
+            output_bias,
+        })
+    }
+
+    /// N, the width of each accumulator.
+    pub fn hidden_size(&self) -> usize {
+        self.hidden
+    }
+
+    /// Parse the weight file format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_BYTES || &bytes[..4] != MAGIC {
+            return Err("not an NNUE weight file".to_string());
+        }
+        let word = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let version = word(4);
+        if version != VERSION {
+            return Err(format!("unsupported NNUE version {version}"));
+        }
+        let hidden = word(8) as usize;
+        let count = (FEATURES + 1 + 2) * hidden + 1;
+        if hidden == 0 || bytes.len() != HEADER_BYTES + 2 * count {
+            return Err(format!(
+                "hidden size {hidden} needs {} bytes, file has {}",
+                HEADER_BYTES + 2 * count,
+                bytes.len()
+            ));
+        }
+        let mut values = bytes[HEADER_BYTES..]
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]));
+        let mut take = |n: usize| values.by_ref().take(n).collect::<Vec<i16>>();
+        let feature_weights = take(FEATURES * hidden);
+        let feature_biases = take(hidden);
+        let output_weights = take(2 * hidden);
+        let output_bias = take(1)[0];
+        Self::new(feature_weights, feature_biases, output_weights, output_bias)
+    }
+
+    /// Serialise to the weight file format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_BYTES + 2 * (FEATURES + 3) * self.hidden + 2);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.hidden as u32).to_le_bytes());
+        for value in self
+            .feature_weights
+            .iter()
+            .chain(&self.feature_biases)
+            .chain(&self.output_weights)
+            .chain(std::iter::once(&self.output_bias))
+        {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::from_bytes(&bytes).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_bytes()).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// `board` evaluated from scratch, from White's point of view.
+    pub fn evaluate(&self, board: &Board) -> i64 {
+        let white = self.accumulate(board, Color::White);
+        let black = self.accumulate(board, Color::Black);
+        self.score(board.side_to_move, &white, &black)
+    }
+
+    /// One point of view's accumulator, computed from scratch.
+    fn accumulate(&self, board: &Board, perspective: Color) -> Vec<i16> {
+        let mut values = self.feature_biases.clone();
+        for (sq, piece) in board.piece_map.iter().enumerate() {
+            if let Some(piece) = piece {
+                let column = self.column(feature_index(perspective, *piece, sq as u8));
+                for (value, weight) in values.iter_mut().zip(column) {
+                    *value = value.wrapping_add(*weight);
+                }
+            }
+        }
+        values
+    }
+
+    fn column(&self, feature: usize) -> &[i16] {
+        &self.feature_weights[feature * self.hidden..(feature + 1) * self.hidden]
+    }
+
+    /// The output layer over both accumulators, as White's score in
+    /// centipawns.
+    fn score(&self, side_to_move: Color, white: &[i16], black: &[i16]) -> i64 {
+        let (us, them) = match side_to_move {
+            Color::White => (white, black),
+            Color::Black => (black, white),
+        };
+        let (us_weights, them_weights) = self.output_weights.split_at(self.hidden);
+        let sum = crelu_dot(us, us_weights) + crelu_dot(them, them_weights);
+        let eval = (sum as i64 + self.output_bias as i64) * SCALE as i64 / (QA * QB) as i64;
+        match side_to_move {
+            Color::White => eval,
+            Color::Black => -eval,
+        }
+    }
+}
+
+/// Index of `piece` on `sq` in `perspective`'s input features.
+pub fn feature_index(perspective: Color, piece: Piece, sq: u8) -> usize {
+    let side = usize::from(piece.color() != perspective);
+    let kind = match piece.piece_type() {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    };
+    let sq = match perspective {
+        Color::White => sq,
+        Color::Black => sq ^ 56,
+    } as usize;
+    side * 384 + kind * 64 + sq
+}
+
+/// `Σ clamp(values[i], 0, QA) · weights[i]`, with AVX2 when the CPU
+/// has it.
+pub fn crelu_dot(values: &[i16], weights: &[i16]) -> i32 {
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        // SAFETY: the CPU supports AVX2, checked just above.
+        return unsafe { crelu_dot_avx2(values, weights) };
+    }
+    crelu_dot_scalar(values, weights)
+}
+
+/// [`crelu_dot`] without SIMD.
+pub fn crelu_dot_scalar(values: &[i16], weights: &[i16]) -> i32 {
+    values
+        .iter()
+        .zip(weights)
+        .map(|(&v, &w)| (v as i32).clamp(0, QA) * w as i32)
+        .sum()
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn crelu_dot_avx2(values: &[i16], weights: &[i16]) -> i32 {
+    use std::arch::x86_64::*;
+
+    let len = values.len().min(weights.len());
+    let chunks = len / 16;
+    let zero = _mm256_setzero_si256();
+    let ceiling = _mm256_set1_epi16(QA as i16);
+    let mut sum = _mm256_setzero_si256();
+    for i in 0..chunks {
+        let v = _mm256_loadu_si256(values.as_ptr().add(i * 16) as *const __m256i);
+        let w = _mm256_loadu_si256(weights.as_ptr().add(i * 16) as *const __m256i);
+        let clipped = _mm256_min_epi16(_mm256_max_epi16(v, zero), ceiling);
+        // Pairwise i16 products summed into i32 lanes: at most
+        // 2 · 255 · 32768, well inside i32.
+        sum = _mm256_add_epi32(sum, _mm256_madd_epi16(clipped, w));
+    }
+    let mut lanes = [0i32; 8];
+    _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, sum);
+    let tail = chunks * 16;
+    lanes.iter().sum::<i32>() + crelu_dot_scalar(&values[tail..len], &weights[tail..len])
+}
+
+/// The two accumulators of one position, and the network they belong
+/// to. Kept on the [`Board`]; empty while the classical evaluation is
+/// in use.
+#[derive(Clone, Default)]
+pub struct Accumulator {
+    /// The network generation these values were built for.
+    generation: u64,
+    network: Option<Arc<Network>>,
+    /// `values[color.idx()]`: that side's point of view.
+    values: [Vec<i16>; 2],
+}
+
+impl fmt::Debug for Accumulator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Accumulator")
+            .field("generation", &self.generation)
+            .field("network", &self.network)
+            .finish()
+    }
+}
+
+impl Accumulator {
+    /// Both accumulators of `board` under `network`, from scratch.
+    pub fn new(network: Arc<Network>, board: &Board) -> Self {
+        let values = [
+            network.accumulate(board, Color::White),
+            network.accumulate(board, Color::Black),
+        ];
+        Self {
+            generation: 0,
+            network: Some(network),
+            values,
+        }
+    }
+
+    /// `perspective`'s accumulator; empty without a network.
+    pub fn values(&self, perspective: Color) -> &[i16] {
+        &self.values[perspective.idx()]
+    }
+
+    /// Take `piece` off the squares in `removed` and put it on those in
+    /// `added`.
+    #[inline]
+    pub(crate) fn update(&mut self, piece: Piece, removed: u64, added: u64) {
+        let Some(network) = &self.network else {
+            return;
+        };
+        for (bits, add) in [(removed, false), (added, true)] {
+            let mut bits = bits;
+            while bits != 0 {
+                let sq = bits.trailing_zeros() as u8;
+                bits &= bits - 1;
+                for perspective in [Color::White, Color::Black] {
+                    let column = network.column(feature_index(perspective, piece, sq));
+                    let values = &mut self.values[perspective.idx()];
+                    if add {
+                        for (value, weight) in values.iter_mut().zip(column) {
+                            *value = value.wrapping_add(*weight);
+                        }
+                    } else {
+                        for (value, weight) in values.iter_mut().zip(column) {
+                            *value = value.wrapping_sub(*weight);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Which evaluation `Board::evaluate` uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Evaluator {
+    /// The hand-written evaluation and its [`EvalParams`](crate::eval_params::EvalParams).
+    Classical,
+    /// The loaded network; classical while none is loaded.
+    Nnue,
+}
+
+static EVALUATOR: AtomicU8 = AtomicU8::new(0);
+
+/// Bumped whenever the network or the evaluator changes. Starts above
+/// the default accumulator's 0, so new boards build theirs on first
+/// use.
+static GENERATION: AtomicU64 = AtomicU64::new(1);
+
+lazy_static::lazy_static! {
+    static ref NETWORK: RwLock<Option<Arc<Network>>> = RwLock::new(None);
+}
+
+/// Choose the evaluator for every thread.
+pub fn set_evaluator(evaluator: Evaluator) {
+    let _network = NETWORK.write().unwrap();
+    EVALUATOR.store(evaluator as u8, Ordering::Release);
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+pub fn evaluator() -> Evaluator {
+    match EVALUATOR.load(Ordering::Acquire) {
+        0 => Evaluator::Classical,
+        _ => Evaluator::Nnue,
+    }
+}
+
+/// Install `network` (or remove it, with `None`) for every thread.
+pub fn set_network(network: Option<Network>) {
+    let mut current = NETWORK.write().unwrap();
+    *current = network.map(Arc::new);
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+/// The loaded network, whichever evaluator is selected.
+pub fn network() -> Option<Arc<Network>> {
+    NETWORK.read().unwrap().clone()
+}
+
+/// The network `Board::evaluate` should use right now, and the
+/// generation it belongs to.
+fn active() -> (u64, Option<Arc<Network>>) {
+    let network = NETWORK.read().unwrap();
+    let generation = GENERATION.load(Ordering::Acquire);
+    match evaluator() {
+        Evaluator::Nnue => (generation, network.clone()),
+        Evaluator::Classical => (generation, None),
+    }
+}
+
+impl Board {
+    /// The NNUE accumulators for this position.
+    pub fn accumulator(&self) -> &Accumulator {
+        &self.nnue
+    }
+
+    /// Rebuild the accumulators from scratch for the active network.
+    /// Needed only after editing the bitboards directly; `apply_move`,
+    /// `undo_last_move` and `load_fen` keep them current.
+    pub fn refresh_accumulator(&mut self) {
+        let (generation, network) = active();
+        self.nnue = match network {
+            Some(network) => Accumulator {
+                generation,
+                ..Accumulator::new(network, self)
+            },
+            None => Accumulator {
+                generation,
+                ..Accumulator::default()
+            },
+        };
+    }
+
+    /// Rebuild the accumulators if the network or evaluator changed
+    /// since they were built.
+    #[inline]
+    pub(crate) fn sync_accumulator(&mut self) {
+        if self.nnue.generation != GENERATION.load(Ordering::Acquire) {
+            self.refresh_accumulator();
+        }
+    }
+
+    /// The NNUE evaluation from White's point of view, or `None` when
+    /// the classical evaluation is in charge.
+    pub(crate) fn nnue_evaluate(&self) -> Option<i64> {
+        if evaluator() == Evaluator::Classical {
+            return None;
+        }
+        if self.nnue.generation == GENERATION.load(Ordering::Acquire) {
+            let network = self.nnue.network.as_ref()?;
+            return Some(network.score(
+                self.side_to_move,
+                self.nnue.values(Color::White),
+                self.nnue.values(Color::Black),
+            ));
+        }
+        // Built before the network changed: evaluate from scratch
+        // until the next move brings the accumulators up to date.
+        active().1.map(|network| network.evaluate(self))
+    }
+}
@@ -0,0 +1,285 @@
+//! Pawn-structure evaluation, cached in a pawn hash table.
+//!
+//! Pawns move rarely and slowly, so the same pawn skeleton turns up in
+//! thousands of nodes of one search. Everything that depends on the
+//! pawns alone — doubled, isolated, backward and connected pawns, and
+//! which pawns are passed — is computed once per skeleton and cached
+//! under the board's pawn-only Zobrist key ([`Board::pawn_hash`]). The
+//! few passed-pawn terms that also depend on the pieces (is the pawn
+//! blocked? can the enemy king still catch it?) are added on top on
+//! every call.
+//!
+//! All terms are [`Tapered`] middlegame/endgame pairs: structural
+//! weaknesses and passed pawns both matter more as material comes off.
+//!
+//! The table is per thread (each search thread gets its own), so it
+//! needs no locking, and a lookup is a single compare. It caches
+//! results under the active [`EvalParams`](crate::eval_params::EvalParams)
+//! only, and empties itself when they change.
+//!
+//! The constants here are the default weights; the evaluation reads
+//! them from [`PawnParams`].
+
+use crate::board::Board;
+use crate::eval_params::{EvalParams, PawnParams};
+use crate::evaluation::Tapered;
+use crate::move_generation::{b_pawn_attacks, w_pawn_attacks};
+use crate::types::{Color, PieceType};
+use std::cell::RefCell;
+
+pub(crate) const FILE_A: u64 = 0x0101_0101_0101_0101;
+const RANK_1: u64 = 0xFF;
+
+/// Per pawn with a friendly pawn in front of it on the same file.
+pub(crate) const DOUBLED: Tapered = Tapered::new(-10, -25);
+/// Per pawn with no friendly pawn on either neighbouring file.
+pub(crate) const ISOLATED: Tapered = Tapered::new(-10, -15);
+/// Per pawn that no neighbour can ever support and whose advance is
+/// covered by an enemy pawn.
+pub(crate) const BACKWARD: Tapered = Tapered::new(-8, -10);
+/// Per pawn with a friendly pawn beside it or defending it, by
+/// relative rank (0 = own back rank). Nothing on the second rank, so
+/// the untouched starting rows aren't rewarded.
+pub(crate) const CONNECTED: [i64; 8] = [0, 0, 5, 8, 14, 24, 40, 0];
+/// Per passed pawn, by relative rank.
+pub(crate) const PASSED: [Tapered; 8] = [
+    Tapered::new(0, 0),
+    Tapered::new(5, 10),
+    Tapered::new(10, 20),
+    Tapered::new(15, 35),
+    Tapered::new(30, 60),
+    Tapered::new(50, 100),
+    Tapered::new(80, 150),
+    Tapered::new(0, 0),
+];
+/// Endgame bonus for a passed pawn the enemy king can't catch, with
+/// no enemy pieces left to stop it.
+pub(crate) const UNSTOPPABLE: i64 = 300;
+
+/// Entries in each thread's pawn table (a power of two).
+const PAWN_TABLE_ENTRIES: usize = 1 << 14;
+
+/// The pawn-only part of the evaluation for one skeleton.
+#[derive(Debug, Clone, Copy, Default)]
+struct PawnEntry {
+    key: u64,
+    /// Structural score of each side's pawns from its own point of
+    /// view, passed-pawn rank bonuses included. Indexed by
+    /// `Color::idx()`.
+    score: [Tapered; 2],
+    /// Passed pawns, indexed by `Color::idx()`.
+    passed: [u64; 2],
+}
+
+/// A thread's pawn table, valid for one generation of the active
+/// parameters.
+struct PawnTable {
+    generation: u64,
+    entries: Vec<PawnEntry>,
+}
+
+thread_local! {
+    static PAWN_TABLE: RefCell<PawnTable> = RefCell::new(PawnTable {
+        generation: 0,
+        entries: vec![PawnEntry::default(); PAWN_TABLE_ENTRIES],
+    });
+}
+
+impl Board {
+    /// Pawn-structure score from White's point of view, before
+    /// tapering.
+    pub fn evaluate_pawns(&self) -> Tapered {
+        let [white, black] = self.pawn_scores();
+        white - black
+    }
+
+    /// Each side's pawn-structure score from its own point of view,
+    /// indexed by `Color::idx()`.
+    pub fn pawn_scores(&self) -> [Tapered; 2] {
+        EvalParams::with_active(|params, generation| {
+            self.pawn_scores_with(&params.pawns, Some(generation))
+        })
+    }
+
+    /// Pawn scores under `params`. With `generation` (the active
+    /// parameters' generation, when `params` are the active ones) the
+    /// structural part comes from the pawn table; without, it's
+    /// computed afresh.
+    pub(crate) fn pawn_scores_with(
+        &self,
+        params: &PawnParams,
+        generation: Option<u64>,
+    ) -> [Tapered; 2] {
+        let entry = match generation {
+            Some(generation) => PAWN_TABLE.with(|table| {
+                let mut table = table.borrow_mut();
+                if table.generation != generation {
+                    table.entries.fill(PawnEntry::default());
+                    table.generation = generation;
+                }
+                let slot = &mut table.entries[self.pawn_hash as usize & (PAWN_TABLE_ENTRIES - 1)];
+                // An empty slot has key 0, which is also the key of a
+                // board with no pawns; its all-zero entry is right for
+                // one.
+                if slot.key != self.pawn_hash {
+                    *slot = self.pawn_entry(params);
+                }
+                *slot
+            }),
+            None => self.pawn_entry(params),
+        };
+        [Color::White, Color::Black].map(|color| {
+            entry.score[color.idx()]
+                + self.passed_pawn_extras(color, entry.passed[color.idx()], params)
+        })
+    }
+
+    /// Compute the cacheable part of the pawn evaluation from scratch.
+    fn pawn_entry(&self, params: &PawnParams) -> PawnEntry {
+        let white = self.white_pawns();
+        let black = self.black_pawns();
+        let (white_score, white_passed) = pawn_structure(Color::White, white, black, params);
+        let (black_score, black_passed) = pawn_structure(Color::Black, black, white, params);
+        PawnEntry {
+            key: self.pawn_hash,
+            score: [white_score, black_score],
+            passed: [white_passed, black_passed],
+        }
+    }
+
+    /// The passed-pawn terms that depend on more than the pawns, for
+    /// `color`'s passers: one whose next square is occupied keeps only
+    /// half its bonus, and one that simply outruns the enemy king in a
+    /// pawn ending is as good as a new queen.
+    fn passed_pawn_extras(&self, color: Color, passed: u64, params: &PawnParams) -> Tapered {
+        let occupied = self.colors[0] | self.colors[1];
+        let enemy = color.opponent();
+        let enemy_pieces = self.colors[enemy.idx()]
+            & !self.pieces[PieceType::Pawn.idx()]
+            & !self.pieces[PieceType::King.idx()];
+        let enemy_king = self.piece_bb(PieceType::King, enemy);
+        let mut extras = Tapered::default();
+        let mut bits = passed;
+        while bits != 0 {
+            let sq = bits.trailing_zeros() as u8;
+            bits &= bits - 1;
+            let rank = relative_rank(color, sq);
+            if occupied & stop_square(color, sq) != 0 {
+                let bonus = params.passed[rank];
+                extras -= Tapered::new(bonus.mg / 2, bonus.eg / 2);
+            }
+            let path = forward_ranks(color, sq) & (FILE_A << (sq % 8));
+            if enemy_pieces == 0 && enemy_king != 0 && occupied & path == 0 {
+                let promotion = match color {
+                    Color::White => 56 + sq % 8,
+                    Color::Black => sq % 8,
+                };
+                // Rule of the square, counting the double step and
+                // the defender's tempo if it's their move.
+                let pawn_moves = 7 - rank as i32 - (rank == 1) as i32;
+                let king_moves = distance(enemy_king.trailing_zeros() as u8, promotion)
+                    - (self.side_to_move == enemy) as i32;
+                if pawn_moves < king_moves {
+                    extras += Tapered::new(0, params.unstoppable);
+                }
+            }
+        }
+        extras
+    }
+}
+
+/// Structural score for `color`'s pawns `own` facing enemy pawns
+/// `enemy`, from `color`'s point of view, and the set of its passed
+/// pawns.
+fn pawn_structure(color: Color, own: u64, enemy: u64, params: &PawnParams) -> (Tapered, u64) {
+    let enemy_attacks = match color {
+        Color::White => b_pawn_attacks(enemy),
+        Color::Black => w_pawn_attacks(enemy),
+    };
+    let mut score = Tapered::default();
+    let mut passed = 0;
+    let mut bits = own;
+    while bits != 0 {
+        let sq = bits.trailing_zeros() as u8;
+        bits &= bits - 1;
+        let bit = 1u64 << sq;
+        let file = sq % 8;
+        let rank = relative_rank(color, sq);
+        let ahead = forward_ranks(color, sq);
+        let front_span = ahead & (FILE_A << file);
+        let neighbours = adjacent_files(file);
+
+        if own & front_span != 0 {
+            score += params.doubled;
+        }
+        if own & neighbours == 0 {
+            score += params.isolated;
+        } else if own & neighbours & !ahead == 0 && enemy_attacks & stop_square(color, sq) != 0 {
+            score += params.backward;
+        }
+
+        // Beside it on the same rank, or diagonally behind it.
+        let phalanx = own & neighbours & (RANK_1 << (8 * (sq / 8)));
+        let support = own
+            & match color {
+                Color::White => b_pawn_attacks(bit),
+                Color::Black => w_pawn_attacks(bit),
+            };
+        if phalanx | support != 0 {
+            score += Tapered::new(params.connected[rank], params.connected[rank]);
+        }
+
+        // No enemy pawn ahead on this or a neighbouring file, and not
+        // behind a friendly pawn (only the front pawn of a doubled
+        // pair counts).
+        if enemy & ahead & ((FILE_A << file) | neighbours) == 0 && own & front_span == 0 {
+            score += params.passed[rank];
+            passed |= bit;
+        }
+    }
+    (score, passed)
+}
+
+/// Files either side of `file`.
+pub(crate) fn adjacent_files(file: u8) -> u64 {
+    let mut mask = 0;
+    if file > 0 {
+        mask |= FILE_A << (file - 1);
+    }
+    if file < 7 {
+        mask |= FILE_A << (file + 1);
+    }
+    mask
+}
+
+/// Every square on a rank strictly ahead of `sq`, from `color`'s side.
+pub(crate) fn forward_ranks(color: Color, sq: u8) -> u64 {
+    let rank = sq / 8;
+    match color {
+        Color::White => (!0u64).checked_shl(8 * (rank as u32 + 1)).unwrap_or(0),
+        Color::Black => (1u64 << (8 * rank)) - 1,
+    }
+}
+
+/// The square a pawn of `color` on `sq` advances to.
+pub(crate) fn stop_square(color: Color, sq: u8) -> u64 {
+    match color {
+        Color::White => (1u64 << sq) << 8,
+        Color::Black => (1u64 << sq) >> 8,
+    }
+}
+
+/// Rank counted from `color`'s own back rank: 0..=7.
+pub(crate) fn relative_rank(color: Color, sq: u8) -> usize {
+    match color {
+        Color::White => (sq / 8) as usize,
+        Color::Black => 7 - (sq / 8) as usize,
+    }
+}
+
+/// King moves between two squares.
+fn distance(a: u8, b: u8) -> i32 {
+    let files = (a % 8) as i32 - (b % 8) as i32;
+    let ranks = (a / 8) as i32 - (b / 8) as i32;
+    files.abs().max(ranks.abs())
+}
@@ -0,0 +1,372 @@
+//! Perft tooling beyond the plain [`Board::perft`] node count.
+//!
+//! Perft is the move generator's test oracle: the number of leaf nodes
+//! at each depth is known for many positions, and any disagreement
+//! means a move-generation or apply/undo bug. This module adds the
+//! tools for tracking such a bug down and for running perft at scale:
+//!
+//!  - [`Board::perft_divide`] splits the count by root move, so it can
+//!    be diffed line by line against another engine's `divide` to find
+//!    the move whose subtree is wrong (then repeat one ply deeper).
+//!  - [`Board::perft_counts`] classifies the leaf moves the way the
+//!    published tables do — captures, en passant, castles, promotions,
+//!    checks (discovered and double), checkmates — which usually says
+//!    *what kind* of move is broken.
+//!  - [`Board::perft_hashed`] caches subtree counts by Zobrist key, and
+//!    [`Board::perft_divide_parallel`] farms root moves out to threads;
+//!    both only make deep counts faster.
+//!  - [`PerftSuiteEntry`] reads the usual EPD perft-suite format
+//!    (`<fen> ;D1 20 ;D2 400 ...`) and checks a position against it.
+//!
+//! The UCI layer exposes divide as `go perft N`; the `perft_suite`
+//! example runs an EPD file.
+
+use crate::board::Board;
+use crate::fen::load_fen;
+use crate::types::{Move, PieceType};
+use std::fmt;
+use std::ops::AddAssign;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Leaf-node counts broken down by the kind of move that reached the
+/// leaf, as in the tables at chessprogramming.org/Perft_Results.
+/// Every counter but `nodes` describes the last move of the line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerftCounts {
+    pub nodes: u64,
+    /// Including en passant.
+    pub captures: u64,
+    pub en_passants: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+    /// Single checks given by a piece other than the one that moved
+    /// (or, for castling, the rook).
+    pub discovered_checks: u64,
+    /// Checks by two pieces at once. The published tables count these
+    /// here and not as discovered checks, so this does too.
+    pub double_checks: u64,
+    pub checkmates: u64,
+}
+
+impl AddAssign for PerftCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passants += other.en_passants;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+        self.discovered_checks += other.discovered_checks;
+        self.double_checks += other.double_checks;
+        self.checkmates += other.checkmates;
+    }
+}
+
+impl fmt::Display for PerftCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "nodes              {}", self.nodes)?;
+        writeln!(f, "captures           {}", self.captures)?;
+        writeln!(f, "en passant         {}", self.en_passants)?;
+        writeln!(f, "castles            {}", self.castles)?;
+        writeln!(f, "promotions         {}", self.promotions)?;
+        writeln!(f, "checks             {}", self.checks)?;
+        writeln!(f, "discovered checks  {}", self.discovered_checks)?;
+        writeln!(f, "double checks      {}", self.double_checks)?;
+        write!(f, "checkmates         {}", self.checkmates)
+    }
+}
+
+/// One cached subtree count.
+#[derive(Clone, Copy, Default)]
+struct PerftEntry {
+    key: u64,
+    depth: u32,
+    nodes: u64,
+}
+
+/// Cache of subtree node counts for [`Board::perft_hashed`], keyed by
+/// Zobrist hash and depth. Always-replace; a collision costs a
+/// recount, never a wrong answer unless two positions share all 64
+/// key bits.
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+}
+
+impl PerftTable {
+    /// A table using (about) `megabytes` of memory, at least one entry.
+    pub fn new(megabytes: usize) -> Self {
+        let len = (megabytes * 1024 * 1024 / std::mem::size_of::<PerftEntry>()).max(1);
+        Self {
+            entries: vec![PerftEntry::default(); len],
+        }
+    }
+
+    fn probe(&self, key: u64, depth: u32) -> Option<u64> {
+        let entry = self.entries[self.index(key)];
+        (entry.key == key && entry.depth == depth && entry.nodes != 0).then_some(entry.nodes)
+    }
+
+    fn store(&mut self, key: u64, depth: u32, nodes: u64) {
+        let idx = self.index(key);
+        self.entries[idx] = PerftEntry { key, depth, nodes };
+    }
+
+    fn index(&self, key: u64) -> usize {
+        ((key as u128 * self.entries.len() as u128) >> 64) as usize
+    }
+}
+
+impl Board {
+    /// Leaf nodes at `depth`, counted as fast as this move generator
+    /// allows: no checkmate detection, and the last ply is counted
+    /// from the move list without playing the moves.
+    pub fn perft_nodes(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let mut moves = Vec::with_capacity(218);
+        self.get_all_raw_moves_append(&mut moves);
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        let mut nodes = 0;
+        for mv in moves {
+            self.apply_move(&mv);
+            nodes += self.perft_nodes(depth - 1);
+            self.undo_last_move();
+        }
+        nodes
+    }
+
+    /// [`Board::perft_nodes`] for each root move, in move-generation
+    /// order. Empty at depth 0.
+    pub fn perft_divide(&mut self, depth: u32) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        let mut moves = Vec::new();
+        self.get_all_raw_moves_append(&mut moves);
+        moves
+            .into_iter()
+            .map(|mv| {
+                self.apply_move(&mv);
+                let nodes = self.perft_nodes(depth - 1);
+                self.undo_last_move();
+                (mv, nodes)
+            })
+            .collect()
+    }
+
+    /// [`Board::perft_divide`] with the root moves shared out among
+    /// `threads` threads, each searching on its own copy of the board.
+    /// Same result, same order.
+    pub fn perft_divide_parallel(&self, depth: u32, threads: usize) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        let mut board = self.clone();
+        let mut moves = Vec::new();
+        board.get_all_raw_moves_append(&mut moves);
+
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(vec![0; moves.len()]);
+        std::thread::scope(|scope| {
+            for _ in 0..threads.clamp(1, moves.len().max(1)) {
+                let mut board = board.clone();
+                let (moves, next, results) = (&moves, &next, &results);
+                scope.spawn(move || loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(mv) = moves.get(i) else { break };
+                    board.apply_move(mv);
+                    let nodes = board.perft_nodes(depth - 1);
+                    board.undo_last_move();
+                    results.lock().unwrap()[i] = nodes;
+                });
+            }
+        });
+        moves
+            .into_iter()
+            .zip(results.into_inner().unwrap())
+            .collect()
+    }
+
+    /// [`Board::perft_nodes`], with subtree counts cached in `table`.
+    /// Transpositions are everywhere in a perft tree, so a table saves
+    /// a large share of the work from depth 5 or so.
+    pub fn perft_hashed(&mut self, depth: u32, table: &mut PerftTable) -> u64 {
+        if depth <= 1 {
+            return self.perft_nodes(depth);
+        }
+        if let Some(nodes) = table.probe(self.hash, depth) {
+            return nodes;
+        }
+        let mut moves = Vec::with_capacity(218);
+        self.get_all_raw_moves_append(&mut moves);
+        let mut nodes = 0;
+        for mv in moves {
+            self.apply_move(&mv);
+            nodes += self.perft_hashed(depth - 1, table);
+            self.undo_last_move();
+        }
+        table.store(self.hash, depth, nodes);
+        nodes
+    }
+
+    /// Leaf nodes at `depth`, classified by the move that reached
+    /// them. At depth 0 the position itself is the one leaf, with no
+    /// move to classify.
+    pub fn perft_counts(&mut self, depth: u32) -> PerftCounts {
+        let mut counts = PerftCounts::default();
+        if depth == 0 {
+            counts.nodes = 1;
+            counts.checkmates = self.is_checkmate() as u64;
+            return counts;
+        }
+        let mut moves = Vec::with_capacity(218);
+        self.get_all_raw_moves_append(&mut moves);
+        for mv in moves {
+            self.apply_move(&mv);
+            if depth == 1 {
+                self.classify_last_move(&mut counts);
+            } else {
+                counts += self.perft_counts(depth - 1);
+            }
+            self.undo_last_move();
+        }
+        counts
+    }
+
+    /// Add the move just played (the top of the history) to `counts`
+    /// as one leaf.
+    fn classify_last_move(&mut self, counts: &mut PerftCounts) {
+        let state = self.move_history.last().expect("a move was just played");
+        let mv = state.last_move;
+        counts.nodes += 1;
+        if state.captured_piece.is_some() {
+            counts.captures += 1;
+            if state.captured_piece_square != Some(mv.target) {
+                counts.en_passants += 1;
+            }
+        }
+        if state.rook_castle_move.is_some() {
+            counts.castles += 1;
+        }
+        if mv.promotion.is_some() {
+            counts.promotions += 1;
+        }
+
+        // Who gives check: the mover's pieces attacking the king of the
+        // side now to move.
+        let king = self.piece_bb(PieceType::King, self.side_to_move);
+        if king == 0 {
+            return;
+        }
+        let mover = self.side_to_move.opponent();
+        let occupied = self.colors[0] | self.colors[1];
+        let checkers =
+            self.attackers_to(king.trailing_zeros() as u8, occupied) & self.colors[mover.idx()];
+        if checkers == 0 {
+            return;
+        }
+        counts.checks += 1;
+        let moved = mv.target.to_bitboard()
+            | state
+                .rook_castle_move
+                .map_or(0, |rook| rook.target.to_bitboard());
+        if checkers.count_ones() > 1 {
+            counts.double_checks += 1;
+        } else if checkers & !moved != 0 {
+            counts.discovered_checks += 1;
+        }
+        if self.is_checkmate() {
+            counts.checkmates += 1;
+        }
+    }
+}
+
+/// A divide listing in the usual format: `<move>: <nodes>` per root
+/// move, then the total.
+pub fn format_divide(divide: &[(Move, u64)]) -> String {
+    let mut out = String::new();
+    for (mv, nodes) in divide {
+        out.push_str(&format!("{mv}: {nodes}\n"));
+    }
+    let total: u64 = divide.iter().map(|(_, nodes)| nodes).sum();
+    out.push_str(&format!("\nNodes searched: {total}"));
+    out
+}
+
+/// One line of an EPD perft suite: a position and its known node
+/// counts, e.g. `<fen> ;D1 20 ;D2 400 ;D3 8902`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PerftSuiteEntry {
+    pub fen: String,
+    /// `(depth, expected nodes)`, in the order given.
+    pub expected: Vec<(u32, u64)>,
+}
+
+/// A depth at which a suite position's count came out wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerftMismatch {
+    pub depth: u32,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+impl PerftSuiteEntry {
+    /// Parse one suite line. The FEN may omit the move counters.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut fields = line.split(';');
+        let fen = fields.next().unwrap_or("").trim().to_string();
+        load_fen(&fen).map_err(|e| format!("{e}: {fen}"))?;
+        let mut expected = Vec::new();
+        for field in fields.map(str::trim).filter(|f| !f.is_empty()) {
+            let parsed = field.strip_prefix('D').and_then(|rest| {
+                let (depth, nodes) = rest.split_once(char::is_whitespace)?;
+                Some((depth.parse().ok()?, nodes.trim().parse().ok()?))
+            });
+            match parsed {
+                Some(pair) => expected.push(pair),
+                None => return Err(format!("bad depth field '{field}' in: {line}")),
+            }
+        }
+        Ok(Self { fen, expected })
+    }
+
+    /// Parse a whole suite, skipping blank lines and `#` comments.
+    pub fn parse_suite(text: &str) -> Result<Vec<Self>, String> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(Self::parse)
+            .collect()
+    }
+
+    /// Count every listed depth up to `max_depth` with `threads`
+    /// threads and return the ones that disagree.
+    pub fn check(&self, max_depth: u32, threads: usize) -> Vec<PerftMismatch> {
+        let board = load_fen(&self.fen).expect("validated by parse");
+        self.expected
+            .iter()
+            .filter(|(depth, _)| *depth <= max_depth)
+            .filter_map(|&(depth, expected)| {
+                let actual = if depth == 0 {
+                    1
+                } else {
+                    board
+                        .perft_divide_parallel(depth, threads)
+                        .iter()
+                        .map(|(_, nodes)| nodes)
+                        .sum()
+                };
+                (actual != expected).then_some(PerftMismatch {
+                    depth,
+                    expected,
+                    actual,
+                })
+            })
+            .collect()
+    }
+}
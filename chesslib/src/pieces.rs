@@ -0,0 +1,277 @@
+//! Piece-specific evaluation: what each kind of piece wants beyond
+//! material and mobility.
+//!
+//! - Rooks on open and semi-open files, and on the seventh rank when
+//!   there is something there to attack.
+//! - Knights on outposts: defended by a pawn, and beyond the reach of
+//!   any enemy pawn.
+//! - Bad bishops, hemmed in by their own pawns on their colour.
+//! - A queen brought out while the minor pieces are still at home.
+//! - Trapped pieces: a bishop grabbing a7/h7 and shut in by ...b6/...g6,
+//!   and a rook boxed in by its own king after the castling rights are
+//!   gone.
+//! - Threats: a pawn attacking a piece, a minor attacking a rook or
+//!   queen, a rook attacking a queen, the king attacking an undefended
+//!   piece.
+//!
+//! Every term is a [`Tapered`] pair; rooks on the seventh and bad
+//! bishops matter more in the endgame, an early queen only in the
+//! opening. The constants here are the default weights; the
+//! evaluation reads them from [`PieceParams`].
+
+use crate::board::Board;
+use crate::eval_params::{EvalParams, PieceParams};
+use crate::evaluation::Tapered;
+use crate::move_generation::{
+    b_pawn_attacks, bishop_moves, king_moves, knight_moves, rook_moves, w_pawn_attacks,
+};
+use crate::pawns::{adjacent_files, forward_ranks, relative_rank, FILE_A};
+use crate::types::{Color, PieceType};
+use crate::Square;
+
+const DARK_SQUARES: u64 = 0xAA55_AA55_AA55_AA55;
+
+pub(crate) const ROOK_OPEN_FILE: Tapered = Tapered::new(35, 15);
+pub(crate) const ROOK_SEMI_OPEN_FILE: Tapered = Tapered::new(15, 10);
+/// On the seventh, with enemy pawns there or the enemy king on the
+/// eighth.
+pub(crate) const ROOK_ON_SEVENTH: Tapered = Tapered::new(20, 35);
+/// On relative ranks 4–6, defended by a pawn, and no enemy pawn left
+/// that could ever chase it away.
+pub(crate) const KNIGHT_OUTPOST: Tapered = Tapered::new(25, 15);
+/// Per friendly pawn on the bishop's colour; doubled for pawns that
+/// are also blocked, which will never get off that colour.
+pub(crate) const BAD_BISHOP_PAWN: Tapered = Tapered::new(-2, -5);
+/// Per minor piece still on its starting square, once the queen has
+/// left hers.
+pub(crate) const EARLY_QUEEN: Tapered = Tapered::new(-10, 0);
+pub(crate) const TRAPPED_BISHOP: Tapered = Tapered::new(-100, -80);
+pub(crate) const TRAPPED_ROOK: Tapered = Tapered::new(-45, -5);
+/// Per enemy knight, bishop, rook or queen a pawn attacks.
+pub(crate) const THREAT_BY_PAWN: Tapered = Tapered::new(45, 35);
+/// Per enemy rook or queen a knight or bishop attacks.
+pub(crate) const THREAT_BY_MINOR: Tapered = Tapered::new(25, 25);
+/// Per enemy queen a rook attacks.
+pub(crate) const THREAT_BY_ROOK: Tapered = Tapered::new(25, 15);
+/// Per enemy knight, bishop, rook or queen the king attacks that
+/// nothing defends: a piece standing next to the king for free.
+pub(crate) const THREAT_BY_KING: Tapered = Tapered::new(25, 60);
+
+impl Board {
+    /// Piece-specific terms from White's point of view, before
+    /// tapering.
+    pub fn evaluate_pieces(&self) -> Tapered {
+        self.piece_terms(Color::White) - self.piece_terms(Color::Black)
+    }
+
+    /// Piece-specific terms for `color`'s pieces, from `color`'s point
+    /// of view.
+    pub fn piece_terms(&self, color: Color) -> Tapered {
+        EvalParams::with_active(|params, _| self.piece_terms_with(color, &params.pieces))
+    }
+
+    /// Piece-specific terms for `color` under `params`.
+    pub(crate) fn piece_terms_with(&self, color: Color, params: &PieceParams) -> Tapered {
+        self.rook_terms(color, params)
+            + self.knight_outposts(color, params)
+            + self.bad_bishops(color, params)
+            + self.early_queen(color, params)
+            + self.trapped_pieces(color, params)
+            + self.threats(color, params)
+    }
+
+    fn rook_terms(&self, color: Color, params: &PieceParams) -> Tapered {
+        let enemy = color.opponent();
+        let pawns = self.pieces[PieceType::Pawn.idx()];
+        let own_pawns = pawns & self.colors[color.idx()];
+        let seventh = relative_rank_mask(color, 6);
+        let eighth = relative_rank_mask(color, 7);
+        let seventh_has_targets = self.piece_bb(PieceType::Pawn, enemy) & seventh != 0
+            || self.piece_bb(PieceType::King, enemy) & eighth != 0;
+
+        let mut score = Tapered::default();
+        let mut rooks = self.piece_bb(PieceType::Rook, color);
+        while rooks != 0 {
+            let sq = rooks.trailing_zeros() as u8;
+            rooks &= rooks - 1;
+            let file = FILE_A << (sq % 8);
+            if pawns & file == 0 {
+                score += params.rook_open_file;
+            } else if own_pawns & file == 0 {
+                score += params.rook_semi_open_file;
+            }
+            if seventh_has_targets && relative_rank(color, sq) == 6 {
+                score += params.rook_on_seventh;
+            }
+        }
+        score
+    }
+
+    fn knight_outposts(&self, color: Color, params: &PieceParams) -> Tapered {
+        let own_pawns = self.piece_bb(PieceType::Pawn, color);
+        let enemy_pawns = self.piece_bb(PieceType::Pawn, color.opponent());
+        let defended = match color {
+            Color::White => w_pawn_attacks(own_pawns),
+            Color::Black => b_pawn_attacks(own_pawns),
+        };
+
+        let mut score = Tapered::default();
+        let mut knights = self.piece_bb(PieceType::Knight, color) & defended;
+        while knights != 0 {
+            let sq = knights.trailing_zeros() as u8;
+            knights &= knights - 1;
+            let chasers = enemy_pawns & forward_ranks(color, sq) & adjacent_files(sq % 8);
+            if (3..=5).contains(&relative_rank(color, sq)) && chasers == 0 {
+                score += params.knight_outpost;
+            }
+        }
+        score
+    }
+
+    fn bad_bishops(&self, color: Color, params: &PieceParams) -> Tapered {
+        let occupied = self.colors[0] | self.colors[1];
+        let own_pawns = self.piece_bb(PieceType::Pawn, color);
+        let blocked = own_pawns
+            & match color {
+                Color::White => occupied >> 8,
+                Color::Black => occupied << 8,
+            };
+
+        let mut score = Tapered::default();
+        let mut bishops = self.piece_bb(PieceType::Bishop, color);
+        while bishops != 0 {
+            let bishop = bishops & bishops.wrapping_neg();
+            bishops &= bishops - 1;
+            let colour = if bishop & DARK_SQUARES != 0 {
+                DARK_SQUARES
+            } else {
+                !DARK_SQUARES
+            };
+            let count = (own_pawns & colour).count_ones() + (blocked & colour).count_ones();
+            score += params.bad_bishop_pawn * count as i64;
+        }
+        score
+    }
+
+    fn early_queen(&self, color: Color, params: &PieceParams) -> Tapered {
+        let (queen_home, minor_homes) = match color {
+            Color::White => (
+                Square::D1.to_bitboard(),
+                Square::B1.to_bitboard()
+                    | Square::C1.to_bitboard()
+                    | Square::F1.to_bitboard()
+                    | Square::G1.to_bitboard(),
+            ),
+            Color::Black => (
+                Square::D8.to_bitboard(),
+                Square::B8.to_bitboard()
+                    | Square::C8.to_bitboard()
+                    | Square::F8.to_bitboard()
+                    | Square::G8.to_bitboard(),
+            ),
+        };
+        let queens = self.piece_bb(PieceType::Queen, color);
+        if queens == 0 || queens & queen_home != 0 {
+            return Tapered::default();
+        }
+        let minors =
+            self.piece_bb(PieceType::Knight, color) | self.piece_bb(PieceType::Bishop, color);
+        params.early_queen * (minors & minor_homes).count_ones() as i64
+    }
+
+    fn trapped_pieces(&self, color: Color, params: &PieceParams) -> Tapered {
+        let bishops = self.piece_bb(PieceType::Bishop, color);
+        let enemy_pawns = self.piece_bb(PieceType::Pawn, color.opponent());
+        let (traps, kingside_rights, queenside_rights) = match color {
+            Color::White => (
+                [(Square::A7, Square::B6), (Square::H7, Square::G6)],
+                self.white_kingside_castle_rights,
+                self.white_queenside_castle_rights,
+            ),
+            Color::Black => (
+                [(Square::A2, Square::B3), (Square::H2, Square::G3)],
+                self.black_kingside_castle_rights,
+                self.black_queenside_castle_rights,
+            ),
+        };
+
+        let mut score = Tapered::default();
+        for (bishop_sq, pawn_sq) in traps {
+            if bishops & bishop_sq.to_bitboard() != 0 && enemy_pawns & pawn_sq.to_bitboard() != 0 {
+                score += params.trapped_bishop;
+            }
+        }
+
+        // A king that has walked to f1/g1 (or b1-d1) without castling
+        // shuts in the rook between it and the corner.
+        let king = self.piece_bb(PieceType::King, color);
+        let back_rank = relative_rank_mask(color, 0);
+        if king & back_rank != 0 {
+            let king_file = king.trailing_zeros() % 8;
+            let rank_shift = king.trailing_zeros() - king_file;
+            let rooks = self.piece_bb(PieceType::Rook, color);
+            let kingside = (0xFF & !((2u64 << king_file) - 1)) << rank_shift;
+            let queenside = ((1u64 << king_file) - 1) << rank_shift;
+            if (5..=6).contains(&king_file) && rooks & kingside != 0 && !kingside_rights {
+                score += params.trapped_rook;
+            }
+            if (1..=3).contains(&king_file) && rooks & queenside != 0 && !queenside_rights {
+                score += params.trapped_rook;
+            }
+        }
+        score
+    }
+
+    fn threats(&self, color: Color, params: &PieceParams) -> Tapered {
+        let enemy = self.colors[color.opponent().idx()];
+        let occupied = self.colors[0] | self.colors[1];
+        let enemy_minors =
+            (self.pieces[PieceType::Knight.idx()] | self.pieces[PieceType::Bishop.idx()]) & enemy;
+        let enemy_rooks = self.pieces[PieceType::Rook.idx()] & enemy;
+        let enemy_queens = self.pieces[PieceType::Queen.idx()] & enemy;
+
+        let pawns = self.piece_bb(PieceType::Pawn, color);
+        let pawn_attacks = match color {
+            Color::White => w_pawn_attacks(pawns),
+            Color::Black => b_pawn_attacks(pawns),
+        };
+        let mut minor_attacks = knight_moves(self.piece_bb(PieceType::Knight, color));
+        let mut bishops = self.piece_bb(PieceType::Bishop, color);
+        while bishops != 0 {
+            let bishop = bishops & bishops.wrapping_neg();
+            bishops &= bishops - 1;
+            minor_attacks |= bishop_moves(bishop, 0, occupied);
+        }
+        let mut rook_attacks = 0;
+        let mut rooks = self.piece_bb(PieceType::Rook, color);
+        while rooks != 0 {
+            let rook = rooks & rooks.wrapping_neg();
+            rooks &= rooks - 1;
+            rook_attacks |= rook_moves(rook, 0, occupied);
+        }
+
+        let by_pawn = pawn_attacks & (enemy_minors | enemy_rooks | enemy_queens);
+        let by_minor = minor_attacks & (enemy_rooks | enemy_queens);
+        let by_rook = rook_attacks & enemy_queens;
+        let mut by_king = king_moves(self.piece_bb(PieceType::King, color))
+            & (enemy_minors | enemy_rooks | enemy_queens);
+        let mut undefended = 0;
+        while by_king != 0 {
+            let square = by_king.trailing_zeros() as u8;
+            by_king &= by_king - 1;
+            undefended += i64::from(self.attackers_to(square, occupied) & enemy == 0);
+        }
+        params.threat_by_pawn * by_pawn.count_ones() as i64
+            + params.threat_by_minor * by_minor.count_ones() as i64
+            + params.threat_by_rook * by_rook.count_ones() as i64
+            + params.threat_by_king * undefended
+    }
+}
+
+/// All of `color`'s relative rank `rank` (0 = own back rank).
+fn relative_rank_mask(color: Color, rank: u8) -> u64 {
+    match color {
+        Color::White => 0xFF << (8 * rank),
+        Color::Black => 0xFF << (8 * (7 - rank)),
+    }
+}
@@ -2,15 +2,18 @@
 //!
 //! Public surface is [`Searcher`] plus the [`MATE_SCORE`] constant. The
 //! [`Searcher`] owns the internal state that needs to live across calls
-//! (today: killer-move table, history heuristic, deterministic-mode
-//! flag; tomorrow: transposition table, repetition history, time
-//! controller, statistics).
+//! (today: killer-move table, history heuristic, transposition table,
+//! helper threads, deterministic-mode flag, contempt settings;
+//! tomorrow: time controller, statistics). Repetition history lives on
+//! the [`Board`] itself, as the Zobrist keys in its move history.
 //!
 //! The search algorithm itself is a fairly conventional negamax with
-//! alpha-beta pruning, iterative deepening, quiescence at the
-//! depth-zero horizon, and cheap-signal move ordering (MVV-LVA for
-//! captures, killer + history for quiets). All of it lives in this
-//! module — Board owns the position primitive (apply / undo /
+//! alpha-beta pruning (principal variation search below the root),
+//! iterative deepening, quiescence at the depth-zero horizon,
+//! shallow-depth futility-style pruning at non-PV nodes, and
+//! cheap-signal move ordering (MVV-LVA plus SEE for captures, killer +
+//! history for quiets). All of it lives in
+//! this module — Board owns the position primitive (apply / undo /
 //! attack queries), Search owns the algorithm.
 //!
 //! ## Score conventions
@@ -25,8 +28,24 @@
 //! When the side-to-move has no legal moves and is in check, the
 //! search returns `-MATE_SCORE + ply` so that closer mates score
 //! larger in magnitude — the engine prefers shorter forced mates
-//! over delayed ones. Stalemate scores 0. See the comment in
-//! `negamax_ab` for the full reasoning.
+//! over delayed ones. Stalemate is a draw (see below). See the comment
+//! in `negamax_ab` for the full reasoning.
+//!
+//! ## Draws and contempt
+//!
+//! Stalemate, repetition, the fifty-move rule and insufficient
+//! material all score the same draw value: 0 by default, or with
+//! [`Searcher::set_contempt`] a penalty for the side to move at the
+//! root, so the engine steers away from (or towards) draws depending
+//! on how it rates its opponent.
+//!
+//! Because mate scores are ply-aware, every node also applies *mate
+//! distance pruning*: once a mate in N has been found, no subtree
+//! deeper than N plies can improve on it, so those nodes cut off
+//! immediately. [`Score::from_raw`] turns a raw mate score back into
+//! "mate in N moves" for the UCI `score mate N` output, and
+//! [`score_to_tt`] / [`score_from_tt`] re-anchor mate scores when they
+//! are cached away from the ply they were found at.
 //!
 //! ## Why we have a stateful `Searcher`
 //!
@@ -34,13 +53,47 @@
 //! iterations: a quiet move that caused a beta cutoff at depth 4 is
 //! tried first at depth 5. With free functions we'd thread that state
 //! through every signature. With a `Searcher` it's just `&mut self`.
-//! When the transposition table lands the same logic applies — it'll
-//! be a private field on `Searcher`, persisting across calls within a
-//! game so that "go depth N+1" reuses subtree results from "go depth
-//! N".
+//! The transposition table follows the same logic — it's a field on
+//! `Searcher`, persisting across calls within a game so that "go depth
+//! N+1" reuses subtree results from "go depth N".
+//!
+//! ## Lazy SMP
+//!
+//! With more than one thread configured ([`Searcher::set_threads`]),
+//! every search also starts `threads - 1` helper threads. Each helper
+//! is a `Searcher` of its own — own board copy, own killers, history
+//! and PV table — sharing only the lock-free transposition table and
+//! the stop flag with the main thread. Helpers run plain iterative
+//! deepening on the same position; odd-numbered helpers start one ply
+//! deeper so the threads don't all walk the same tree in lock-step.
+//! There is no explicit work splitting: helpers help purely by filling
+//! the table with results the main thread then hits. The main thread's
+//! answer is the one returned, and when it finishes it raises the stop
+//! flag and the helpers unwind.
+//!
+//! Deterministic mode always searches on one thread — thread timing
+//! would otherwise decide which table entries exist when.
+//!
+//! ## Controlling a running search
+//!
+//! [`Searcher::search`] is the general entry point: iterative deepening
+//! under [`SearchLimits`]. While it runs, another thread can steer it
+//! through a [`SearchHandle`] — stop it outright, or hand a search that
+//! was started without a clock (a ponder search) a time budget that
+//! starts counting now. That's what the UCI layer's `stop` and
+//! `ponderhit` do.
 
 use crate::board::Board;
-use crate::types::{Color, Move};
+use crate::evaluation::MAX_PHASE;
+use crate::stats::{DepthStats, SearchStats};
+use crate::strength::Strength;
+use crate::tablebase::{self, Source, Wdl};
+use crate::tt::{Bound, TranspositionTable, DEFAULT_HASH_MB};
+use crate::types::{Color, Move, Piece};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 // ---------------------------------------------------------------------
@@ -52,6 +105,15 @@ use std::time::{Duration, Instant};
 /// extend a few more plies for captures, so 64 is generous.
 pub const MAX_SEARCH_PLY: usize = 64;
 
+/// Deepest iteration the time-managed searches will start. Keeps
+/// killers and the PV table inside `MAX_SEARCH_PLY` even after a few
+/// quiescence plies; raise `MAX_SEARCH_PLY` first if this goes up.
+const MAX_ITERATIVE_DEPTH: i32 = 20;
+
+/// Upper bound for [`Searcher::set_threads`] (and the UCI `Threads`
+/// option).
+pub const MAX_THREADS: usize = 256;
+
 /// Mate score returned by the search when the side-to-move is checkmated.
 ///
 /// Used as `-MATE_SCORE + ply` so closer mates score larger in
@@ -61,13 +123,341 @@ pub const MAX_SEARCH_PLY: usize = 64;
 /// 900 cp, the whole board ~5000 cp).
 pub const MATE_SCORE: i64 = 30000;
 
+/// Smallest magnitude that still counts as a mate score. A mate can be
+/// at most `MAX_SEARCH_PLY` plies away, so anything at or beyond this
+/// bound is "mate in N" rather than a centipawn evaluation.
+pub const MATE_THRESHOLD: i64 = MATE_SCORE - MAX_SEARCH_PLY as i64;
+
+/// Score of a position a Syzygy table says is won, `ply` plies below
+/// the root: `TB_WIN_SCORE - ply`. Below every mate, so a real mate is
+/// still preferred, and above every evaluation.
+pub const TB_WIN_SCORE: i64 = MATE_THRESHOLD - MAX_SEARCH_PLY as i64;
+
+/// Smallest magnitude of a mate or tablebase score: the scores whose
+/// meaning depends on the ply they were found at.
+const PLY_SCORE_THRESHOLD: i64 = TB_WIN_SCORE - MAX_SEARCH_PLY as i64;
+
+/// A raw search score translated into what it means for the side it's
+/// measured from. Produced by [`Score::from_raw`]; its `Display` impl
+/// is the UCI `score` payload (`cp 35`, `mate 3`, `mate -2`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    /// The side this score belongs to mates in `n` moves (not plies).
+    MateIn(i32),
+    /// The side this score belongs to is mated in `n` moves. `MatedIn(0)`
+    /// means it is checkmated on the board right now.
+    MatedIn(i32),
+    /// An ordinary evaluation in centipawns.
+    Centipawns(i64),
+}
+
+impl Score {
+    /// Classify a raw search score. The POV is preserved: pass a
+    /// side-to-move score to get a side-to-move answer, a White-POV
+    /// score to get a White-POV answer.
+    ///
+    /// Mate scores are `MATE_SCORE - ply` for the mating side, so the
+    /// ply count is recovered from the distance to `MATE_SCORE` and
+    /// rounded up to whole moves: mate on the next ply (our move) is
+    /// mate in 1, mate three plies away is mate in 2. Rounding up also
+    /// gives the natural answer for a White-POV score taken with Black
+    /// to move, where the parities are swapped.
+    pub fn from_raw(score: i64) -> Score {
+        if score >= MATE_THRESHOLD {
+            let plies = MATE_SCORE - score;
+            Score::MateIn(((plies + 1) / 2) as i32)
+        } else if score <= -MATE_THRESHOLD {
+            let plies = MATE_SCORE + score;
+            Score::MatedIn(((plies + 1) / 2) as i32)
+        } else {
+            Score::Centipawns(score)
+        }
+    }
+}
+
+impl std::fmt::Display for Score {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Score::MateIn(n) => write!(f, "mate {n}"),
+            Score::MatedIn(n) => write!(f, "mate -{n}"),
+            Score::Centipawns(cp) => write!(f, "cp {cp}"),
+        }
+    }
+}
+
+/// Convert a score found `ply` plies below the root into the form a
+/// transposition-table entry must store.
+///
+/// Mate scores are root-relative (`MATE_SCORE - ply_of_mate`), but a
+/// cached entry may be probed from a different ply than the one it was
+/// stored from. Storing "mate N plies *from this node*" instead keeps
+/// the entry valid wherever it's probed; [`score_from_tt`] re-anchors
+/// it to the probing node's ply. Tablebase wins and losses are
+/// root-relative the same way. Other scores pass through.
+pub fn score_to_tt(score: i64, ply: usize) -> i64 {
+    if score >= PLY_SCORE_THRESHOLD {
+        score + ply as i64
+    } else if score <= -PLY_SCORE_THRESHOLD {
+        score - ply as i64
+    } else {
+        score
+    }
+}
+
+/// Inverse of [`score_to_tt`]: turn a node-relative mate score read
+/// from the transposition table at `ply` back into a root-relative one.
+pub fn score_from_tt(score: i64, ply: usize) -> i64 {
+    if score >= PLY_SCORE_THRESHOLD {
+        score - ply as i64
+    } else if score <= -PLY_SCORE_THRESHOLD {
+        score + ply as i64
+    } else {
+        score
+    }
+}
+
+// ---------------------------------------------------------------------
+// Limits, results and the handle for steering a running search.
+// ---------------------------------------------------------------------
+
+/// How far [`Searcher::search`] may go. `None` means unlimited, so
+/// `SearchLimits::default()` searches until stopped (UCI `go infinite`
+/// and `go ponder`) or until `MAX_ITERATIVE_DEPTH`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    /// Deepest iteration to run.
+    pub depth: Option<i32>,
+    /// Wall-clock budget, counted from the start of the search.
+    pub time: Option<Duration>,
+    /// Node budget for the main thread. Like the hard deadline, it
+    /// aborts the iteration it runs out in.
+    pub nodes: Option<u64>,
+}
+
+/// Search heuristics that can be switched off one at a time, to
+/// measure what each is worth: compare node counts (or, with the
+/// `stats` feature, the counters) of the same search with and without
+/// it. Everything is on by default, and that's how the engine plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchFeatures {
+    /// Try the quiet move that last refuted the opponent's previous
+    /// move right after the killers.
+    pub countermoves: bool,
+    /// Add continuation history to the quiet-move ordering score.
+    pub continuation_history: bool,
+    /// Reverse futility pruning (see `RFP_MARGIN`).
+    pub reverse_futility: bool,
+    /// Razoring (see `RAZOR_MARGINS`).
+    pub razoring: bool,
+    /// Futility pruning of quiet moves (see `FUTILITY_MARGINS`).
+    pub futility: bool,
+    /// Late-move pruning (see `LMP_MOVE_COUNTS`).
+    pub late_move_pruning: bool,
+    /// The singularity test on hash moves, and with it both singular
+    /// extensions and multi-cut (see `SINGULAR_MIN_DEPTH`).
+    pub singular_extensions: bool,
+    /// Internal iterative reduction (see `IIR_MIN_DEPTH`).
+    pub internal_iterative_reduction: bool,
+}
+
+impl Default for SearchFeatures {
+    fn default() -> Self {
+        Self {
+            countermoves: true,
+            continuation_history: true,
+            reverse_futility: true,
+            razoring: true,
+            futility: true,
+            late_move_pruning: true,
+            singular_extensions: true,
+            internal_iterative_reduction: true,
+        }
+    }
+}
+
+/// One MultiPV line: the move, its White-POV score and its principal
+/// variation.
+pub type PvLine = (Move, i64, Vec<Move>);
+
+/// What [`Searcher::search`] found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    /// One `(move, White-POV score, principal variation)` per MultiPV
+    /// line, best first. Empty when the position has no legal moves.
+    pub lines: Vec<(Move, i64, Vec<Move>)>,
+    /// White-POV score of the best line, or the checkmate / stalemate
+    /// score when there are no lines.
+    pub score: i64,
+    /// Deepest iteration that completed.
+    pub depth: i32,
+    /// Counters for this search; all but `nodes` read zero unless
+    /// built with the `stats` feature.
+    pub stats: SearchStats,
+}
+
+impl SearchResult {
+    pub fn best_move(&self) -> Option<Move> {
+        self.lines.first().map(|(mv, _, _)| *mv)
+    }
+
+    /// The reply we expect from the opponent: the second move of the
+    /// best line, if the PV is that long. UCI's `bestmove X ponder Y`.
+    pub fn ponder_move(&self) -> Option<Move> {
+        self.lines.first().and_then(|(_, _, pv)| pv.get(1).copied())
+    }
+
+    /// The best line's principal variation.
+    pub fn pv(&self) -> &[Move] {
+        self.lines.first().map_or(&[], |(_, _, pv)| pv.as_slice())
+    }
+}
+
+/// One root move as [`Searcher::analyse_root`] saw it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RootMoveInfo {
+    pub mv: Move,
+    /// White-POV score. Exact, not a bound: every root move is
+    /// searched with a full window.
+    pub score: i64,
+    /// Depth `score` comes from. 0 if the search was stopped before
+    /// this move had been searched at all, in which case `score` is
+    /// the static evaluation after the move.
+    pub depth: i32,
+    /// Principal variation, starting with `mv`.
+    pub pv: Vec<Move>,
+    /// Main-thread nodes spent on this move at `depth`.
+    pub nodes: u64,
+}
+
+/// Soft and hard time limits for the running search.
+#[derive(Debug, Clone, Copy)]
+struct Deadlines {
+    /// No new iteration starts if it would likely end past this.
+    soft: Instant,
+    /// The search is aborted mid-iteration once this passes.
+    hard: Instant,
+}
+
+/// A cheap, cloneable remote control for a [`Searcher`]'s search,
+/// usable from any thread while the search runs. Obtained from
+/// [`Searcher::handle`].
+///
+/// Requests made before a search starts are honoured by it; when a
+/// search finishes the handle is reset, so a request never leaks into
+/// the next search except through that window. Callers that can't rule
+/// the window out call [`SearchHandle::reset`] before starting.
+#[derive(Debug, Clone, Default)]
+pub struct SearchHandle {
+    stop: Arc<AtomicBool>,
+    deadlines: Arc<Mutex<Option<Deadlines>>>,
+}
+
+impl SearchHandle {
+    /// Ask the search to finish as soon as possible. The searcher still
+    /// returns its best result so far (the last completed iteration).
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Give the search `budget` of thinking time, starting now. Used
+    /// on `ponderhit` to switch an unlimited ponder search over to
+    /// normal time management without restarting it. The hard limit
+    /// is twice the budget: past that even the iteration in flight is
+    /// abandoned.
+    pub fn set_time_budget(&self, budget: Duration) {
+        let now = Instant::now();
+        *self.deadlines.lock().unwrap() = Some(Deadlines {
+            soft: now + budget,
+            hard: now + budget * 2,
+        });
+    }
+
+    /// Clear any pending stop request and time budget.
+    pub fn reset(&self) {
+        self.stop.store(false, Ordering::Relaxed);
+        *self.deadlines.lock().unwrap() = None;
+    }
+
+    fn soft_deadline(&self) -> Option<Instant> {
+        self.deadlines.lock().unwrap().map(|d| d.soft)
+    }
+
+    fn past_hard_deadline(&self) -> bool {
+        self.deadlines
+            .lock()
+            .unwrap()
+            .is_some_and(|d| Instant::now() >= d.hard)
+    }
+}
+
+/// How often (in nodes) a search thread looks at the clock. Reading
+/// the time at every node would cost more than the nodes themselves.
+const TIME_CHECK_INTERVAL: u64 = 2048;
+
+/// Ceiling for every history-style table. Updates use "gravity": an
+/// entry moves toward ±`MAX_HISTORY` in proportion to how far it still
+/// is from it, so scores saturate instead of growing without bound
+/// and a move that stops working loses its rank quickly.
+const MAX_HISTORY: i64 = 16_384;
+
+/// Largest single history update, reached at depth 39. Keeps one deep
+/// cutoff from pinning an entry at the ceiling.
+const MAX_HISTORY_BONUS: i64 = 1_536;
+
+/// `(piece, to-square)` pairs: the key continuation history is indexed
+/// by, on both sides.
+const PIECE_SQUARES: usize = 12 * 64;
+
+// Shallow-depth pruning. Every margin is in centipawns and indexed by
+// remaining depth (slot 0 unused); none of it runs at PV nodes or when
+// the side to move is in check. See `negamax_ab` for how each is used.
+
+/// Reverse futility pruning (static null move): a non-PV node whose
+/// static eval beats beta by `RFP_MARGIN * depth` is assumed to hold.
+const RFP_MAX_DEPTH: i32 = 6;
+const RFP_MARGIN: i64 = 90;
+
+/// Razoring: a node whose static eval trails alpha by this much goes
+/// straight to quiescence search and is trusted if that fails low too.
+const RAZOR_MARGINS: [i64; 3] = [0, 300, 550];
+
+/// Futility pruning: quiet moves are skipped once the static eval
+/// plus this margin still can't reach alpha.
+const FUTILITY_MARGINS: [i64; 4] = [0, 150, 280, 420];
+
+/// Late-move pruning: after this many moves at a shallow node, the
+/// remaining quiet moves (ordered last by history) are skipped.
+const LMP_MOVE_COUNTS: [usize; 4] = [0, 6, 10, 16];
+
+/// Internal iterative reduction: non-PV nodes at least this deep that
+/// have no hash move to try first are searched one ply shallower, in
+/// iterative deepening only.
+const IIR_MIN_DEPTH: i32 = 4;
+
+/// Singular extensions: shallowest node that tests its hash move for
+/// singularity. The test costs a reduced search of every other move,
+/// so it only pays for itself with depth to spare.
+const SINGULAR_MIN_DEPTH: i32 = 6;
+
+/// How far below the table score (per ply of depth) every alternative
+/// must fail for the hash move to count as singular.
+const SINGULAR_MARGIN: i64 = 3;
+
+/// A table entry this many plies shallower than the node is still
+/// trusted as the singularity baseline.
+const SINGULAR_TT_DEPTH_SLACK: i32 = 3;
+
 // ---------------------------------------------------------------------
 // SearchState — private to the search module.
 //
 // Holds the cheap move-ordering tables. They live as a struct (rather
-// than inline fields on Searcher) so we can pass `&mut SearchState`
-// around if we ever want to parallelise. Today's single-threaded
-// search just goes through `&mut self.state`.
+// than inline fields on Searcher) because they are strictly
+// per-thread: under Lazy SMP every helper thread gets its own, while
+// the transposition table is the only thing the threads share.
 // ---------------------------------------------------------------------
 
 struct SearchState {
@@ -80,6 +470,36 @@ struct SearchState {
     /// that frequently cause cutoffs anywhere in the tree get bumped
     /// up. Indexed by `Square::to_bit_index()` (0..64).
     history: [[i64; 64]; 64],
+
+    /// The quiet move that last refuted each opponent move, indexed by
+    /// the refuted move's `(piece, to)`. The "natural reply" heuristic:
+    /// the answer to Bxf7+ is usually Kxf7 wherever it happens.
+    countermoves: [[Option<Move>; 64]; 12],
+
+    /// Continuation history: how well a quiet `(piece, to)` has done
+    /// as a reply to the `(piece, to)` played one ply earlier
+    /// (`continuation[0]`) and two plies earlier (`continuation[1]`,
+    /// our own previous move). Flattened `PIECE_SQUARES²` tables on the
+    /// heap — too big for a thread stack even as `i16`, which gravity's
+    /// `±MAX_HISTORY` bound makes sufficient.
+    continuation: [Vec<i16>; 2],
+
+    /// `(piece, to)` of the move being searched at each ply, so nodes
+    /// further down can find their one- and two-ply predecessors.
+    played: [Option<(Piece, u8)>; MAX_SEARCH_PLY],
+
+    /// Move to leave out at each ply. Set only while a node runs its
+    /// singularity test, which re-searches the same position without
+    /// the hash move.
+    excluded: [Option<Move>; MAX_SEARCH_PLY],
+
+    /// Triangular principal-variation table. Row `ply` holds the best
+    /// line found so far from the node at that ply, in slots
+    /// `ply..pv_len[ply]`. A node copies its best child's row into its
+    /// own whenever it raises alpha, so after the search row 1 holds
+    /// the continuation of whichever root move was searched last.
+    pv: [[Option<Move>; MAX_SEARCH_PLY]; MAX_SEARCH_PLY],
+    pv_len: [usize; MAX_SEARCH_PLY],
 }
 
 impl SearchState {
@@ -87,12 +507,60 @@ impl SearchState {
         Self {
             killers: [[None; 2]; MAX_SEARCH_PLY],
             history: [[0; 64]; 64],
+            countermoves: [[None; 64]; 12],
+            continuation: [
+                vec![0; PIECE_SQUARES * PIECE_SQUARES],
+                vec![0; PIECE_SQUARES * PIECE_SQUARES],
+            ],
+            played: [None; MAX_SEARCH_PLY],
+            excluded: [None; MAX_SEARCH_PLY],
+            pv: [[None; MAX_SEARCH_PLY]; MAX_SEARCH_PLY],
+            pv_len: [0; MAX_SEARCH_PLY],
+        }
+    }
+
+    /// Start a node at `ply` with an empty principal variation.
+    fn clear_pv(&mut self, ply: usize) {
+        if ply < MAX_SEARCH_PLY {
+            self.pv_len[ply] = ply;
+        }
+    }
+
+    /// `mv` raised alpha at `ply`: it becomes the head of this node's
+    /// PV, followed by the line the child node just reported.
+    fn update_pv(&mut self, ply: usize, mv: Move) {
+        if ply >= MAX_SEARCH_PLY {
+            return;
+        }
+        self.pv[ply][ply] = Some(mv);
+        let child = ply + 1;
+        if child < MAX_SEARCH_PLY {
+            for i in child..self.pv_len[child] {
+                self.pv[ply][i] = self.pv[child][i];
+            }
+            self.pv_len[ply] = self.pv_len[child].max(child);
+        } else {
+            self.pv_len[ply] = child;
+        }
+    }
+
+    /// The principal variation currently stored for `ply`.
+    fn pv_line(&self, ply: usize) -> Vec<Move> {
+        if ply >= MAX_SEARCH_PLY {
+            return Vec::new();
         }
+        self.pv[ply][ply..self.pv_len[ply]]
+            .iter()
+            .flatten()
+            .copied()
+            .collect()
     }
 
-    /// Called after a beta cutoff. Updates killers (only for quiet
-    /// moves) and history (only for quiet moves, weighted by depth^2
-    /// so deep cutoffs carry more signal).
+    /// Called after a beta cutoff. Updates killers, the countermove
+    /// for the previous move, history and continuation history — all
+    /// for quiet moves only. The bonus is depth^2 so deep cutoffs carry
+    /// more signal. `played[ply]` must hold the cutoff move's
+    /// `(piece, to)` for the continuation update to happen.
     fn record_cutoff(&mut self, ply: usize, mv: Move, depth: i32, is_capture: bool) {
         if is_capture {
             return;
@@ -103,34 +571,178 @@ impl SearchState {
                 self.killers[ply][1] = self.killers[ply][0];
                 self.killers[ply][0] = Some(mv);
             }
+            if let Some((piece, to)) = self.previous_move(ply, 1) {
+                self.countermoves[piece as usize][to as usize] = Some(mv);
+            }
         }
+        let bonus = history_bonus(depth);
         let from = mv.src.to_bit_index() as usize;
         let to = mv.target.to_bit_index() as usize;
-        self.history[from][to] += (depth as i64).pow(2);
+        apply_gravity(&mut self.history[from][to], bonus);
+        if let Some(current) = self.played.get(ply).copied().flatten() {
+            self.update_continuation(ply, current, bonus);
+        }
+    }
+
+    /// A quiet move was searched at `ply` before another move cut off:
+    /// it had its chance and didn't refute the position, so push its
+    /// history and continuation scores down by the same amount the
+    /// cutoff move went up.
+    fn penalise_quiet(&mut self, ply: usize, mv: Move, piece: Piece, depth: i32) {
+        let malus = -history_bonus(depth);
+        let from = mv.src.to_bit_index() as usize;
+        let to = mv.target.to_bit_index() as usize;
+        apply_gravity(&mut self.history[from][to], malus);
+        self.update_continuation(ply, (piece, to as u8), malus);
+    }
+
+    fn update_continuation(&mut self, ply: usize, current: (Piece, u8), delta: i64) {
+        for back in 1..=2 {
+            if let Some(previous) = self.previous_move(ply, back) {
+                let entry = &mut self.continuation[back - 1][continuation_index(previous, current)];
+                let mut value = *entry as i64;
+                apply_gravity(&mut value, delta);
+                *entry = value as i16;
+            }
+        }
+    }
+
+    /// `(piece, to)` of the move played `back` plies above `ply`.
+    fn previous_move(&self, ply: usize, back: usize) -> Option<(Piece, u8)> {
+        if ply < back || ply - back >= MAX_SEARCH_PLY {
+            return None;
+        }
+        self.played[ply - back]
+    }
+
+    /// The quiet-move ordering score: main history plus, with
+    /// `continuation`, both continuation histories for `(piece, to)` at
+    /// `ply`.
+    fn quiet_score(&self, ply: usize, mv: &Move, piece: Piece, continuation: bool) -> i64 {
+        let from = mv.src.to_bit_index() as usize;
+        let to = mv.target.to_bit_index();
+        let mut score = self.history[from][to as usize];
+        if !continuation {
+            return score;
+        }
+        for back in 1..=2 {
+            if let Some(previous) = self.previous_move(ply, back) {
+                score +=
+                    self.continuation[back - 1][continuation_index(previous, (piece, to))] as i64;
+            }
+        }
+        score
+    }
+
+    /// Between searches: halve every history-style score so what the
+    /// last search learned still orders the next one, but fresh
+    /// evidence quickly outweighs it. Killers and countermoves are
+    /// cheap to relearn and stay as they are.
+    fn age(&mut self) {
+        for row in self.history.iter_mut() {
+            for entry in row.iter_mut() {
+                *entry /= 2;
+            }
+        }
+        for table in self.continuation.iter_mut() {
+            for entry in table.iter_mut() {
+                *entry /= 2;
+            }
+        }
     }
 }
 
+/// History bonus for a cutoff at `depth`: depth², capped.
+fn history_bonus(depth: i32) -> i64 {
+    (depth as i64).pow(2).min(MAX_HISTORY_BONUS)
+}
+
+/// Gravity update: add `delta`, minus a pull back toward zero that
+/// grows with the entry's size, so `|entry|` never exceeds
+/// `MAX_HISTORY`.
+fn apply_gravity(entry: &mut i64, delta: i64) {
+    *entry += delta - *entry * delta.abs() / MAX_HISTORY;
+}
+
+fn continuation_index(previous: (Piece, u8), current: (Piece, u8)) -> usize {
+    let prev = previous.0 as usize * 64 + previous.1 as usize;
+    let cur = current.0 as usize * 64 + current.1 as usize;
+    prev * PIECE_SQUARES + cur
+}
+
 // ---------------------------------------------------------------------
 // Searcher — the public search engine.
 // ---------------------------------------------------------------------
 
 /// The search engine. Owns search-internal state (killer/history
-/// tables, deterministic-mode flag) and exposes a small interface for
-/// "find me the best move from this position."
+/// tables, transposition table, helper threads, deterministic-mode
+/// flag) and exposes a small interface for "find me the best move from
+/// this position."
 ///
 /// One Searcher per UCI session is the typical lifetime: state
-/// persists across moves within a game (killer/history mature, future
-/// TT accumulates), and a fresh `Searcher::new()` starts the next
-/// game from a clean slate. Tests typically construct a fresh
-/// Searcher per case.
+/// persists across moves within a game (killer/history mature, the TT
+/// accumulates), and a fresh `Searcher::new()` starts the next game
+/// from a clean slate. Tests typically construct a fresh Searcher per
+/// case.
 pub struct Searcher {
     state: SearchState,
 
+    /// Shared with every helper thread.
+    tt: Arc<TranspositionTable>,
+
+    /// Stop flag and deadlines, shared with the helpers and with
+    /// whoever holds a [`SearchHandle`]. The stop flag is raised when
+    /// the search must unwind: by the main thread once it has its
+    /// answer (to release the helpers), by the hard deadline, or from
+    /// outside. Checked at every node.
+    handle: SearchHandle,
+
+    /// Nodes visited by this thread in the current (or last) search.
+    /// Paces the clock checks and feeds [`Searcher::nodes`].
+    nodes: u64,
+
+    /// Configured search threads, main thread included.
+    threads: usize,
+
+    /// One Searcher per helper thread, kept between searches so their
+    /// killer/history tables mature like the main thread's do.
+    helpers: Vec<Searcher>,
+
     /// When `true`, suppresses random tie-breaking among equal-scoring
     /// root moves so benchmarks and tests are reproducible. Read from
     /// the `CHESS_DETERMINISTIC` environment variable at construction
     /// time; tests can override via [`Searcher::new_deterministic`].
     deterministic: bool,
+
+    /// Contempt in centipawns: how much worse than 0 a draw is for the
+    /// side to move at the root (negative: better). See
+    /// [`Searcher::set_contempt`].
+    contempt: i64,
+
+    /// Scale contempt with the game phase at the root.
+    dynamic_contempt: bool,
+
+    /// The side to move at the root of the current search and the
+    /// contempt in effect for it, fixed when the search starts so
+    /// every node (and every helper thread) scores draws alike.
+    root_side: Color,
+    root_contempt: i64,
+
+    /// Deliberate weakening, if any (see [`Searcher::set_strength`]).
+    strength: Option<Strength>,
+
+    /// The running search's node budget, from [`SearchLimits::nodes`].
+    node_limit: Option<u64>,
+
+    /// Whether the running search deepens iteratively, so a node cut
+    /// short now is searched again by the next iteration.
+    iterating: bool,
+
+    /// This thread's counters for the current (or last) search.
+    stats: SearchStats,
+
+    /// Heuristics in use (see [`Searcher::set_features`]).
+    features: SearchFeatures,
 }
 
 impl Default for Searcher {
@@ -144,10 +756,7 @@ impl Searcher {
     /// the environment to decide whether random tie-breaking among
     /// equal-scoring root moves is enabled.
     pub fn new() -> Self {
-        Self {
-            state: SearchState::new(),
-            deterministic: std::env::var("CHESS_DETERMINISTIC").is_ok(),
-        }
+        Self::with_deterministic(std::env::var("CHESS_DETERMINISTIC").is_ok())
     }
 
     /// Construct a Searcher with deterministic root-move tie-breaking
@@ -155,14 +764,268 @@ impl Searcher {
     /// a test wants stable output without having to set
     /// `CHESS_DETERMINISTIC=1` in the test runner.
     pub fn new_deterministic() -> Self {
+        Self::with_deterministic(true)
+    }
+
+    fn with_deterministic(deterministic: bool) -> Self {
         Self {
             state: SearchState::new(),
-            deterministic: true,
+            tt: Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+            handle: SearchHandle::default(),
+            nodes: 0,
+            threads: 1,
+            helpers: Vec::new(),
+            deterministic,
+            contempt: 0,
+            dynamic_contempt: false,
+            root_side: Color::White,
+            root_contempt: 0,
+            strength: None,
+            node_limit: None,
+            iterating: false,
+            stats: SearchStats::default(),
+            features: SearchFeatures::default(),
+        }
+    }
+
+    /// Search with `threads` threads (clamped to `1..=MAX_THREADS`).
+    /// Ignored in deterministic mode, which always uses one.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.clamp(1, MAX_THREADS);
+        self.helpers.truncate(self.threads - 1);
+    }
+
+    /// Number of threads a search will actually use.
+    pub fn threads(&self) -> usize {
+        if self.deterministic {
+            1
+        } else {
+            self.threads
+        }
+    }
+
+    /// Replace the transposition table with an empty one of
+    /// `megabytes` MB.
+    pub fn set_hash_size(&mut self, megabytes: usize) {
+        self.tt = Arc::new(TranspositionTable::new(megabytes));
+        // Helpers hold the old table; they'll be rebuilt on demand.
+        self.helpers.clear();
+    }
+
+    /// Empty the transposition table without resizing it.
+    pub fn clear_hash(&mut self) {
+        self.tt.clear();
+    }
+
+    /// Set the contempt factor, in centipawns. Every draw the search
+    /// finds — stalemate, repetition, the fifty-move rule, insufficient
+    /// material — scores `-contempt` for the side to move at the root
+    /// and `+contempt` for its opponent. Positive values make the
+    /// engine play on against a weaker opponent rather than settle for
+    /// a draw; negative values make it take draws against a stronger
+    /// one. 0 (the default) scores draws as exactly even.
+    pub fn set_contempt(&mut self, centipawns: i64) {
+        self.contempt = centipawns;
+    }
+
+    /// The configured contempt, in centipawns.
+    pub fn contempt(&self) -> i64 {
+        self.contempt
+    }
+
+    /// With dynamic contempt on, the contempt applied in a search is
+    /// scaled by the root position's game phase: full strength with
+    /// all the pieces on the board, fading to nothing in a pawn
+    /// ending, where a draw is usually the honest result.
+    pub fn set_dynamic_contempt(&mut self, enabled: bool) {
+        self.dynamic_contempt = enabled;
+    }
+
+    /// Play at `strength` instead of full strength, or at full
+    /// strength again with `None`. Applies to [`Searcher::search`] and
+    /// the time-managed wrappers built on it: the limits passed in are
+    /// tightened to the level's depth and node caps, and the move
+    /// returned is picked from the top candidates by
+    /// [`Strength::choose`]. The fixed-depth `find_best_move*` calls
+    /// are unaffected.
+    pub fn set_strength(&mut self, strength: Option<Strength>) {
+        self.strength = strength;
+    }
+
+    /// The configured strength limit, if any.
+    pub fn strength(&self) -> Option<Strength> {
+        self.strength
+    }
+
+    /// Switch search heuristics on or off (see [`SearchFeatures`]).
+    pub fn set_features(&mut self, features: SearchFeatures) {
+        self.features = features;
+        // Helpers copy the features when they're built.
+        self.helpers.clear();
+    }
+
+    /// The heuristics in use.
+    pub fn features(&self) -> SearchFeatures {
+        self.features
+    }
+
+    /// Nodes visited by the last search, helper threads included.
+    /// Main-search and quiescence nodes each count once.
+    pub fn nodes(&self) -> u64 {
+        let helpers = self.helpers.iter().take(self.threads() - 1);
+        self.nodes + helpers.map(|h| h.nodes).sum::<u64>()
+    }
+
+    /// Statistics for the last search, helper threads' counters
+    /// included (per-depth entries are the main thread's). Only
+    /// `nodes` is filled in unless built with the `stats` feature.
+    pub fn stats(&self) -> SearchStats {
+        let mut stats = self.stats.clone();
+        stats.nodes = self.nodes;
+        for helper in self.helpers.iter().take(self.threads() - 1) {
+            stats.merge(&helper.stats);
+            stats.nodes += helper.nodes;
+        }
+        stats
+    }
+
+    /// A remote control for this Searcher's searches (see
+    /// [`SearchHandle`]). Clone it out before handing the Searcher to
+    /// the thread that runs the search.
+    pub fn handle(&self) -> SearchHandle {
+        self.handle.clone()
+    }
+
+    /// A fresh helper sharing this Searcher's table and handle.
+    fn helper(&self) -> Searcher {
+        Searcher {
+            state: SearchState::new(),
+            tt: Arc::clone(&self.tt),
+            handle: self.handle.clone(),
+            nodes: 0,
+            threads: 1,
+            helpers: Vec::new(),
+            deterministic: self.deterministic,
+            contempt: self.contempt,
+            dynamic_contempt: self.dynamic_contempt,
+            root_side: self.root_side,
+            root_contempt: self.root_contempt,
+            strength: None,
+            node_limit: None,
+            iterating: true,
+            stats: SearchStats::default(),
+            features: self.features,
+        }
+    }
+
+    /// Run `main` on this thread with the Lazy SMP helpers searching
+    /// alongside it up to `max_depth`, then stop the helpers and
+    /// return `main`'s result.
+    ///
+    /// Also the one place a search is set up and torn down: it
+    /// re-syncs the board's hash (in case the caller edited the board
+    /// by hand) and ages the transposition table, and when the search
+    /// is over it resets the handle. The handle is deliberately *not*
+    /// reset up front, so a `stop` that arrives before the search
+    /// thread gets going still stops it.
+    fn run_smp<R>(
+        &mut self,
+        board: &mut Board,
+        max_depth: i32,
+        main: impl FnOnce(&mut Self, &mut Board) -> R,
+    ) -> R {
+        board.hash = board.compute_hash();
+        board.pawn_hash = board.compute_pawn_hash();
+        self.tt.new_search();
+        self.state.age();
+        self.nodes = 0;
+        self.stats.record(|s| *s = SearchStats::default());
+        self.root_side = board.side_to_move;
+        self.root_contempt = if self.dynamic_contempt {
+            self.contempt * board.game_phase() as i64 / MAX_PHASE as i64
+        } else {
+            self.contempt
+        };
+
+        let threads = self.threads();
+        if threads <= 1 {
+            let result = main(self, board);
+            self.handle.reset();
+            return result;
+        }
+        while self.helpers.len() < threads - 1 {
+            let helper = self.helper();
+            self.helpers.push(helper);
+        }
+
+        let mut helpers = std::mem::take(&mut self.helpers);
+        for helper in &mut helpers {
+            helper.root_side = self.root_side;
+            helper.root_contempt = self.root_contempt;
+        }
+        let result = std::thread::scope(|scope| {
+            for (id, helper) in helpers.iter_mut().enumerate() {
+                let mut helper_board = board.clone();
+                scope.spawn(move || helper.helper_search(&mut helper_board, max_depth, id + 1));
+            }
+            let result = main(self, board);
+            self.handle.stop();
+            result
+        });
+        self.helpers = helpers;
+        self.handle.reset();
+        result
+    }
+
+    /// A helper thread's whole job: iterative deepening until the main
+    /// thread raises the stop flag. Results only matter through what
+    /// they leave in the shared table. Odd-numbered helpers skip depth
+    /// 1 so half the helpers are always one iteration ahead.
+    fn helper_search(&mut self, board: &mut Board, max_depth: i32, id: usize) {
+        self.state.age();
+        self.nodes = 0;
+        self.stats.record(|s| *s = SearchStats::default());
+        let first_depth = 1 + (id % 2) as i32;
+        for depth in first_depth..=max_depth {
+            if self.stopped() {
+                break;
+            }
+            self.search_root(board, depth, &[]);
+        }
+    }
+
+    /// What a drawn position is worth at this node, in the side to
+    /// move's POV: contempt counts against the root side.
+    #[inline]
+    fn draw_score(&self, board: &Board) -> i64 {
+        if board.side_to_move == self.root_side {
+            -self.root_contempt
+        } else {
+            self.root_contempt
+        }
+    }
+
+    #[inline]
+    fn stopped(&self) -> bool {
+        self.handle.is_stopped()
+    }
+
+    /// Count a node, and every `TIME_CHECK_INTERVAL` nodes enforce the
+    /// hard deadline by raising the stop flag for every thread.
+    #[inline]
+    fn count_node(&mut self) {
+        self.nodes += 1;
+        if self.node_limit.is_some_and(|limit| self.nodes >= limit) {
+            self.handle.stop();
+        }
+        if self.nodes.is_multiple_of(TIME_CHECK_INTERVAL) && self.handle.past_hard_deadline() {
+            self.handle.stop();
         }
     }
 
     // ------------------------------------------------------------
-    // Public entry points: find_best_move, find_best_move_within
+    // Public entry points: find_best_move, find_best_move_within,
+    // their MultiPV counterparts, and `_with_stats` variants of each
     // ------------------------------------------------------------
 
     /// Search the position to a fixed depth and return the best move
@@ -175,11 +1038,100 @@ impl Searcher {
     /// specially; an `Option<Move>` is the only honest return type for
     /// "what's the best move" given that there might not be one.
     ///
+    /// Like the other `find_best_move*` functions, this has a
+    /// `_with_stats` variant that also returns the search's
+    /// [`SearchStats`].
+    ///
     /// Borrows `board` mutably because the underlying make/unmake
     /// algorithm temporarily mutates the board during recursion. The
     /// board is restored to its original state before this method
     /// returns.
     pub fn find_best_move(&mut self, board: &mut Board, depth: i32) -> (Option<Move>, i64) {
+        let start = Instant::now();
+        let (best_move, score, _pv) =
+            self.run_smp(board, depth, |s, b| s.search_root(b, depth, &[]));
+        let nodes = self.nodes;
+        self.stats.record(|s| {
+            s.depths.push(DepthStats {
+                depth,
+                nodes,
+                time: start.elapsed(),
+                branching_factor: 0.0,
+            })
+        });
+        (best_move, score)
+    }
+
+    /// [`Searcher::find_best_move`], also returning the search's
+    /// statistics (see [`Searcher::stats`]).
+    pub fn find_best_move_with_stats(
+        &mut self,
+        board: &mut Board,
+        depth: i32,
+    ) -> (Option<Move>, i64, SearchStats) {
+        let (best_move, score) = self.find_best_move(board, depth);
+        (best_move, score, self.stats())
+    }
+
+    /// MultiPV search: the best `num_pv` root moves, each with its
+    /// score (White's POV) and principal variation, best first.
+    ///
+    /// Runs the root search `num_pv` times, each time excluding the
+    /// moves already reported, so line k is the best move once lines
+    /// 1..k-1 are taken off the table. Returns fewer than `num_pv`
+    /// lines when the position has fewer legal moves, and an empty
+    /// list when it has none.
+    pub fn find_best_moves_multipv(
+        &mut self,
+        board: &mut Board,
+        depth: i32,
+        num_pv: usize,
+    ) -> Vec<(Move, i64, Vec<Move>)> {
+        self.run_smp(board, depth, |s, b| s.multipv_lines(b, depth, num_pv))
+    }
+
+    /// [`Searcher::find_best_moves_multipv`], also returning the
+    /// search's statistics.
+    pub fn find_best_moves_multipv_with_stats(
+        &mut self,
+        board: &mut Board,
+        depth: i32,
+        num_pv: usize,
+    ) -> (Vec<PvLine>, SearchStats) {
+        let lines = self.find_best_moves_multipv(board, depth, num_pv);
+        (lines, self.stats())
+    }
+
+    /// The exclusion loop behind [`Searcher::find_best_moves_multipv`].
+    fn multipv_lines(
+        &mut self,
+        board: &mut Board,
+        depth: i32,
+        num_pv: usize,
+    ) -> Vec<(Move, i64, Vec<Move>)> {
+        let mut lines = Vec::with_capacity(num_pv);
+        let mut excluded = Vec::with_capacity(num_pv);
+        while lines.len() < num_pv {
+            let (best_move, score, pv) = self.search_root(board, depth, &excluded);
+            let Some(mv) = best_move else { break };
+            excluded.push(mv);
+            lines.push((mv, score, pv));
+            if self.stopped() {
+                break;
+            }
+        }
+        lines
+    }
+
+    /// Root search shared by [`Searcher::find_best_move`] and the
+    /// MultiPV driver. Searches every legal root move not in `exclude`
+    /// and returns the best one with its White-POV score and PV.
+    fn search_root(
+        &mut self,
+        board: &mut Board,
+        depth: i32,
+        exclude: &[Move],
+    ) -> (Option<Move>, i64, Vec<Move>) {
         // Generate root moves once, then dispatch into the recursive
         // search. Most of the bookkeeping that used to live in
         // `find_best_move_with_state` has migrated here.
@@ -190,51 +1142,95 @@ impl Searcher {
         // Return a ply-zero mate score (or 0) so callers don't have to
         // special-case the empty result.
         if moves.is_empty() {
-            let in_check = side_in_check(board);
-            let score_pov = if in_check { -MATE_SCORE } else { 0 };
-            // Convert side-to-move POV → White POV for the public API.
-            let white_pov = if board.side_to_move == Color::Black {
-                -score_pov
-            } else {
-                score_pov
-            };
-            return (None, white_pov);
+            return (None, no_moves_score(board), Vec::new());
+        }
+
+        // MultiPV: every remaining move has already been reported.
+        moves.retain(|m| !exclude.contains(m));
+        if moves.is_empty() {
+            return (None, 0, Vec::new());
+        }
+
+        // With every reply in a table, search only the moves that keep
+        // the best result: the search below then picks among them.
+        if let Some(keep) = tablebase::root_moves(board, &moves) {
+            self.stats.record(|s| s.tb_hits += 1);
+            moves = keep;
         }
 
         // Order moves with the same heuristics negamax uses internally
-        // — captures via MVV-LVA, then killers, then history. Highest
+        // — the previous iteration's best move from the table, then
+        // captures via MVV-LVA, then killers, then history. Highest
         // score first (`Reverse` flips std's ascending sort).
-        moves.sort_unstable_by_key(|m| std::cmp::Reverse(self.order_score(board, m, 0)));
+        let hash_move = self.tt.probe(board.hash).and_then(|e| e.best_move);
+        moves.sort_unstable_by_key(|m| {
+            if Some(*m) == hash_move {
+                std::cmp::Reverse(i64::MAX)
+            } else {
+                std::cmp::Reverse(self.order_score(board, m, 0))
+            }
+        });
 
         // best_score lives in the side-to-move's POV. Initialise to
         // i64::MIN + 1 — using MIN itself would overflow when we
         // negate at the end for Black-to-move.
         let mut best_score = i64::MIN + 1;
         let mut best_move: Option<Move> = None;
+        let mut best_pv = Vec::new();
+        let first_move = moves[0];
 
         for mv in moves {
+            self.state.played[0] = moved_piece(board, &mv);
             board.apply_move(&mv);
             let score = -self.negamax_ab(board, depth - 1, 1, i64::MIN + 1, i64::MAX - 1);
             board.undo_last_move();
 
-            if score > best_score {
-                best_score = score;
-                best_move = Some(mv);
-            } else if score == best_score && !self.deterministic {
+            // Aborted mid-move: that move's score is garbage, but every
+            // move before it was searched completely.
+            if self.stopped() {
+                break;
+            }
+
+            // Every root move is searched with a full window, so the
+            // child's PV row is exact for this move right now (the
+            // next root move will overwrite it).
+            let take = if score > best_score {
+                true
+            } else {
                 // Random tie-break adds opening variety during real
                 // play. Disabled in deterministic mode for stable
                 // benches and test output.
-                if rand::random::<bool>() {
-                    best_move = Some(mv);
-                }
+                score == best_score && !self.deterministic && rand::random::<bool>()
+            };
+            if take {
+                best_score = score;
+                best_move = Some(mv);
+                best_pv = std::iter::once(mv).chain(self.state.pv_line(1)).collect();
             }
         }
 
+        // Stopped before even the first move finished. The caller only
+        // falls back on this if no earlier iteration completed, and the
+        // first move in order (usually the previous hash move) is the
+        // best guess there is.
+        if best_move.is_none() {
+            best_move = Some(first_move);
+            best_score = 0;
+            best_pv = vec![first_move];
+        }
+
+        // A MultiPV root result only covers the moves not excluded, so
+        // it isn't the position's value and mustn't be cached as one.
+        if exclude.is_empty() && !self.stopped() {
+            self.tt
+                .store(board.hash, best_move, best_score, depth, Bound::Exact);
+        }
+
         // Convert side-to-move POV → White POV at the seam.
         if board.side_to_move == Color::Black {
-            (best_move, -best_score)
+            (best_move, -best_score, best_pv)
         } else {
-            (best_move, best_score)
+            (best_move, best_score, best_pv)
         }
     }
 
@@ -254,35 +1250,322 @@ impl Searcher {
         board: &mut Board,
         time_budget: Duration,
     ) -> (Option<Move>, i64, i32) {
-        let deadline = Instant::now() + time_budget;
-        let mut best_move = None;
-        let mut best_score = 0_i64;
+        let limits = SearchLimits {
+            time: Some(time_budget),
+            ..SearchLimits::default()
+        };
+        let result = self.search(board, limits, 1);
+        (result.best_move(), result.score, result.depth)
+    }
+
+    /// [`Searcher::find_best_move_within`], also returning the
+    /// search's statistics.
+    pub fn find_best_move_within_with_stats(
+        &mut self,
+        board: &mut Board,
+        time_budget: Duration,
+    ) -> (Option<Move>, i64, i32, SearchStats) {
+        let limits = SearchLimits {
+            time: Some(time_budget),
+            ..SearchLimits::default()
+        };
+        let result = self.search(board, limits, 1);
+        (result.best_move(), result.score, result.depth, result.stats)
+    }
+
+    /// [`Searcher::find_best_moves_multipv`] under a wall-clock budget,
+    /// deepening the same way [`Searcher::find_best_move_within`] does.
+    /// Returns the lines from the last completed depth together with
+    /// that depth.
+    pub fn find_best_moves_multipv_within(
+        &mut self,
+        board: &mut Board,
+        time_budget: Duration,
+        num_pv: usize,
+    ) -> (Vec<(Move, i64, Vec<Move>)>, i32) {
+        let limits = SearchLimits {
+            time: Some(time_budget),
+            ..SearchLimits::default()
+        };
+        let result = self.search(board, limits, num_pv);
+        (result.lines, result.depth)
+    }
+
+    /// [`Searcher::find_best_moves_multipv_within`], also returning
+    /// the search's statistics.
+    pub fn find_best_moves_multipv_within_with_stats(
+        &mut self,
+        board: &mut Board,
+        time_budget: Duration,
+        num_pv: usize,
+    ) -> (Vec<PvLine>, i32, SearchStats) {
+        let limits = SearchLimits {
+            time: Some(time_budget),
+            ..SearchLimits::default()
+        };
+        let result = self.search(board, limits, num_pv);
+        (result.lines, result.depth, result.stats)
+    }
+
+    /// Iterative deepening under `limits`, reporting `num_pv` lines —
+    /// the entry point the others are built on, and the one to use
+    /// when the search needs steering from another thread through
+    /// [`Searcher::handle`].
+    ///
+    /// With no time limit the search runs until `limits.depth` (or
+    /// `MAX_ITERATIVE_DEPTH`) or until it's stopped, whichever comes
+    /// first; a time budget can still be imposed later with
+    /// [`SearchHandle::set_time_budget`]. If the search is stopped or
+    /// runs into the hard deadline mid-iteration, that iteration is
+    /// dropped and the previous one's result is returned.
+    pub fn search(
+        &mut self,
+        board: &mut Board,
+        limits: SearchLimits,
+        num_pv: usize,
+    ) -> SearchResult {
+        let num_pv = num_pv.max(1);
+        let (limits, search_pv) = match self.strength {
+            Some(strength) => (strength.limit(limits), num_pv.max(strength.candidates())),
+            None => (limits, num_pv),
+        };
+        let max_depth = limits
+            .depth
+            .unwrap_or(MAX_ITERATIVE_DEPTH)
+            .clamp(1, MAX_ITERATIVE_DEPTH);
+        if let Some(budget) = limits.time {
+            self.handle.set_time_budget(budget);
+        }
+        self.node_limit = limits.nodes;
+        self.iterating = true;
+        let (mut lines, depth) =
+            self.run_smp(board, max_depth, |s, b| s.iterate(b, max_depth, search_pv));
+        self.iterating = false;
+        self.node_limit = None;
+
+        // Strength limiting: play one of the candidates instead of the
+        // best line, and report it as line 1. A mated or stalemated
+        // root has nothing to pick from.
+        if let Some(strength) = self.strength.filter(|_| !lines.is_empty()) {
+            let scores: Vec<i64> = lines
+                .iter()
+                .map(|(_, score, _)| match board.side_to_move {
+                    Color::White => *score,
+                    Color::Black => -*score,
+                })
+                .collect();
+            let pick = if self.deterministic {
+                strength.choose(&scores, &mut StdRng::seed_from_u64(board.hash))
+            } else {
+                strength.choose(&scores, &mut rand::thread_rng())
+            };
+            let chosen = lines.remove(pick);
+            lines.insert(0, chosen);
+        }
+        lines.truncate(num_pv);
+
+        if let Some((_, _, pv)) = lines.first_mut() {
+            self.extend_pv_from_tt(board, pv);
+        }
+        let score = match lines.first() {
+            Some((_, score, _)) => *score,
+            None => no_moves_score(board),
+        };
+        SearchResult {
+            lines,
+            score,
+            depth,
+            stats: self.stats(),
+        }
+    }
+
+    /// A PV cut short by a table hit right below the root has no
+    /// second move, and the second move is what UCI ponders on. Fill it
+    /// in from the table entry for the position after the first move —
+    /// if that entry's move is legal there.
+    fn extend_pv_from_tt(&self, board: &mut Board, pv: &mut Vec<Move>) {
+        if pv.len() != 1 {
+            return;
+        }
+        board.apply_move(&pv[0]);
+        if let Some(reply) = self.tt.probe(board.hash).and_then(|e| e.best_move) {
+            let mut replies = Vec::new();
+            board.get_all_raw_moves_append(&mut replies);
+            if replies.contains(&reply) {
+                pv.push(reply);
+            }
+        }
+        board.undo_last_move();
+    }
+
+    /// Main-thread iterative deepening behind [`Searcher::search`].
+    fn iterate(
+        &mut self,
+        board: &mut Board,
+        max_depth: i32,
+        num_pv: usize,
+    ) -> (Vec<(Move, i64, Vec<Move>)>, i32) {
+        let mut lines = Vec::new();
         let mut completed_depth = 0;
 
-        // Cap iterative deepening at 20 to keep the killer/history
-        // arrays in their MAX_SEARCH_PLY=64 budget even after a few
-        // quiescence extensions. If we ever push past depth 20, raise
-        // MAX_SEARCH_PLY first.
-        for depth in 1..=20 {
+        for depth in 1..=max_depth {
             let iter_start = Instant::now();
-            let (mv, score) = self.find_best_move(board, depth);
-            best_move = mv;
-            best_score = score;
+            let nodes_before = self.nodes;
+            let iteration = self.multipv_lines(board, depth, num_pv);
+            if self.stopped() {
+                // A partial iteration is only better than nothing.
+                if completed_depth == 0 {
+                    lines = iteration;
+                }
+                break;
+            }
+            lines = iteration;
             completed_depth = depth;
+            let nodes = self.nodes - nodes_before;
+            self.stats.record(|s| {
+                let previous = s.depths.last().map_or(0, |d| d.nodes);
+                s.depths.push(DepthStats {
+                    depth,
+                    nodes,
+                    time: iter_start.elapsed(),
+                    branching_factor: if previous == 0 {
+                        0.0
+                    } else {
+                        nodes as f64 / previous as f64
+                    },
+                });
+            });
 
             // Heuristic: chess search nodes per iteration grow ~3-4×
             // per ply. If the *next* iteration would likely overshoot
             // the deadline by 4× the time we just spent, stop now and
             // return what we have rather than start a search we'll
-            // abort partway through (which would leave best_move
-            // pointing at something potentially worse than the
-            // depth-N result).
-            let elapsed = iter_start.elapsed();
-            if Instant::now() + elapsed * 4 >= deadline {
+            // abort partway through. The deadline is re-read every
+            // iteration because `ponderhit` may set one mid-search.
+            if let Some(deadline) = self.handle.soft_deadline() {
+                if Instant::now() + iter_start.elapsed() * 4 >= deadline {
+                    break;
+                }
+            }
+        }
+        (lines, completed_depth)
+    }
+
+    /// Score every legal root move, not just the best: iterative
+    /// deepening under `limits` like [`Searcher::search`], except that
+    /// each root move gets its own full-window search, so no move is
+    /// cut off by the ones before it and every score is exact. Costs
+    /// roughly as much as a MultiPV search over all the moves; meant
+    /// for analysis and tooling, not for play.
+    ///
+    /// Returns one entry per legal move, best first for the side to
+    /// move (ties keep generation order), or an empty list when there
+    /// are none. If the search is stopped mid-iteration, the moves it
+    /// got to carry the new depth and the rest keep the previous one,
+    /// so check [`RootMoveInfo::depth`] before comparing scores.
+    /// Strength limiting does not apply.
+    pub fn analyse_root(&mut self, board: &mut Board, limits: SearchLimits) -> Vec<RootMoveInfo> {
+        let max_depth = limits
+            .depth
+            .unwrap_or(MAX_ITERATIVE_DEPTH)
+            .clamp(1, MAX_ITERATIVE_DEPTH);
+        if let Some(budget) = limits.time {
+            self.handle.set_time_budget(budget);
+        }
+        self.node_limit = limits.nodes;
+        self.iterating = true;
+        let mut infos = self.run_smp(board, max_depth, |s, b| s.analyse_iterate(b, max_depth));
+        self.iterating = false;
+        self.node_limit = None;
+
+        let sign = match board.side_to_move {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+        infos.sort_by_key(|info| std::cmp::Reverse(sign * info.score));
+        infos
+    }
+
+    /// Main-thread iterative deepening behind
+    /// [`Searcher::analyse_root`].
+    fn analyse_iterate(&mut self, board: &mut Board, max_depth: i32) -> Vec<RootMoveInfo> {
+        let mut moves = Vec::new();
+        board.get_all_raw_moves_append(&mut moves);
+        let mut infos: Vec<RootMoveInfo> = moves
+            .into_iter()
+            .map(|mv| {
+                board.apply_move(&mv);
+                let score = board.evaluate();
+                board.undo_last_move();
+                RootMoveInfo {
+                    mv,
+                    score,
+                    depth: 0,
+                    pv: vec![mv],
+                    nodes: 0,
+                }
+            })
+            .collect();
+        let sign = match board.side_to_move {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+
+        for depth in 1..=max_depth {
+            let iter_start = Instant::now();
+            let nodes_before = self.nodes;
+            for info in infos.iter_mut() {
+                let move_nodes_before = self.nodes;
+                self.state.played[0] = moved_piece(board, &info.mv);
+                board.apply_move(&info.mv);
+                let score = -self.negamax_ab(board, depth - 1, 1, i64::MIN + 1, i64::MAX - 1);
+                board.undo_last_move();
+                if self.stopped() {
+                    break;
+                }
+                info.score = sign * score;
+                info.depth = depth;
+                info.pv = std::iter::once(info.mv)
+                    .chain(self.state.pv_line(1))
+                    .collect();
+                info.nodes = self.nodes - move_nodes_before;
+            }
+            if self.stopped() {
                 break;
             }
+
+            // A completed iteration is the position's exact value, so
+            // it's worth caching like a normal root search.
+            if let Some(best) = infos.iter().max_by_key(|info| sign * info.score) {
+                self.tt.store(
+                    board.hash,
+                    Some(best.mv),
+                    sign * best.score,
+                    depth,
+                    Bound::Exact,
+                );
+            }
+            let nodes = self.nodes - nodes_before;
+            self.stats.record(|s| {
+                let previous = s.depths.last().map_or(0, |d| d.nodes);
+                s.depths.push(DepthStats {
+                    depth,
+                    nodes,
+                    time: iter_start.elapsed(),
+                    branching_factor: if previous == 0 {
+                        0.0
+                    } else {
+                        nodes as f64 / previous as f64
+                    },
+                });
+            });
+            if let Some(deadline) = self.handle.soft_deadline() {
+                if Instant::now() + iter_start.elapsed() * 4 >= deadline {
+                    break;
+                }
+            }
         }
-        (best_move, best_score, completed_depth)
+        infos
     }
 
     // ------------------------------------------------------------
@@ -293,17 +1576,121 @@ impl Searcher {
     /// move ordering. `ply` is the distance from the root (0 at root);
     /// `depth` is remaining depth.
     ///
+    /// Searches principal-variation style: a node whose window is wider
+    /// than one centipawn is a PV node and gets searched in full, while
+    /// null-window nodes are fair game for the shallow-depth pruning
+    /// (reverse futility, razoring, futility, late-move pruning). At
+    /// depth, a hash move that's clearly better than every alternative
+    /// is extended by a ply (singular extensions); under iterative
+    /// deepening, a null-window node with no hash move at all is
+    /// reduced by one (internal iterative reduction).
+    ///
     /// Returns score in the **current side-to-move's POV** (negamax
     /// convention). The public `find_best_move` flips to White's POV
     /// at the root.
     fn negamax_ab(
         &mut self,
         board: &mut Board,
-        depth: i32,
+        mut depth: i32,
         ply: usize,
         mut alpha: i64,
-        beta: i64,
+        mut beta: i64,
     ) -> i64 {
+        // Another thread has the answer (or the search was cut short):
+        // unwind. The score is meaningless and is never stored.
+        if self.stopped() {
+            return 0;
+        }
+        self.count_node();
+        self.state.clear_pv(ply);
+
+        // Draws by rule. A position that repeats one already on the
+        // path from the game's start is scored as drawn on its first
+        // repeat: whatever improvement either side had available, it
+        // had the first time round. A mate delivered on the move that
+        // reaches the fifty-move limit still stands.
+        if board.is_repetition() || board.is_insufficient_material() {
+            return self.draw_score(board);
+        }
+        if board.is_fifty_move_draw() {
+            let mut moves = Vec::new();
+            board.get_all_raw_moves_append(&mut moves);
+            return if moves.is_empty() && side_in_check(board) {
+                -MATE_SCORE + ply as i64
+            } else {
+                self.draw_score(board)
+            };
+        }
+
+        // Mate distance pruning. The best this node can possibly score
+        // is mating on the very next ply (MATE_SCORE - ply - 1); the
+        // worst is being mated right here (-MATE_SCORE + ply). If a
+        // shorter mate is already known higher up, the window closes
+        // and nothing below can matter.
+        alpha = alpha.max(-MATE_SCORE + ply as i64);
+        beta = beta.min(MATE_SCORE - ply as i64 - 1);
+        if alpha >= beta {
+            return alpha;
+        }
+
+        // Transposition table. An entry searched at least as deep as we
+        // are about to answers the node outright when its bound is
+        // conclusive for this window; otherwise its move is still the
+        // best first guess for ordering. A singularity test shares the
+        // position's key but not its move list, so it neither takes
+        // table cutoffs nor stores results.
+        let alpha_orig = alpha;
+        let excluded = self.state.excluded.get(ply).copied().flatten();
+        let tt_entry = self.tt.probe(board.hash);
+        self.stats.record(|s| {
+            s.tt_probes += 1;
+            s.tt_hits += tt_entry.is_some() as u64;
+        });
+        let mut hash_move = None;
+        if let Some(entry) = tt_entry {
+            hash_move = entry.best_move;
+            if excluded.is_none() && entry.depth >= depth {
+                let score = score_from_tt(entry.score, ply);
+                let cutoff = match entry.bound {
+                    Bound::Exact => Some(score),
+                    Bound::Lower if score >= beta => Some(beta),
+                    Bound::Upper if score <= alpha => Some(alpha),
+                    _ => None,
+                };
+                if let Some(score) = cutoff {
+                    self.stats.record(|s| s.tt_cutoffs += 1);
+                    return score;
+                }
+            }
+        }
+
+        // Tablebases. A draw is exact and ends the node, and so does a
+        // Syzygy win or loss, scored `TB_WIN_SCORE - ply`: the table
+        // leaves only the distance to mate open, and the root's DTZ
+        // ranking makes sure the win is actually played out. A KPK win
+        // or loss isn't scored at all: once the pawn promotes the game
+        // leaves the table, so a table score beyond the evaluation
+        // would only teach the search to put off converting. The
+        // evaluation knows those endings itself, and the root never
+        // strays from the result (`search_root`). None of it is
+        // stored: a draw's score carries the contempt of whichever
+        // side is at the root, and the tables outlive the search.
+        if excluded.is_none()
+            && ply > 0
+            && (board.colors[0] | board.colors[1]).count_ones() <= tablebase::max_pieces()
+        {
+            let score = match tablebase::probe(board) {
+                Some((Wdl::Draw, _)) => Some(self.draw_score(board)),
+                Some((Wdl::Win, Source::Syzygy)) => Some(TB_WIN_SCORE - ply as i64),
+                Some((Wdl::Loss, Source::Syzygy)) => Some(-TB_WIN_SCORE + ply as i64),
+                _ => None,
+            };
+            if let Some(score) = score {
+                self.stats.record(|s| s.tb_hits += 1);
+                return score;
+            }
+        }
+
         // Generate moves up-front so we can detect mate/stalemate
         // before deciding whether to drop into quiescence. If we did
         // the depth==0 check first, mates discovered exactly at the
@@ -316,11 +1703,12 @@ impl Searcher {
         if moves.is_empty() {
             let in_check = side_in_check(board);
             // Ply-aware mate score: closer mates score larger in
-            // magnitude so the search prefers them. Stalemate scores 0.
+            // magnitude so the search prefers them. Stalemate is a
+            // draw like any other.
             return if in_check {
                 -MATE_SCORE + ply as i64
             } else {
-                0
+                self.draw_score(board)
             };
         }
 
@@ -328,22 +1716,196 @@ impl Searcher {
             return self.quiesce(board, alpha, beta);
         }
 
-        // Order moves: MVV-LVA captures > killers > history > rest.
-        moves.sort_unstable_by(|a, b| {
-            self.order_score(board, b, ply)
-                .cmp(&self.order_score(board, a, ply))
-        });
+        // Shallow-depth pruning, all of it keyed off the static eval.
+        // PV nodes (open window) are searched in full — their score is
+        // reported — and so are nodes in check, where the static eval
+        // says little and every evasion matters. Mate-range bounds are
+        // left alone so a pruned subtree can't fake or hide a mate.
+        let pv_node = beta - alpha > 1;
+        let in_check = side_in_check(board);
+        let mut futile = false;
+        let mut late_move_limit = usize::MAX;
+        let features = self.features;
+        if !pv_node && !in_check && excluded.is_none() && beta.abs() < MATE_THRESHOLD {
+            let eval = evaluate_pov(board);
+
+            // Reverse futility: even after giving back a margin that
+            // grows with depth we'd still be above beta.
+            if features.reverse_futility
+                && depth <= RFP_MAX_DEPTH
+                && eval - RFP_MARGIN * depth as i64 >= beta
+            {
+                self.stats.record(|s| s.rfp_cutoffs += 1);
+                return beta;
+            }
+
+            // Razoring: hopelessly below alpha with one or two plies
+            // left. Only captures can plausibly recover that much, so
+            // ask quiescence; if it agrees, fail low.
+            if let Some(&margin) = RAZOR_MARGINS.get(depth as usize) {
+                if features.razoring && eval + margin <= alpha {
+                    let score = self.quiesce(board, alpha, beta);
+                    if score <= alpha {
+                        self.stats.record(|s| s.razor_cutoffs += 1);
+                        return alpha;
+                    }
+                }
+            }
+
+            if let Some(&margin) = FUTILITY_MARGINS.get(depth as usize) {
+                futile = features.futility && eval + margin <= alpha;
+            }
+            if let Some(&count) = LMP_MOVE_COUNTS.get(depth as usize) {
+                if features.late_move_pruning {
+                    late_move_limit = count;
+                }
+            }
+        }
+
+        // Internal iterative reduction. With no hash move this node has
+        // never been searched (or the entry was overwritten), so its
+        // move ordering is guesswork and a full-depth search would be
+        // spent largely on the wrong moves. Search it a ply shallower:
+        // that leaves a best move in the table for the next iteration,
+        // which reaches the node a ply deeper again and searches it
+        // that deep, hash move first — internal iterative deepening
+        // spread across iterations instead of run in place. A
+        // fixed-depth search has no next iteration, and a PV node's
+        // score is the result itself, so neither is reduced.
+        let has_hash_move = hash_move.is_some_and(|m| moves.contains(&m));
+        if self.features.internal_iterative_reduction
+            && self.iterating
+            && !pv_node
+            && depth >= IIR_MIN_DEPTH
+            && !has_hash_move
+            && excluded.is_none()
+        {
+            depth -= 1;
+        }
+
+        // Singular extension. If the table says the hash move is good
+        // (a lower bound or exact score from a search nearly as deep),
+        // check whether it's the *only* good move: search everything
+        // else at reduced depth against a null window just below its
+        // score. If nothing gets there, the hash move is singular and
+        // earns an extra ply. If something does and that bar is
+        // already above beta, at least two moves beat beta — multi-cut
+        // treats the node as a cutoff without searching it in full.
+        let mut singular_move = None;
+        if let (Some(entry), Some(tt_move)) = (tt_entry, hash_move) {
+            let tt_score = score_from_tt(entry.score, ply);
+            if self.features.singular_extensions
+                && depth >= SINGULAR_MIN_DEPTH
+                && excluded.is_none()
+                && ply < MAX_SEARCH_PLY
+                && (ply as i32) + depth < 2 * MAX_ITERATIVE_DEPTH
+                && entry.bound != Bound::Upper
+                && entry.depth >= depth - SINGULAR_TT_DEPTH_SLACK
+                && tt_score.abs() < MATE_THRESHOLD
+                && moves.contains(&tt_move)
+            {
+                let singular_beta = tt_score - SINGULAR_MARGIN * depth as i64;
+                self.state.excluded[ply] = Some(tt_move);
+                let score = self.negamax_ab(
+                    board,
+                    (depth - 1) / 2,
+                    ply,
+                    singular_beta - 1,
+                    singular_beta,
+                );
+                self.state.excluded[ply] = None;
+                self.state.clear_pv(ply);
+                if self.stopped() {
+                    return 0;
+                }
+                if score < singular_beta {
+                    singular_move = Some(tt_move);
+                    self.stats.record(|s| s.singular_extensions += 1);
+                } else if singular_beta >= beta {
+                    self.stats.record(|s| s.multi_cuts += 1);
+                    return singular_beta;
+                }
+            }
+        }
+
+        // Order moves: hash move > good captures > killers > history >
+        // bad captures. Scores are computed once per move up front — SEE
+        // is too expensive to re-run inside the sort comparator. The
+        // hash move only counts if it's in the legal list, which guards
+        // against key collisions.
+        let mut scored: Vec<(i64, Move)> = moves
+            .into_iter()
+            .map(|mv| {
+                if Some(mv) == hash_move {
+                    (i64::MAX, mv)
+                } else {
+                    (self.order_score(board, &mv, ply), mv)
+                }
+            })
+            .collect();
+        scored.sort_unstable_by_key(|&(score, _)| std::cmp::Reverse(score));
 
         // best_score in side-to-move POV; initialise just above MIN
         // so a future negation doesn't overflow.
         let mut best_score = i64::MIN + 1;
+        let mut best_move = None;
 
-        for mv in moves {
+        // Quiet moves searched so far, for the history penalty if a
+        // later move cuts off.
+        let mut quiets_tried: Vec<(Move, Piece)> = Vec::new();
+        let mut moves_searched = 0;
+
+        for (_order, mv) in scored {
+            if Some(mv) == excluded {
+                continue;
+            }
             let is_cap = move_is_capture(board, &mv);
+            let played = moved_piece(board, &mv);
+            if ply < MAX_SEARCH_PLY {
+                self.state.played[ply] = played;
+            }
             board.apply_move(&mv);
-            let score = -self.negamax_ab(board, depth - 1, ply + 1, -beta, -alpha);
+
+            // Futility and late-move pruning skip quiet moves that
+            // don't give check, once one move has produced a real
+            // (non-mated) score to fall back on.
+            let quiet = !is_cap && mv.promotion.is_none();
+            if quiet
+                && best_score > -MATE_THRESHOLD
+                && (futile || moves_searched >= late_move_limit)
+                && !side_in_check(board)
+            {
+                board.undo_last_move();
+                self.stats.record(|s| s.pruned_moves += 1);
+                continue;
+            }
+
+            // Principal variation search: the first move gets the full
+            // window; the rest only have to prove they're no better,
+            // which a null window does cheaply. One that unexpectedly
+            // lands inside the window is searched again properly.
+            let new_depth = if Some(mv) == singular_move {
+                depth
+            } else {
+                depth - 1
+            };
+            let mut score = if moves_searched == 0 {
+                -self.negamax_ab(board, new_depth, ply + 1, -beta, -alpha)
+            } else {
+                self.stats.record(|s| s.null_window_searches += 1);
+                -self.negamax_ab(board, new_depth, ply + 1, -alpha - 1, -alpha)
+            };
+            if moves_searched > 0 && score > alpha && score < beta {
+                self.stats.record(|s| s.researches += 1);
+                score = -self.negamax_ab(board, new_depth, ply + 1, -beta, -alpha);
+            }
+            moves_searched += 1;
             board.undo_last_move();
 
+            if self.stopped() {
+                return 0;
+            }
+
             if score > best_score {
                 best_score = score;
             }
@@ -352,15 +1914,56 @@ impl Searcher {
                 // Beta cutoff. Record this move so the same position
                 // (or similar at the same ply) tries it first next
                 // time.
+                self.stats.record(|s| {
+                    s.beta_cutoffs += 1;
+                    s.first_move_cutoffs += (moves_searched == 1) as u64;
+                });
                 self.state.record_cutoff(ply, mv, depth, is_cap);
+                if !is_cap {
+                    for &(quiet, piece) in &quiets_tried {
+                        self.state.penalise_quiet(ply, quiet, piece, depth);
+                    }
+                }
+                if excluded.is_none() {
+                    self.tt.store(
+                        board.hash,
+                        Some(mv),
+                        score_to_tt(beta, ply),
+                        depth,
+                        Bound::Lower,
+                    );
+                }
                 return beta;
             }
 
             if score > alpha {
                 alpha = score;
+                best_move = Some(mv);
+                self.state.update_pv(ply, mv);
+            }
+            if !is_cap {
+                if let Some((piece, _)) = played {
+                    quiets_tried.push((mv, piece));
+                }
             }
         }
 
+        // Raised alpha: exact value. Otherwise every move failed low
+        // and best_score is only an upper bound.
+        let bound = if alpha > alpha_orig {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        if excluded.is_none() {
+            self.tt.store(
+                board.hash,
+                best_move,
+                score_to_tt(best_score, ply),
+                depth,
+                bound,
+            );
+        }
         best_score
     }
 
@@ -380,21 +1983,19 @@ impl Searcher {
     /// or second move much more often, and the recursion terminates
     /// fast even in tactical positions.
     ///
+    /// Captures that lose material by static exchange (SEE < 0, e.g.
+    /// QxP defended by a pawn) are pruned outright: stand-pat is
+    /// already at least as good as starting an exchange we lose.
+    ///
     /// Limitations of this first cut:
     ///  - Doesn't generate check evasions when in check (should search
     ///    all moves if in check, not just captures).
     ///  - Misses en-passant captures (the `is_capture` test only
     ///    checks whether the target square is occupied; en passant
     ///    moves to an empty square).
-    ///
-    // `&mut self` is currently used only for the recursive call —
-    // quiesce reads no Searcher state today. We keep it because the
-    // transposition-table-aware quiesce we're about to add WILL read
-    // and write `self.tt`, and changing the signature later costs
-    // every call site. The alternative (free function now, method
-    // later) would mean a churn-y rename.
-    #[allow(clippy::only_used_in_recursion)]
     fn quiesce(&mut self, board: &mut Board, mut alpha: i64, beta: i64) -> i64 {
+        self.count_node();
+        self.stats.record(|s| s.qnodes += 1);
         let stand_pat = evaluate_pov(board);
         if stand_pat >= beta {
             return beta;
@@ -406,20 +2007,23 @@ impl Searcher {
         let mut moves = Vec::new();
         board.get_all_raw_moves_append(&mut moves);
 
-        // Filter to captures and score by MVV-LVA. Higher score first.
-        // Score = victim_value * 10 - attacker_value, so PxQ (8990)
-        // ranks far above QxP (-700) and beats every quiet move
-        // (which is filtered out by the `victim?` ?-operator).
+        // Filter to non-losing captures and score by MVV-LVA. Higher
+        // score first. Score = victim_value * 10 - attacker_value, so
+        // PxQ (8990) ranks far above QxP (-700) and beats every quiet
+        // move (which is filtered out by the `victim?` ?-operator).
         let mut scored: Vec<(i64, Move)> = moves
             .into_iter()
             .filter_map(|mv| {
                 let victim = board.get_piece_at_square_fast(mv.target.to_bit_index())?;
                 let attacker = board.get_piece_at_square_fast(mv.src.to_bit_index())?;
+                if !board.see_ge(&mv, 0) {
+                    return None;
+                }
                 let score = victim.material_value() * 10 - attacker.material_value();
                 Some((score, mv))
             })
             .collect();
-        scored.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+        scored.sort_unstable_by_key(|&(score, _)| std::cmp::Reverse(score));
 
         for (_score, mv) in scored {
             board.apply_move(&mv);
@@ -444,18 +2048,28 @@ impl Searcher {
     /// Score a move for ordering inside the main search. Higher = try
     /// first.
     ///
-    /// Tier 1: captures, ordered MVV-LVA.
+    /// Tier 1: good captures (SEE ≥ 0), ordered MVV-LVA.
     /// Tier 2: killer-move slot 0 from this ply.
     /// Tier 3: killer-move slot 1 from this ply.
-    /// Tier 4: history-heuristic score (any quiet move).
+    /// Tier 4: the countermove to the opponent's last move.
+    /// Tier 5: history + continuation history (any quiet move; may be
+    ///         negative, but the sum stays within ±3 × `MAX_HISTORY`).
+    /// Tier 6: bad captures (SEE < 0), ordered MVV-LVA.
     fn order_score(&self, board: &Board, mv: &Move, ply: usize) -> i64 {
         if let (Some(victim), Some(attacker)) = (
             board.get_piece_at_square_fast(mv.target.to_bit_index()),
             board.get_piece_at_square_fast(mv.src.to_bit_index()),
         ) {
-            // Capture: MVV-LVA, big offset to put captures above any
-            // killer/history score.
-            return 1_000_000 + victim.material_value() * 10 - attacker.material_value();
+            // Capture: MVV-LVA within its tier. Good captures get a
+            // big offset to sit above any killer/history score; losing
+            // ones (QxP defended by a pawn) a big negative one so
+            // they're only tried once every quiet move has been.
+            let mvv_lva = victim.material_value() * 10 - attacker.material_value();
+            return if board.see_ge(mv, 0) {
+                1_000_000 + mvv_lva
+            } else {
+                -1_000_000 + mvv_lva
+            };
         }
         if ply < MAX_SEARCH_PLY {
             if self.state.killers[ply][0] == Some(*mv) {
@@ -464,10 +2078,21 @@ impl Searcher {
             if self.state.killers[ply][1] == Some(*mv) {
                 return 400_000;
             }
+            if let Some((piece, to)) = self.state.previous_move(ply, 1) {
+                if self.features.countermoves
+                    && self.state.countermoves[piece as usize][to as usize] == Some(*mv)
+                {
+                    return 300_000;
+                }
+            }
+        }
+        match board.get_piece_at_square_fast(mv.src.to_bit_index()) {
+            Some(piece) => {
+                self.state
+                    .quiet_score(ply, mv, piece, self.features.continuation_history)
+            }
+            None => 0,
         }
-        let from = mv.src.to_bit_index() as usize;
-        let to = mv.target.to_bit_index() as usize;
-        self.state.history[from][to]
     }
 }
 
@@ -478,6 +2103,26 @@ impl Searcher {
 // them free functions keeps the Searcher methods tidier.
 // ---------------------------------------------------------------------
 
+/// White-POV score of a position with no legal moves: mated at ply
+/// zero if in check, otherwise stalemate.
+fn no_moves_score(board: &Board) -> i64 {
+    let score_pov = if side_in_check(board) { -MATE_SCORE } else { 0 };
+    if board.side_to_move == Color::Black {
+        -score_pov
+    } else {
+        score_pov
+    }
+}
+
+/// `(piece, to)` of `mv` in `board`, before it's played — the key the
+/// countermove and continuation tables use.
+#[inline]
+fn moved_piece(board: &Board, mv: &Move) -> Option<(Piece, u8)> {
+    board
+        .get_piece_at_square_fast(mv.src.to_bit_index())
+        .map(|piece| (piece, mv.target.to_bit_index()))
+}
+
 /// Is the side that's currently to move in check?
 #[inline]
 fn side_in_check(board: &Board) -> bool {
@@ -510,7 +2155,9 @@ fn move_is_capture(board: &Board, mv: &Move) -> bool {
 }
 
 // ---------------------------------------------------------------------
-// Unit tests for `SearchState`'s killer/history bookkeeping.
+// Unit tests for `SearchState`'s killer/history bookkeeping, and for
+// search internals (like the singularity test's excluded move) that
+// only show up through private state.
 //
 // `SearchState` is private to the search module, so these tests live
 // here (rather than under `chesslib/tests/`) to access internal
@@ -618,4 +2265,149 @@ mod search_state_tests {
         let to = Square::E4 as usize;
         assert_eq!(ss.history[from][to], 25);
     }
+
+    #[test]
+    fn history_saturates_under_gravity() {
+        // However many deep cutoffs a move collects, its score stays
+        // within MAX_HISTORY.
+        let mut ss = SearchState::new();
+        let m = mv(Square::E2, Square::E4);
+        for _ in 0..1000 {
+            ss.record_cutoff(0, m, 40, false);
+        }
+        let score = ss.history[Square::E2 as usize][Square::E4 as usize];
+        assert!(score > MAX_HISTORY / 2 && score <= MAX_HISTORY);
+    }
+
+    #[test]
+    fn penalised_quiet_goes_negative() {
+        let mut ss = SearchState::new();
+        let m = mv(Square::B1, Square::C3);
+        ss.penalise_quiet(0, m, Piece::WhiteKnight, 4);
+        assert_eq!(ss.history[Square::B1 as usize][Square::C3 as usize], -16);
+    }
+
+    #[test]
+    fn cutoff_records_countermove_for_previous_move() {
+        let mut ss = SearchState::new();
+        // 1.e4 at ply 0; Nc6 refutes it at ply 1.
+        ss.played[0] = Some((Piece::WhitePawn, Square::E4 as u8));
+        let reply = mv(Square::B8, Square::C6);
+        ss.played[1] = Some((Piece::BlackKnight, Square::C6 as u8));
+        ss.record_cutoff(1, reply, 3, false);
+        assert_eq!(
+            ss.countermoves[Piece::WhitePawn as usize][Square::E4 as usize],
+            Some(reply)
+        );
+    }
+
+    #[test]
+    fn continuation_history_follows_the_previous_moves() {
+        let mut ss = SearchState::new();
+        ss.played[0] = Some((Piece::WhitePawn, Square::E4 as u8));
+        ss.played[1] = Some((Piece::BlackPawn, Square::E5 as u8));
+        let m = mv(Square::G1, Square::F3);
+        ss.played[2] = Some((Piece::WhiteKnight, Square::F3 as u8));
+        ss.record_cutoff(2, m, 4, false);
+
+        // Same move after the same two predecessors: history (16) plus
+        // one- and two-ply continuation (16 each).
+        assert_eq!(ss.quiet_score(2, &m, Piece::WhiteKnight, true), 48);
+        // Without continuation history, just the plain history.
+        assert_eq!(ss.quiet_score(2, &m, Piece::WhiteKnight, false), 16);
+
+        // After a different reply only the plain history and the
+        // two-ply entry (our own e4) still apply.
+        ss.played[1] = Some((Piece::BlackPawn, Square::C5 as u8));
+        assert_eq!(ss.quiet_score(2, &m, Piece::WhiteKnight, true), 32);
+    }
+
+    #[test]
+    fn ageing_halves_history() {
+        let mut ss = SearchState::new();
+        let m = mv(Square::E2, Square::E4);
+        ss.played[1] = Some((Piece::WhitePawn, Square::E4 as u8));
+        ss.played[0] = Some((Piece::BlackPawn, Square::E5 as u8));
+        ss.record_cutoff(1, m, 10, false);
+        ss.age();
+        assert_eq!(ss.history[Square::E2 as usize][Square::E4 as usize], 50);
+        assert_eq!(ss.quiet_score(1, &m, Piece::WhitePawn, true), 100);
+    }
+
+    #[test]
+    fn excluded_move_is_skipped_and_nothing_is_stored() {
+        // White's only legal move is Kxb2. With it excluded, the node
+        // has nothing to search and fails low; a singularity test must
+        // never leave its partial result in the table either.
+        let mut board = crate::fen::load_fen("k7/8/8/8/8/8/1q6/K7 w - - 0 1").unwrap();
+        let mut searcher = Searcher::new_deterministic();
+        searcher.state.excluded[1] = Some(mv(Square::A1, Square::B2));
+        let score = searcher.negamax_ab(&mut board, 2, 1, -100, -99);
+        assert!(score <= -100);
+        assert!(searcher.tt.probe(board.hash).is_none());
+
+        searcher.state.excluded[1] = None;
+        let score = searcher.negamax_ab(&mut board, 2, 1, -100, -99);
+        assert!(score > -100);
+        assert!(searcher.tt.probe(board.hash).is_some());
+    }
+
+    /// A fresh searcher's score for `fen` at `depth`, one ply below the
+    /// root, with the window `(alpha, beta)`.
+    fn node_score(fen: &str, depth: i32, alpha: i64, beta: i64) -> i64 {
+        let mut board = crate::fen::load_fen(fen).unwrap();
+        Searcher::new_deterministic().negamax_ab(&mut board, depth, 1, alpha, beta)
+    }
+
+    // Black is a queen and more up on the board, and every position
+    // below is lost: static-eval pruning at any of these nodes would
+    // return the wrong side of the window.
+
+    #[test]
+    fn shallow_pruning_skips_nodes_in_check() {
+        // Nf7+ forces Kg8, and Re8 mates. A null window far below the
+        // static eval is exactly where reverse futility would fail high.
+        let fen = "qqb4k/ppp2Npp/8/8/2B5/8/8/4R1K1 b - - 0 1";
+        assert_eq!(node_score(fen, 5, 0, 1), 0);
+    }
+
+    #[test]
+    fn shallow_pruning_skips_pv_nodes() {
+        // Not in check, but nothing stops Re8 mate: the queenside is
+        // locked, and the bishops cover the squares any pawn move
+        // gives the king. Searched with an open window the node must
+        // see it; a null window at the same beta is pruned.
+        let fen = "qqb4k/pppp2pp/8/8/8/3B4/1B1K4/4RR2 b - - 0 1";
+        assert_eq!(node_score(fen, 5, -100, 100), -100);
+        assert_eq!(node_score(fen, 5, 99, 100), 100);
+    }
+
+    #[test]
+    fn shallow_pruning_leaves_mate_bounds_alone() {
+        // Ra8 mates, but it's quiet and the static eval is a long way
+        // below a mate-score alpha: razoring would fail low.
+        let fen = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+        let beta = MATE_SCORE - 10;
+        assert!(node_score(fen, 2, beta - 1, beta) >= beta);
+
+        // Kb8 is forced and Re8 mates: with beta at a mated score,
+        // reverse futility would fail high.
+        let fen = "k7/8/1K6/8/8/8/8/4R3 b - - 0 1";
+        let beta = -MATE_SCORE + 10;
+        assert_eq!(node_score(fen, 3, beta - 1, beta), beta - 1);
+    }
+
+    #[test]
+    fn tablebase_draws_are_not_stored() {
+        // A draw scored with contempt would come back out of the table
+        // with that contempt in a later search that has none, or from
+        // the other side.
+        let mut board = crate::fen::load_fen("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1").unwrap();
+        let mut searcher = Searcher::new_deterministic();
+        searcher.root_side = Color::Black;
+        searcher.root_contempt = 50;
+        let score = searcher.negamax_ab(&mut board, 3, 1, -100_000, 100_000);
+        assert_eq!(score, 50);
+        assert!(searcher.tt.probe(board.hash).is_none());
+    }
 }
@@ -0,0 +1,210 @@
+//! Static exchange evaluation (SEE).
+//!
+//! SEE answers "if both sides keep recapturing on this square with
+//! their cheapest piece, who comes out ahead, and by how much?" without
+//! touching the board. It's the standard tool for telling a winning
+//! capture (PxN) from a losing one (QxP defended by a pawn) — MVV-LVA
+//! alone can't, because it never looks at the defenders.
+//!
+//! Consumers:
+//!  - move ordering in `search.rs`, which splits captures into "good"
+//!    (SEE ≥ 0, tried before killers) and "bad" (tried after quiets);
+//!  - quiescence, which skips captures that lose material outright;
+//!  - [`Board::hanging_pieces`], for evaluation terms that want to know
+//!    which pieces are en prise.
+//!
+//! The exchange is resolved with the classic swap-list algorithm. After
+//! every capture the attacker set is recomputed from the reduced
+//! occupancy, which is what brings x-ray attackers (a rook behind a
+//! rook, a queen behind a bishop) into play.
+
+use crate::board::Board;
+use crate::move_generation::{
+    b_pawn_attacks, bishop_moves, king_moves, knight_moves, rook_moves, w_pawn_attacks,
+};
+use crate::types::{Color, Move, Piece, PieceType, Square, PAWN_VALUE};
+
+/// Longest possible exchange on one square: every piece on the board
+/// captures once. 32 pieces is a hard upper bound.
+const MAX_SWAPS: usize = 32;
+
+/// Least-valuable-attacker order used when picking the next piece to
+/// recapture with.
+const LVA_ORDER: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King,
+];
+
+impl Board {
+    /// Every piece, of either colour, that attacks `square` when the
+    /// board's occupancy is `occupied`.
+    ///
+    /// Passing an occupancy other than the real one is the point: SEE
+    /// removes each capturer from `occupied` as it goes, so sliders
+    /// that were hidden behind it (x-rays) show up on the next call.
+    /// Pieces not in `occupied` are never reported as attackers.
+    pub fn attackers_to(&self, square: u8, occupied: u64) -> u64 {
+        let target = 1u64 << square;
+
+        // Pawn attacks are asymmetric: a white pawn attacks `square`
+        // iff a black pawn standing on `square` would attack the white
+        // pawn's square, and vice versa.
+        let white_pawns = b_pawn_attacks(target) & self.white_pawns();
+        let black_pawns = w_pawn_attacks(target) & self.black_pawns();
+
+        let knights = knight_moves(target) & self.pieces[PieceType::Knight.idx()];
+        let kings = king_moves(target) & self.pieces[PieceType::King.idx()];
+
+        // Slider rays from `square`, stopping at (and including) the
+        // first occupied square in each direction.
+        let queens = self.pieces[PieceType::Queen.idx()];
+        let diagonal =
+            bishop_moves(target, 0, occupied) & (self.pieces[PieceType::Bishop.idx()] | queens);
+        let orthogonal =
+            rook_moves(target, 0, occupied) & (self.pieces[PieceType::Rook.idx()] | queens);
+
+        (white_pawns | black_pawns | knights | kings | diagonal | orthogonal) & occupied
+    }
+
+    /// Static exchange evaluation of `mv`, in centipawns, from the
+    /// moving side's point of view.
+    ///
+    /// Positive means the exchange sequence started by `mv` wins
+    /// material, zero means it's even, negative means it loses
+    /// material. Quiet moves are handled too: a quiet move onto a
+    /// square the opponent can profitably capture on scores negative.
+    ///
+    /// Pins and checks are ignored (as in every SEE implementation),
+    /// except that a king never captures onto a square that is still
+    /// defended.
+    pub fn see(&self, mv: &Move) -> i64 {
+        let from = mv.src.to_bit_index();
+        let to = mv.target.to_bit_index();
+        let mover = match self.get_piece_at_square_fast(from) {
+            Some(piece) => piece,
+            None => return 0,
+        };
+
+        let mut occupied = self.colors[0] | self.colors[1];
+        let mut gain = [0i64; MAX_SWAPS];
+
+        // What the first capture wins. En passant is the one capture
+        // whose victim doesn't sit on the target square.
+        let is_en_passant = mover.piece_type() == PieceType::Pawn
+            && self.en_passant_target == Some(mv.target)
+            && self.get_piece_at_square_fast(to).is_none();
+        gain[0] = if is_en_passant {
+            let victim_sq = if mover.color() == Color::White {
+                to - 8
+            } else {
+                to + 8
+            };
+            occupied ^= 1u64 << victim_sq;
+            PAWN_VALUE
+        } else {
+            self.get_piece_at_square_fast(to)
+                .map_or(0, |victim| victim.material_value())
+        };
+
+        // The piece standing on the square after the first move — the
+        // next thing the opponent gets to capture.
+        let mut on_square = mover.material_value();
+        if let Some(promotion) = mv.promotion {
+            let promoted = Piece::from_type_and_color(promotion, mover.color());
+            gain[0] += promoted.material_value() - PAWN_VALUE;
+            on_square = promoted.material_value();
+        }
+
+        occupied ^= 1u64 << from;
+        let mut side = mover.color().opponent();
+        let mut depth = 0;
+
+        loop {
+            let attackers = self.attackers_to(to, occupied);
+            let ours = attackers & self.colors[side.idx()];
+            if ours == 0 || depth + 1 >= MAX_SWAPS {
+                break;
+            }
+
+            let (pt, attacker_bb) = LVA_ORDER
+                .iter()
+                .map(|&pt| (pt, ours & self.pieces[pt.idx()]))
+                .find(|&(_, bb)| bb != 0)
+                .expect("non-empty attacker set has a least valuable attacker");
+            let attacker_bit = attacker_bb & attacker_bb.wrapping_neg();
+
+            // The king may only recapture if the opponent has nothing
+            // left to hit back with; otherwise the capture is illegal.
+            if pt == PieceType::King {
+                let theirs = attackers & self.colors[side.opponent().idx()];
+                if theirs != 0 {
+                    break;
+                }
+            }
+
+            depth += 1;
+            gain[depth] = on_square - gain[depth - 1];
+            on_square = Piece::from_type_and_color(pt, side).material_value();
+            occupied ^= attacker_bit;
+            side = side.opponent();
+        }
+
+        // Negamax the swap list back to the root: at every step the
+        // side to move may also decline to recapture.
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+        gain[0]
+    }
+
+    /// Does `mv` win at least `threshold` centipawns by static exchange?
+    /// `see_ge(mv, 0)` is the usual "is this capture not losing" test.
+    #[inline]
+    pub fn see_ge(&self, mv: &Move, threshold: i64) -> bool {
+        self.see(mv) >= threshold
+    }
+
+    /// The pieces of `color` (king excluded) that the opponent can win
+    /// material against right now: attacked, and the opponent's best
+    /// capture on that square has a positive SEE.
+    ///
+    /// Side-to-move agnostic — answers "what would be lost if `color`'s
+    /// opponent were on move", which is the question an evaluation term
+    /// wants answered for both sides.
+    pub fn hanging_pieces(&self, color: Color) -> u64 {
+        let occupied = self.colors[0] | self.colors[1];
+        let opponent = color.opponent();
+        let mut candidates = self.colors[color.idx()] & !self.pieces[PieceType::King.idx()];
+        let mut hanging = 0u64;
+
+        while candidates != 0 {
+            let sq = candidates.trailing_zeros() as u8;
+            candidates &= candidates - 1;
+
+            let attackers = self.attackers_to(sq, occupied) & self.colors[opponent.idx()];
+            if attackers == 0 {
+                continue;
+            }
+            // The cheapest attacker starts the best exchange.
+            let lva = LVA_ORDER
+                .iter()
+                .map(|&pt| attackers & self.pieces[pt.idx()])
+                .find(|&bb| bb != 0)
+                .expect("non-empty attacker set has a least valuable attacker");
+            let capture = Move {
+                src: Square::from_bit_index(lva.trailing_zeros() as u8),
+                target: Square::from_bit_index(sq),
+                promotion: None,
+            };
+            if self.see(&capture) > 0 {
+                hanging |= 1u64 << sq;
+            }
+        }
+        hanging
+    }
+}
@@ -0,0 +1,181 @@
+//! Search statistics — counters that explain *why* a search took the
+//! nodes it did: how often the transposition table answered, how often
+//! the first move tried was already the cutoff (move-ordering
+//! quality), how often null-window probes had to be searched again,
+//! and how the tree grew from one iteration to the next.
+//!
+//! Collection is behind the `stats` cargo feature. Without it every
+//! [`SearchStats::record`] call compiles to nothing, so the counters
+//! cost nothing in a normal build and simply read zero;
+//! [`SearchStats::nodes`] is the one exception, as it mirrors the node
+//! count the search keeps anyway.
+//!
+//! ```text
+//! cargo run --release --features stats --example bench_search
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+/// One completed iteration of iterative deepening.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthStats {
+    pub depth: i32,
+    /// Nodes (main search and quiescence) this iteration visited.
+    pub nodes: u64,
+    pub time: Duration,
+    /// `nodes` divided by the previous iteration's nodes: the
+    /// effective branching factor. 0 for the first iteration.
+    pub branching_factor: f64,
+}
+
+/// Counters for one search. Returned by the `_with_stats` variants of
+/// `Searcher::find_best_move*`, and also obtained from
+/// [`crate::search::Searcher::stats`] or
+/// [`crate::search::SearchResult::stats`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchStats {
+    /// Every node, main search and quiescence.
+    pub nodes: u64,
+    /// Of which quiescence nodes.
+    pub qnodes: u64,
+    /// Transposition-table lookups in the main search, and how many
+    /// found an entry for the position.
+    pub tt_probes: u64,
+    pub tt_hits: u64,
+    /// Nodes answered outright by a table entry.
+    pub tt_cutoffs: u64,
+    /// Beta cutoffs in the main search, and how many came from the
+    /// first move searched. Their ratio is the usual measure of
+    /// move-ordering quality; above 90% is good.
+    pub beta_cutoffs: u64,
+    pub first_move_cutoffs: u64,
+    /// Null-window (PVS) searches of non-first moves, and how many
+    /// landed inside the window and had to be searched again. These
+    /// are the only re-searches there are: the search has no null-move
+    /// pruning and no late-move reductions, so there is no null-move
+    /// or LMR re-search rate to report.
+    pub null_window_searches: u64,
+    pub researches: u64,
+    /// Nodes cut by reverse futility pruning and razoring, and quiet
+    /// moves skipped by futility and late-move pruning.
+    pub rfp_cutoffs: u64,
+    pub razor_cutoffs: u64,
+    pub pruned_moves: u64,
+    /// Hash moves found singular and extended, and nodes cut by
+    /// multi-cut.
+    pub singular_extensions: u64,
+    pub multi_cuts: u64,
+    /// Nodes ended by a tablebase draw or a Syzygy win or loss, plus
+    /// root move lists the tables filtered.
+    pub tb_hits: u64,
+    /// One entry per completed iteration (main thread only).
+    pub depths: Vec<DepthStats>,
+}
+
+impl SearchStats {
+    /// Apply `update` if statistics are compiled in; otherwise do
+    /// nothing, at no cost — the closure is never called and inlines
+    /// away.
+    #[inline(always)]
+    pub(crate) fn record(&mut self, update: impl FnOnce(&mut Self)) {
+        #[cfg(feature = "stats")]
+        update(self);
+        #[cfg(not(feature = "stats"))]
+        let _ = update;
+    }
+
+    /// Whether this build collects statistics.
+    pub fn enabled() -> bool {
+        cfg!(feature = "stats")
+    }
+
+    /// Share of cutoffs produced by the first move searched, 0.0–1.0.
+    pub fn first_move_cutoff_rate(&self) -> f64 {
+        ratio(self.first_move_cutoffs, self.beta_cutoffs)
+    }
+
+    /// Share of null-window searches that had to be repeated.
+    pub fn research_rate(&self) -> f64 {
+        ratio(self.researches, self.null_window_searches)
+    }
+
+    /// Share of table probes that found the position.
+    pub fn tt_hit_rate(&self) -> f64 {
+        ratio(self.tt_hits, self.tt_probes)
+    }
+
+    /// Add a helper thread's counters to these. Per-depth entries stay
+    /// the main thread's.
+    pub(crate) fn merge(&mut self, other: &SearchStats) {
+        self.nodes += other.nodes;
+        self.qnodes += other.qnodes;
+        self.tt_probes += other.tt_probes;
+        self.tt_hits += other.tt_hits;
+        self.tt_cutoffs += other.tt_cutoffs;
+        self.beta_cutoffs += other.beta_cutoffs;
+        self.first_move_cutoffs += other.first_move_cutoffs;
+        self.null_window_searches += other.null_window_searches;
+        self.researches += other.researches;
+        self.rfp_cutoffs += other.rfp_cutoffs;
+        self.razor_cutoffs += other.razor_cutoffs;
+        self.pruned_moves += other.pruned_moves;
+        self.singular_extensions += other.singular_extensions;
+        self.multi_cuts += other.multi_cuts;
+        self.tb_hits += other.tb_hits;
+    }
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "nodes {} (qnodes {})", self.nodes, self.qnodes)?;
+        writeln!(
+            f,
+            "tt: {} probes, {:.1}% hits, {} cutoffs",
+            self.tt_probes,
+            100.0 * self.tt_hit_rate(),
+            self.tt_cutoffs
+        )?;
+        writeln!(
+            f,
+            "beta cutoffs: {}, {:.1}% on the first move",
+            self.beta_cutoffs,
+            100.0 * self.first_move_cutoff_rate()
+        )?;
+        writeln!(
+            f,
+            "pvs: {} null-window searches, {:.1}% re-searched",
+            self.null_window_searches,
+            100.0 * self.research_rate()
+        )?;
+        writeln!(
+            f,
+            "pruning: {} rfp, {} razor, {} moves skipped; {} singular extensions, {} multi-cuts",
+            self.rfp_cutoffs,
+            self.razor_cutoffs,
+            self.pruned_moves,
+            self.singular_extensions,
+            self.multi_cuts
+        )?;
+        writeln!(f, "tablebase hits: {}", self.tb_hits)?;
+        for d in &self.depths {
+            writeln!(
+                f,
+                "  depth {:>2}: {:>10} nodes {:>9.3}s  ebf {:.2}",
+                d.depth,
+                d.nodes,
+                d.time.as_secs_f64(),
+                d.branching_factor
+            )?;
+        }
+        Ok(())
+    }
+}
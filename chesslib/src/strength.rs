@@ -0,0 +1,147 @@
+//! Strength limiting — deliberately weaker play, for opponents that a
+//! beginner can actually beat.
+//!
+//! A [`Strength`] is a skill level from 0 (weakest) to
+//! [`MAX_SKILL_LEVEL`]. It weakens the engine in two ways that both
+//! resemble how people lose games, rather than by playing random
+//! moves:
+//!
+//!  - **A shorter horizon.** The search is capped in depth and nodes,
+//!    so low levels simply don't see a threat three moves out — the
+//!    classic human oversight.
+//!  - **Picking a plausible second-best move.** The search reports its
+//!    top few candidates (MultiPV), and the move played is drawn from
+//!    them with probability `exp(-loss / temperature)`, where `loss`
+//!    is how many centipawns a candidate gives up against the best.
+//!    Only moves the engine itself rated near the top are ever in the
+//!    draw, so a mistake is a reasonable-looking move that's a bit
+//!    worse, not a rook left hanging for no reason. Higher levels have
+//!    a lower temperature and a tighter cap on the loss, so their
+//!    mistakes are both rarer and smaller.
+//!
+//! [`Strength::from_elo`] maps a UCI `UCI_Elo` rating onto a level. The
+//! mapping is linear and only roughly calibrated: level 0 is meant to
+//! play around [`MIN_ELO`], level 20 around [`MAX_ELO`].
+//!
+//! The random draw goes through whatever `Rng` the caller passes in;
+//! the search seeds it from the position's hash in deterministic mode,
+//! so the same position always gets the same "mistake".
+
+use crate::search::SearchLimits;
+use rand::Rng;
+
+/// Highest skill level (the UCI `Skill Level` maximum). A `Strength`
+/// at this level still caps depth and nodes but always plays the best
+/// move it finds; the UCI layer treats it as no limit at all.
+pub const MAX_SKILL_LEVEL: u8 = 20;
+
+/// `UCI_Elo` range the engine advertises.
+pub const MIN_ELO: u32 = 600;
+pub const MAX_ELO: u32 = 2400;
+
+/// Candidate moves (MultiPV lines) a limited search considers.
+const CANDIDATES: usize = 4;
+
+/// A skill level and the search limits and move-choice parameters it
+/// implies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Strength {
+    level: u8,
+}
+
+impl Strength {
+    /// Skill level `level`, clamped to `0..=MAX_SKILL_LEVEL`.
+    pub fn from_level(level: u8) -> Self {
+        Self {
+            level: level.min(MAX_SKILL_LEVEL),
+        }
+    }
+
+    /// The skill level that best matches a rating of `elo`, clamped to
+    /// `MIN_ELO..=MAX_ELO`.
+    pub fn from_elo(elo: u32) -> Self {
+        let elo = elo.clamp(MIN_ELO, MAX_ELO);
+        let level = (elo - MIN_ELO) * MAX_SKILL_LEVEL as u32 / (MAX_ELO - MIN_ELO);
+        Self::from_level(level as u8)
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    /// Deepest iteration this level searches: 1 ply at level 0, 11 at
+    /// the top.
+    pub fn max_depth(&self) -> i32 {
+        1 + self.level as i32 / 2
+    }
+
+    /// Node budget per move: 500 at level 0, doubling every two levels.
+    pub fn max_nodes(&self) -> u64 {
+        500 << (self.level / 2)
+    }
+
+    /// How many MultiPV candidates the move is chosen from.
+    pub fn candidates(&self) -> usize {
+        CANDIDATES
+    }
+
+    /// Softmax temperature in centipawns: 200 at level 0, 0 (always
+    /// the best move) at `MAX_SKILL_LEVEL`.
+    pub fn temperature(&self) -> i64 {
+        (MAX_SKILL_LEVEL - self.level) as i64 * 10
+    }
+
+    /// Most centipawns a chosen move may give up against the best one:
+    /// 600 (a piece and more) at level 0, nothing at the top.
+    pub fn max_loss(&self) -> i64 {
+        (MAX_SKILL_LEVEL - self.level) as i64 * 30
+    }
+
+    /// `limits` tightened to this level's depth and node caps.
+    pub fn limit(&self, limits: SearchLimits) -> SearchLimits {
+        SearchLimits {
+            depth: Some(
+                limits
+                    .depth
+                    .map_or(self.max_depth(), |d| d.min(self.max_depth())),
+            ),
+            nodes: Some(
+                limits
+                    .nodes
+                    .map_or(self.max_nodes(), |n| n.min(self.max_nodes())),
+            ),
+            ..limits
+        }
+    }
+
+    /// Pick one of `scores` (side-to-move POV, best first) to play.
+    /// Returns its index: 0 is the best move.
+    pub fn choose(&self, scores: &[i64], rng: &mut impl Rng) -> usize {
+        let Some(&best) = scores.first() else {
+            return 0;
+        };
+        let temperature = self.temperature();
+        if temperature == 0 {
+            return 0;
+        }
+        let weights: Vec<f64> = scores
+            .iter()
+            .map(|&score| {
+                let loss = best.saturating_sub(score).max(0);
+                if loss > self.max_loss() {
+                    0.0
+                } else {
+                    (-(loss as f64) / temperature as f64).exp()
+                }
+            })
+            .collect();
+        let mut pick = rng.gen::<f64>() * weights.iter().sum::<f64>();
+        for (i, weight) in weights.iter().enumerate() {
+            if pick < *weight {
+                return i;
+            }
+            pick -= weight;
+        }
+        0
+    }
+}
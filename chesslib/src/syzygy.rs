@@ -0,0 +1,1501 @@
+//! Syzygy endgame tablebases: win/draw/loss (`.rtbw`) and distance to
+//! zeroing (`.rtbz`) probes for positions with few pieces.
+//!
+//! The `SyzygyPath` option names one or more directories, separated
+//! by `:` (`;` on Windows), holding table files named by their
+//! material: `KRvK.rtbw`, `KBNvK.rtbz`. [`Tablebases::load`] finds
+//! them and checks their magic numbers; a file is read into memory and
+//! its header parsed the first time a probe needs it.
+//!
+//! # Results
+//!
+//! A WDL probe gives the result with best play for the side to move,
+//! on the scale the files use: [`LOSS`], [`BLESSED_LOSS`] (a loss the
+//! fifty-move rule turns into a draw), [`DRAW`], [`CURSED_WIN`] (a win
+//! it spoils) and [`WIN`]. A DTZ probe gives the plies to the next
+//! capture or pawn move with best play, positive when winning and
+//! negative when losing. A mate counts as a zeroing move here, and the
+//! fifty-move count starts afresh after one, so always playing a move
+//! that lowers DTZ wins without ever running into that rule. Neither
+//! probe looks at the position's fifty-move counter or its history,
+//! and positions with castling rights aren't in any table.
+//!
+//! # Format
+//!
+//! The layout is the Syzygy generator's, read the way Stockfish reads
+//! it. A position maps to an index: the board is mirrored so the
+//! leading piece (the leading pawn, or the first king) lies in a fixed
+//! part of it, and each group of like pieces is encoded as a
+//! combination of the squares the earlier groups left free. Tables
+//! with pawns are split in four by the leading pawn's file. The values
+//! are Huffman-coded in fixed-size blocks, with a sparse index into
+//! the blocks every `span` positions, and a symbol may stand for a
+//! pair of symbols, recursively. A WDL table stores both sides to
+//! move, a DTZ table only one: the other is a one-ply search away.
+//!
+//! Both probes search the captures (and, for DTZ, the pawn moves)
+//! themselves before looking at a table: the tables don't know about
+//! en passant, and where a capture wins the stored value is allowed to
+//! be anything.
+//!
+//! [`crate::tbgen`] writes tables in this format.
+
+use crate::board::Board;
+use crate::endgame::MaterialKey;
+use crate::logger::log_to_file;
+use crate::types::{Color, Move, Piece, PieceType};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// First four bytes of every `.rtbw` file.
+pub const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+/// First four bytes of every `.rtbz` file.
+pub const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+/// WDL results, for the side to move.
+pub const LOSS: i32 = -2;
+pub const BLESSED_LOSS: i32 = -1;
+pub const DRAW: i32 = 0;
+pub const CURSED_WIN: i32 = 1;
+pub const WIN: i32 = 2;
+
+/// Most pieces, kings included, any table has.
+pub const MAX_TB_PIECES: usize = 7;
+
+#[cfg(windows)]
+const PATH_SEPARATOR: char = ';';
+#[cfg(not(windows))]
+const PATH_SEPARATOR: char = ':';
+
+// Header flags: the first byte of a file.
+pub(crate) const SPLIT: u8 = 1;
+pub(crate) const HAS_PAWNS: u8 = 2;
+
+// Per-table flags: the first byte of its sizes.
+pub(crate) const STM: u8 = 1;
+const MAPPED: u8 = 2;
+pub(crate) const WIN_PLIES: u8 = 4;
+pub(crate) const LOSS_PLIES: u8 = 8;
+const WIDE: u8 = 16;
+pub(crate) const SINGLE_VALUE: u8 = 128;
+
+/// Values of the leading group of three unique pieces.
+const UNIQUE_PIECES_SIZE: u64 = 31332;
+/// Values of the two kings when they lead.
+const KINGS_SIZE: u64 = 462;
+
+lazy_static::lazy_static! {
+    static ref MAPS: Maps = Maps::new();
+    static ref ACTIVE: RwLock<Arc<Tablebases>> = RwLock::new(Arc::new(Tablebases::default()));
+}
+
+/// [`Tablebases::max_pieces`] of the installed tables, readable without
+/// taking the lock.
+static ACTIVE_MAX_PIECES: AtomicU32 = AtomicU32::new(0);
+
+// ---------------------------------------------------------------------
+// Finding the files
+// ---------------------------------------------------------------------
+
+/// The tables found under a `SyzygyPath`.
+#[derive(Default)]
+pub struct Tablebases {
+    /// By signature, White's side first as in the file names.
+    tables: BTreeMap<String, Arc<Entry>>,
+    /// Every table under both of its material keys.
+    by_key: HashMap<MaterialKey, Arc<Entry>>,
+    /// Files that look like tables but aren't: `path: reason`.
+    pub rejected: Vec<String>,
+}
+
+/// One material signature: its files, and their contents once read.
+struct Entry {
+    material: Material,
+    wdl_path: Option<PathBuf>,
+    dtz_path: Option<PathBuf>,
+    wdl: OnceLock<Option<Table>>,
+    dtz: OnceLock<Option<Table>>,
+}
+
+impl Entry {
+    fn table(&self, dtz: bool) -> Option<&Table> {
+        let (cell, path) = if dtz {
+            (&self.dtz, &self.dtz_path)
+        } else {
+            (&self.wdl, &self.wdl_path)
+        };
+        cell.get_or_init(|| {
+            let path = path.as_ref()?;
+            let table = std::fs::read(path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| Table::parse(bytes, &self.material, dtz));
+            match table {
+                Ok(table) => Some(table),
+                Err(e) => {
+                    log_to_file(
+                        &format!("Skipped Syzygy file {}: {e}", path.display()),
+                        true,
+                    );
+                    None
+                }
+            }
+        })
+        .as_ref()
+    }
+}
+
+impl Tablebases {
+    /// Scan every directory in `path` (separated as for `SyzygyPath`).
+    /// An empty path or `<empty>` finds nothing; a directory that
+    /// can't be read is an error.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let mut tablebases = Self::default();
+        let path = path.trim();
+        if path.is_empty() || path == "<empty>" {
+            return Ok(tablebases);
+        }
+        for dir in path.split(PATH_SEPARATOR).filter(|d| !d.is_empty()) {
+            let entries = std::fs::read_dir(dir).map_err(|e| format!("{dir}: {e}"))?;
+            let mut files: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+            files.sort();
+            for file in files {
+                tablebases.add(&file);
+            }
+        }
+        for entry in tablebases.tables.values() {
+            let material = entry.material;
+            tablebases.by_key.insert(material.key, entry.clone());
+            tablebases.by_key.insert(material.key2, entry.clone());
+        }
+        Ok(tablebases)
+    }
+
+    fn add(&mut self, file: &Path) {
+        let (Some(stem), Some(extension)) = (
+            file.file_stem().and_then(|s| s.to_str()),
+            file.extension().and_then(|e| e.to_str()),
+        ) else {
+            return;
+        };
+        let magic = match extension {
+            "rtbw" => WDL_MAGIC,
+            "rtbz" => DTZ_MAGIC,
+            _ => return,
+        };
+        let key = match MaterialKey::parse(stem) {
+            Ok(key) => key,
+            Err(e) => {
+                self.rejected.push(format!("{}: {e}", file.display()));
+                return;
+            }
+        };
+        let material = Material::new(key);
+        if material.pieces > MAX_TB_PIECES {
+            self.rejected.push(format!(
+                "{}: more than {MAX_TB_PIECES} pieces",
+                file.display()
+            ));
+            return;
+        }
+        if let Err(e) = check_magic(file, magic) {
+            self.rejected.push(format!("{}: {e}", file.display()));
+            return;
+        }
+        let entry = self.tables.entry(stem.to_string()).or_insert_with(|| {
+            Arc::new(Entry {
+                material,
+                wdl_path: None,
+                dtz_path: None,
+                wdl: OnceLock::new(),
+                dtz: OnceLock::new(),
+            })
+        });
+        // Nothing else holds the entry until `load` indexes it by key.
+        let entry = Arc::get_mut(entry).expect("entry is not shared yet");
+        match extension {
+            "rtbw" => entry.wdl_path = Some(file.to_path_buf()),
+            _ => entry.dtz_path = Some(file.to_path_buf()),
+        }
+    }
+
+    /// How many material signatures have at least one file.
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// The most pieces in any table found, kings included; 0 if none.
+    pub fn max_pieces(&self) -> u32 {
+        self.tables
+            .values()
+            .map(|entry| entry.material.pieces as u32)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The signatures found, White's side first.
+    pub fn signatures(&self) -> impl Iterator<Item = &str> {
+        self.tables.keys().map(String::as_str)
+    }
+
+    /// Whether the tables can answer for `board` at all.
+    fn covers(&self, board: &Board) -> bool {
+        let pieces = (board.colors[0] | board.colors[1]).count_ones();
+        pieces <= self.max_pieces() && !has_castling_rights(board)
+    }
+
+    /// The result with best play for the side to move, from [`LOSS`]
+    /// to [`WIN`]. `None` if a table it needs is missing.
+    pub fn probe_wdl(&self, board: &mut Board) -> Option<i32> {
+        if !self.covers(board) {
+            return None;
+        }
+        self.search(board, false).map(|(wdl, _)| wdl)
+    }
+
+    /// Plies to the next zeroing move with best play: positive for the
+    /// side to move winning, negative for it losing, 0 for a draw. A
+    /// mate is one ply away for the side giving it, and -1 for the side
+    /// mated. Cursed wins and blessed losses are 100 plies further off
+    /// than they are, so they count as beyond the fifty-move rule
+    /// whatever the counter. `None` if a table it needs is missing.
+    pub fn probe_dtz(&self, board: &mut Board) -> Option<i32> {
+        if !self.covers(board) {
+            return None;
+        }
+        self.dtz(board)
+    }
+
+    /// The moves among `moves` (legal moves in `board`) that keep the
+    /// best result the tables know of, and among those the quickest to
+    /// the next zeroing move when winning and the slowest when losing,
+    /// in their original order. Wins the fifty-move counter would
+    /// spoil rank below the others, though still above a draw. `None`
+    /// unless the tables cover `board` and every move from it.
+    pub fn root_moves(&self, board: &mut Board, moves: &[Move]) -> Option<Vec<Move>> {
+        if !self.covers(board) {
+            return None;
+        }
+        let halfmove_clock = board.halfmove_clock as i32;
+        let mut ranked = Vec::with_capacity(moves.len());
+        for mv in moves {
+            board.apply_move(mv);
+            let dtz = if board.halfmove_clock == 0 {
+                self.search(board, false)
+                    .map(|(wdl, _)| dtz_before_zeroing(-wdl))
+            } else if board.is_repetition() || board.is_fifty_move_draw() {
+                Some(0)
+            } else {
+                // One ply further from the root than from the child.
+                self.dtz(board).map(|dtz| -dtz - dtz.signum())
+            };
+            // The one move that zeroes nothing and still ends the count.
+            let dtz = dtz.map(|dtz| if dtz == 2 && is_mate(board) { 1 } else { dtz });
+            board.undo_last_move();
+            ranked.push((*mv, root_rank(dtz?, halfmove_clock)));
+        }
+        let best = ranked.iter().map(|&(_, rank)| rank).max()?;
+        Some(
+            ranked
+                .into_iter()
+                .filter(|&(_, rank)| rank == best)
+                .map(|(mv, _)| mv)
+                .collect(),
+        )
+    }
+
+    /// Stockfish's `search`: the best result among the captures (and
+    /// the pawn moves, with `zeroing_moves`), or the table's if that's
+    /// better. The flag says the best move is a capture or pawn move,
+    /// so a DTZ table's value for the position can't be trusted.
+    fn search(&self, board: &mut Board, zeroing_moves: bool) -> Option<(i32, bool)> {
+        let mut moves = Vec::new();
+        board.get_all_raw_moves_append(&mut moves);
+        let total = moves.len();
+        let mut searched = 0;
+        let mut best = LOSS;
+        for mv in moves {
+            if !(is_capture(board, &mv) || zeroing_moves && is_pawn_move(board, &mv)) {
+                continue;
+            }
+            searched += 1;
+            board.apply_move(&mv);
+            let value = self.search(board, false).map(|(wdl, _)| -wdl);
+            board.undo_last_move();
+            let value = value?;
+            if value > best {
+                best = value;
+                if value >= WIN {
+                    return Some((value, true));
+                }
+            }
+        }
+        // With every move searched the table isn't needed, and may be
+        // wrong: it knows nothing of en passant.
+        let all_searched = searched > 0 && searched == total;
+        let value = if all_searched {
+            best
+        } else {
+            match self.probe_table(board, false, DRAW) {
+                TableProbe::Found(value) => value,
+                _ => return None,
+            }
+        };
+        if best >= value {
+            return Some((best, best > DRAW || all_searched));
+        }
+        Some((value, false))
+    }
+
+    fn dtz(&self, board: &mut Board) -> Option<i32> {
+        let (wdl, zeroing_best) = self.search(board, true)?;
+        if wdl == DRAW {
+            return Some(0);
+        }
+        if zeroing_best {
+            return Some(dtz_before_zeroing(wdl));
+        }
+        match self.probe_table(board, true, wdl) {
+            TableProbe::Found(dtz) => {
+                let cursed = if wdl == CURSED_WIN || wdl == BLESSED_LOSS {
+                    100
+                } else {
+                    0
+                };
+                Some((dtz + cursed) * wdl.signum())
+            }
+            TableProbe::Missing => None,
+            TableProbe::OtherSide => {
+                // The table stores the other side to move: look one
+                // ply ahead for the best result soonest (winning) or
+                // latest (losing).
+                let mut moves = Vec::new();
+                board.get_all_raw_moves_append(&mut moves);
+                let mut best: Option<i32> = None;
+                for mv in moves {
+                    let zeroing = is_capture(board, &mv) || is_pawn_move(board, &mv);
+                    board.apply_move(&mv);
+                    let dtz = if zeroing {
+                        self.search(board, false)
+                            .map(|(wdl, _)| -dtz_before_zeroing(wdl))
+                    } else {
+                        self.dtz(board).map(|dtz| -dtz)
+                    };
+                    let mates = dtz == Some(1) && is_mate(board);
+                    board.undo_last_move();
+                    let mut dtz = dtz?;
+                    if !zeroing {
+                        dtz += dtz.signum();
+                    }
+                    if mates {
+                        dtz = 1;
+                    }
+                    if dtz.signum() == wdl.signum() && best.is_none_or(|best| dtz < best) {
+                        best = Some(dtz);
+                    }
+                }
+                // No moves at all: mated.
+                Some(best.unwrap_or(-1))
+            }
+        }
+    }
+
+    /// Look `board` up in its table: a WDL result, or a DTZ value in
+    /// plies (unsigned) for a position known to have result `wdl`.
+    fn probe_table(&self, board: &Board, dtz: bool, wdl: i32) -> TableProbe {
+        if (board.colors[0] | board.colors[1]).count_ones() == 2 {
+            return TableProbe::Found(DRAW);
+        }
+        let key = MaterialKey::of(board);
+        let Some(entry) = self.by_key.get(&key) else {
+            return TableProbe::Missing;
+        };
+        let Some(table) = entry.table(dtz) else {
+            return TableProbe::Missing;
+        };
+        let material = &entry.material;
+        let symmetric_black_to_move =
+            material.key == material.key2 && board.side_to_move == Color::Black;
+        let flip = symmetric_black_to_move || key != material.key;
+        let lead_pawn = table.pairs[0][0].layout.pieces[0];
+        let oriented = Oriented::new(material, lead_pawn, flip, &Placement::of(board));
+        let file = oriented.file;
+        // Symmetric pawnless tables store only one side: the other is
+        // the same position with the colours swapped.
+        let one_side = material.key == material.key2 && !material.has_pawns;
+        if dtz && !one_side && (table.pairs[file][0].flags & STM) as usize != oriented.stm {
+            return TableProbe::OtherSide;
+        }
+        let pairs = &table.pairs[file][oriented.stm % table.pairs[file].len()];
+        let idx = oriented.index(material, &pairs.layout);
+        let Some(value) = table.decompress(pairs, idx) else {
+            return TableProbe::Missing;
+        };
+        if dtz {
+            TableProbe::Found(table.map_score(file, value, wdl))
+        } else {
+            TableProbe::Found(value as i32 - 2)
+        }
+    }
+}
+
+enum TableProbe {
+    Found(i32),
+    /// A DTZ table that stores the other side to move.
+    OtherSide,
+    /// No table, or a broken one.
+    Missing,
+}
+
+fn check_magic(file: &Path, magic: [u8; 4]) -> Result<(), String> {
+    let mut header = [0u8; 4];
+    File::open(file)
+        .and_then(|mut f| f.read_exact(&mut header))
+        .map_err(|e| e.to_string())?;
+    if header != magic {
+        return Err(format!("not a Syzygy table (magic {header:02x?})"));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------
+// The installed tables
+// ---------------------------------------------------------------------
+
+/// Load the tables under `path` for [`probe_wdl`], [`probe_dtz`] and
+/// [`root_moves`], replacing any loaded before. An empty path unloads
+/// them.
+pub fn init(path: &str) -> Result<Arc<Tablebases>, String> {
+    let tablebases = Arc::new(Tablebases::load(path)?);
+    *ACTIVE.write().unwrap() = tablebases.clone();
+    ACTIVE_MAX_PIECES.store(tablebases.max_pieces(), Ordering::Relaxed);
+    Ok(tablebases)
+}
+
+/// The most pieces in any loaded table; 0 with none loaded.
+pub fn max_pieces() -> u32 {
+    ACTIVE_MAX_PIECES.load(Ordering::Relaxed)
+}
+
+fn active() -> Option<Arc<Tablebases>> {
+    if max_pieces() == 0 {
+        return None;
+    }
+    Some(ACTIVE.read().unwrap().clone())
+}
+
+/// [`Tablebases::probe_wdl`] with the loaded tables.
+pub fn probe_wdl(board: &mut Board) -> Option<i32> {
+    active()?.probe_wdl(board)
+}
+
+/// [`Tablebases::probe_dtz`] with the loaded tables.
+pub fn probe_dtz(board: &mut Board) -> Option<i32> {
+    active()?.probe_dtz(board)
+}
+
+/// [`Tablebases::root_moves`] with the loaded tables.
+pub fn root_moves(board: &mut Board, moves: &[Move]) -> Option<Vec<Move>> {
+    active()?.root_moves(board, moves)
+}
+
+// ---------------------------------------------------------------------
+// Move helpers
+// ---------------------------------------------------------------------
+
+fn has_castling_rights(board: &Board) -> bool {
+    board.white_kingside_castle_rights
+        || board.white_queenside_castle_rights
+        || board.black_kingside_castle_rights
+        || board.black_queenside_castle_rights
+}
+
+fn is_capture(board: &Board, mv: &Move) -> bool {
+    let target = mv.target.to_bit_index();
+    board.get_piece_at_square_fast(target).is_some()
+        || (board.en_passant_target == Some(mv.target) && is_pawn_move(board, mv))
+}
+
+fn is_pawn_move(board: &Board, mv: &Move) -> bool {
+    board
+        .get_piece_at_square_fast(mv.src.to_bit_index())
+        .is_some_and(|piece| piece.piece_type() == PieceType::Pawn)
+}
+
+fn is_mate(board: &mut Board) -> bool {
+    let in_check = match board.side_to_move {
+        Color::White => board.white_king_in_check,
+        Color::Black => board.black_king_in_check,
+    };
+    if !in_check {
+        return false;
+    }
+    let mut moves = Vec::new();
+    board.get_all_raw_moves_append(&mut moves);
+    moves.is_empty()
+}
+
+/// DTZ of a position whose best move zeroes into result `wdl` for the
+/// side making it.
+fn dtz_before_zeroing(wdl: i32) -> i32 {
+    match wdl {
+        WIN => 1,
+        CURSED_WIN => 101,
+        BLESSED_LOSS => -101,
+        LOSS => -1,
+        _ => 0,
+    }
+}
+
+/// Order of root moves by `dtz` from the root: real wins soonest
+/// first, then wins the fifty-move rule spoils, then draws, then
+/// losses latest first. One ply of slack against the fifty-move
+/// counter, as tables that count moves rather than plies round up.
+fn root_rank(dtz: i32, halfmove_clock: i32) -> (u8, i32) {
+    match dtz.signum() {
+        1 if dtz + halfmove_clock <= 99 => (3, -dtz),
+        1 => (2, -dtz),
+        0 => (1, 0),
+        _ => (0, -dtz),
+    }
+}
+
+// ---------------------------------------------------------------------
+// Position indices
+// ---------------------------------------------------------------------
+
+/// The square tables the index encoding is built from.
+struct Maps {
+    /// Squares below the a1–h8 diagonal, 0..28.
+    b1h1h7: [u64; 64],
+    /// The a1–d1–d4 triangle, 0..10: below the diagonal first.
+    a1d1d4: [u64; 64],
+    /// Both kings, the first in the triangle: 462 values.
+    kk: [[u64; 64]; 10],
+    /// `binomial[k][n]`: ways to choose `k` of `n`.
+    binomial: [[u64; 64]; MAX_TB_PIECES],
+    /// Pawn squares a2–h7, 0..48: toward the edge and the second rank
+    /// is higher. The leading pawn is the highest.
+    pawns: [u64; 64],
+    /// Index of the leading pawn's square, by how many pawns lead.
+    lead_pawn_idx: [[u64; 64]; 6],
+    /// Values of the leading pawns, by how many and the file.
+    lead_pawns_size: [[u64; 4]; 6],
+}
+
+fn off_a1h8(sq: u8) -> i32 {
+    (sq >> 3) as i32 - (sq & 7) as i32
+}
+
+impl Maps {
+    fn new() -> Self {
+        let mut maps = Maps {
+            b1h1h7: [0; 64],
+            a1d1d4: [0; 64],
+            kk: [[0; 64]; 10],
+            binomial: [[0; 64]; MAX_TB_PIECES],
+            pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+        let mut code = 0;
+        for sq in 0..64 {
+            if off_a1h8(sq) < 0 {
+                maps.b1h1h7[sq as usize] = code;
+                code += 1;
+            }
+        }
+
+        let mut diagonal = Vec::new();
+        code = 0;
+        for sq in 0..=27u8 {
+            if off_a1h8(sq) < 0 && sq & 7 <= 3 {
+                maps.a1d1d4[sq as usize] = code;
+                code += 1;
+            } else if off_a1h8(sq) == 0 && sq & 7 <= 3 {
+                diagonal.push(sq);
+            }
+        }
+        for sq in diagonal {
+            maps.a1d1d4[sq as usize] = code;
+            code += 1;
+        }
+
+        // Kings on the diagonal both are encoded last.
+        let mut both_on_diagonal = Vec::new();
+        code = 0;
+        for idx in 0..10 {
+            for s1 in 0..=27u8 {
+                // b1 is the only square of the triangle mapped to 0.
+                if maps.a1d1d4[s1 as usize] != idx || (idx == 0 && s1 != 1) {
+                    continue;
+                }
+                for s2 in 0..64u8 {
+                    let adjacent =
+                        (s1 & 7).abs_diff(s2 & 7) <= 1 && (s1 >> 3).abs_diff(s2 >> 3) <= 1;
+                    if adjacent || (off_a1h8(s1) == 0 && off_a1h8(s2) > 0) {
+                        continue;
+                    }
+                    if off_a1h8(s1) == 0 && off_a1h8(s2) == 0 {
+                        both_on_diagonal.push((idx as usize, s2 as usize));
+                    } else {
+                        maps.kk[idx as usize][s2 as usize] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, s2) in both_on_diagonal {
+            maps.kk[idx][s2] = code;
+            code += 1;
+        }
+
+        maps.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_TB_PIECES.min(n + 1) {
+                maps.binomial[k][n] = if k > 0 {
+                    maps.binomial[k - 1][n - 1]
+                } else {
+                    0
+                } + if k < n { maps.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        let mut available = 47i64;
+        for lead_pawns in 1..=5 {
+            for file in 0..4 {
+                let mut idx = 0;
+                for rank in 1..=6 {
+                    let sq = rank * 8 + file;
+                    if lead_pawns == 1 {
+                        maps.pawns[sq] = available as u64;
+                        maps.pawns[sq ^ 7] = (available - 1) as u64;
+                        available -= 2;
+                    }
+                    maps.lead_pawn_idx[lead_pawns][sq] = idx;
+                    idx += maps.binomial[lead_pawns - 1][maps.pawns[sq] as usize];
+                }
+                maps.lead_pawns_size[lead_pawns][file] = idx;
+            }
+        }
+        maps
+    }
+}
+
+/// Piece codes as the files store them: 1–6 for White's pawn, knight,
+/// bishop, rook, queen and king, 9–14 for Black's.
+pub(crate) fn piece_code(piece_type: PieceType, color: Color) -> u8 {
+    let code = match piece_type {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    };
+    code | (color.idx() as u8) << 3
+}
+
+const PAWN_CODE: u8 = 1;
+
+/// What a table's index depends on in its material.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Material {
+    /// With White as the file name's first side...
+    pub(crate) key: MaterialKey,
+    /// ...and with the colours swapped.
+    pub(crate) key2: MaterialKey,
+    pub(crate) pieces: usize,
+    pub(crate) has_pawns: bool,
+    /// Some side has exactly one of some piece other than its king.
+    pub(crate) has_unique_pieces: bool,
+    /// The colour whose pawns lead, as the table sees it: the side
+    /// with fewer, which compresses better.
+    pub(crate) lead_color: Color,
+    /// Pawns of the leading colour, and of the other one.
+    pub(crate) pawn_count: [u32; 2],
+}
+
+impl Material {
+    pub(crate) fn new(key: MaterialKey) -> Self {
+        let count = |color, piece_type| key.count(color, piece_type);
+        let pieces = [Color::White, Color::Black]
+            .into_iter()
+            .flat_map(|color| PieceType::ALL.map(|piece_type| count(color, piece_type)))
+            .sum::<u32>() as usize;
+        let has_unique_pieces = [Color::White, Color::Black].into_iter().any(|color| {
+            PieceType::ALL
+                .into_iter()
+                .any(|piece_type| piece_type != PieceType::King && count(color, piece_type) == 1)
+        });
+        let (white_pawns, black_pawns) = (
+            count(Color::White, PieceType::Pawn),
+            count(Color::Black, PieceType::Pawn),
+        );
+        let white_leads = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+        let lead_color = if white_leads {
+            Color::White
+        } else {
+            Color::Black
+        };
+        Self {
+            key,
+            key2: key.flipped(),
+            pieces,
+            has_pawns: white_pawns + black_pawns > 0,
+            has_unique_pieces,
+            lead_color,
+            pawn_count: if white_leads {
+                [white_pawns, black_pawns]
+            } else {
+                [black_pawns, white_pawns]
+            },
+        }
+    }
+
+    /// Pawns on both sides.
+    fn both_pawns(&self) -> bool {
+        self.has_pawns && self.pawn_count[1] > 0
+    }
+
+    /// Tables per file: four with pawns (files a–d), one without.
+    pub(crate) fn files(&self) -> usize {
+        if self.has_pawns {
+            4
+        } else {
+            1
+        }
+    }
+
+    /// Sides to move a table stores.
+    pub(crate) fn sides(&self, dtz: bool) -> usize {
+        if !dtz && self.key != self.key2 {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/// How one table orders its pieces into groups, and what each group's
+/// index is worth.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Layout {
+    pub(crate) pieces: [u8; MAX_TB_PIECES],
+    group_len: [usize; MAX_TB_PIECES + 1],
+    group_idx: [u64; MAX_TB_PIECES + 1],
+}
+
+impl Layout {
+    /// Stockfish's `set_groups`. `order` says where among the groups'
+    /// factors the leading group and the other side's pawns go.
+    pub(crate) fn new(
+        material: &Material,
+        pieces: &[u8],
+        order: [u8; 2],
+        file: usize,
+    ) -> Result<Self, String> {
+        let maps = &*MAPS;
+        let mut layout = Layout::default();
+        layout.pieces[..pieces.len()].copy_from_slice(pieces);
+        let mut first_len = if material.has_pawns {
+            0
+        } else if material.has_unique_pieces {
+            3
+        } else {
+            2
+        };
+        let mut n = 0;
+        layout.group_len[0] = 1;
+        for i in 1..material.pieces {
+            first_len -= 1;
+            if first_len > 0 || pieces[i] == pieces[i - 1] {
+                layout.group_len[n] += 1;
+            } else {
+                n += 1;
+                layout.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        layout.group_len[n] = 0;
+        if layout.group_len[..n]
+            .iter()
+            .any(|&len| len >= MAX_TB_PIECES)
+            || (material.has_pawns && layout.group_len[0] > 5)
+        {
+            return Err("bad piece groups".into());
+        }
+
+        let both_pawns = material.both_pawns();
+        let mut next = if both_pawns { 2 } else { 1 };
+        let mut free_squares =
+            64 - layout.group_len[0] - if both_pawns { layout.group_len[1] } else { 0 };
+        let mut idx = 1u64;
+        let mut k = 0;
+        while next < n || k == order[0] || k == order[1] {
+            if k == order[0] {
+                layout.group_idx[0] = idx;
+                idx *= if material.has_pawns {
+                    maps.lead_pawns_size[layout.group_len[0]][file]
+                } else if material.has_unique_pieces {
+                    UNIQUE_PIECES_SIZE
+                } else {
+                    KINGS_SIZE
+                };
+            } else if k == order[1] {
+                layout.group_idx[1] = idx;
+                idx *= maps.binomial[layout.group_len[1]][48 - layout.group_len[0]];
+            } else {
+                layout.group_idx[next] = idx;
+                idx *= maps.binomial[layout.group_len[next]][free_squares];
+                free_squares -= layout.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        layout.group_idx[n] = idx;
+        Ok(layout)
+    }
+
+    /// Positions in the table: one past the largest index.
+    pub(crate) fn size(&self) -> u64 {
+        let groups = self.group_len.iter().position(|&len| len == 0).unwrap_or(0);
+        self.group_idx[groups]
+    }
+}
+
+/// A position as the index sees it: piece codes (see [`piece_code`])
+/// and their squares, a1 = 0.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Placement {
+    pub(crate) codes: [u8; MAX_TB_PIECES],
+    pub(crate) squares: [u8; MAX_TB_PIECES],
+    pub(crate) len: usize,
+    pub(crate) black_to_move: bool,
+}
+
+impl Placement {
+    pub(crate) fn of(board: &Board) -> Self {
+        let mut placement = Placement {
+            codes: [0; MAX_TB_PIECES],
+            squares: [0; MAX_TB_PIECES],
+            len: 0,
+            black_to_move: board.side_to_move == Color::Black,
+        };
+        let mut occupied = board.colors[0] | board.colors[1];
+        while occupied != 0 && placement.len < MAX_TB_PIECES {
+            let sq = occupied.trailing_zeros() as u8;
+            occupied &= occupied - 1;
+            let piece: Piece = board.get_piece_at_square_fast(sq).expect("occupied square");
+            placement.codes[placement.len] = piece_code(piece.piece_type(), piece.color());
+            placement.squares[placement.len] = sq;
+            placement.len += 1;
+        }
+        placement
+    }
+}
+
+/// A placement turned the way its table stores it: colours swapped if
+/// the table has the other side stronger, the leading pawn found.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Oriented {
+    /// 0 when the table's White is to move.
+    pub(crate) stm: usize,
+    /// Which of the table's files: the leading pawn's, a–d.
+    pub(crate) file: usize,
+    squares: [u8; MAX_TB_PIECES],
+    codes: [u8; MAX_TB_PIECES],
+    len: usize,
+    lead_pawns: usize,
+}
+
+impl Oriented {
+    /// `lead_pawn` is the first piece of the table's layouts (a pawn
+    /// when it has any); `flip` swaps the colours.
+    pub(crate) fn new(
+        material: &Material,
+        lead_pawn: u8,
+        flip: bool,
+        placement: &Placement,
+    ) -> Self {
+        let maps = &*MAPS;
+        let flip_color = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let mut oriented = Oriented {
+            stm: (flip ^ placement.black_to_move) as usize,
+            file: 0,
+            squares: [0; MAX_TB_PIECES],
+            codes: [0; MAX_TB_PIECES],
+            len: 0,
+            lead_pawns: 0,
+        };
+        let pieces = || {
+            placement.codes[..placement.len]
+                .iter()
+                .zip(&placement.squares)
+        };
+        let lead = lead_pawn ^ flip_color;
+        if material.has_pawns {
+            for (_, &sq) in pieces().filter(|&(&code, _)| code == lead) {
+                oriented.squares[oriented.len] = sq ^ flip_squares;
+                oriented.codes[oriented.len] = lead ^ flip_color;
+                oriented.len += 1;
+            }
+            oriented.lead_pawns = oriented.len;
+            let leading = (0..oriented.lead_pawns)
+                .max_by_key(|&i| {
+                    (
+                        maps.pawns[oriented.squares[i] as usize],
+                        std::cmp::Reverse(i),
+                    )
+                })
+                .unwrap_or(0);
+            oriented.squares.swap(0, leading);
+            let file = (oriented.squares[0] & 7) as usize;
+            oriented.file = file.min(7 - file);
+        }
+        for (&code, &sq) in pieces() {
+            if material.has_pawns && code == lead {
+                continue;
+            }
+            oriented.squares[oriented.len] = sq ^ flip_squares;
+            oriented.codes[oriented.len] = code ^ flip_color;
+            oriented.len += 1;
+        }
+        oriented
+    }
+
+    /// Stockfish's index encoding for a table with `layout`.
+    pub(crate) fn index(mut self, material: &Material, layout: &Layout) -> u64 {
+        let maps = &*MAPS;
+        let len = self.len;
+        let squares = &mut self.squares;
+        // Put the pieces in the table's order.
+        for i in self.lead_pawns..len.saturating_sub(1) {
+            if let Some(j) = (i + 1..len).find(|&j| self.codes[j] == layout.pieces[i]) {
+                self.codes.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+        // The leading piece goes to files a–d...
+        if squares[0] & 7 > 3 {
+            squares[..len].iter_mut().for_each(|sq| *sq ^= 7);
+        }
+        let mut idx;
+        if material.has_pawns {
+            let lead = self.lead_pawns;
+            idx = maps.lead_pawn_idx[lead][squares[0] as usize];
+            squares[1..lead].sort_by_key(|&sq| maps.pawns[sq as usize]);
+            for (i, &sq) in squares.iter().enumerate().take(lead).skip(1) {
+                idx += maps.binomial[i][maps.pawns[sq as usize] as usize];
+            }
+        } else {
+            // ...ranks 1–4, and below the a1–h8 diagonal: the first
+            // piece of the leading group off it decides.
+            if squares[0] >> 3 > 3 {
+                squares[..len].iter_mut().for_each(|sq| *sq ^= 56);
+            }
+            for i in 0..layout.group_len[0] {
+                let off = off_a1h8(squares[i]);
+                if off == 0 {
+                    continue;
+                }
+                if off > 0 {
+                    for sq in &mut squares[i..len] {
+                        *sq = ((*sq >> 3) | (*sq << 3)) & 63;
+                    }
+                }
+                break;
+            }
+            idx = if material.has_unique_pieces {
+                let (s0, s1, s2) = (squares[0] as u64, squares[1] as u64, squares[2] as u64);
+                let adjust1 = (s1 > s0) as u64;
+                let adjust2 = (s2 > s0) as u64 + (s2 > s1) as u64;
+                let (r0, r1, r2) = (s0 >> 3, s1 >> 3, s2 >> 3);
+                if off_a1h8(squares[0]) != 0 {
+                    (maps.a1d1d4[s0 as usize] * 63 + s1 - adjust1) * 62 + s2 - adjust2
+                } else if off_a1h8(squares[1]) != 0 {
+                    (6 * 63 + r0 * 28 + maps.b1h1h7[s1 as usize]) * 62 + s2 - adjust2
+                } else if off_a1h8(squares[2]) != 0 {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + r0 * 7 * 28
+                        + (r1 - adjust1) * 28
+                        + maps.b1h1h7[s2 as usize]
+                } else {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + 4 * 7 * 28
+                        + r0 * 7 * 6
+                        + (r1 - adjust1) * 6
+                        + (r2 - adjust2)
+                }
+            } else {
+                maps.kk[maps.a1d1d4[squares[0] as usize] as usize][squares[1] as usize]
+            };
+        }
+
+        // The other groups, each a combination of the squares the
+        // groups before it left free.
+        idx *= layout.group_idx[0];
+        let mut start = layout.group_len[0];
+        let mut remaining_pawns = material.both_pawns();
+        let mut next = 1;
+        while layout.group_len[next] != 0 {
+            let group_len = layout.group_len[next];
+            squares[start..start + group_len].sort_unstable();
+            let mut n = 0;
+            for i in 0..group_len {
+                let sq = squares[start + i];
+                let adjust = squares[..start]
+                    .iter()
+                    .filter(|&&earlier| sq > earlier)
+                    .count();
+                let free = sq as usize - adjust - if remaining_pawns { 8 } else { 0 };
+                n += maps.binomial[i + 1][free];
+            }
+            remaining_pawns = false;
+            idx += n * layout.group_idx[next];
+            start += group_len;
+            next += 1;
+        }
+        idx
+    }
+}
+
+/// The pieces of `material` in the order [`crate::tbgen`] lays them
+/// out, as the table sees them: the leading pawns, the other side's
+/// pawns, then the kings, then each kind of piece together. Without
+/// pawns a unique piece follows the kings, so the three lead.
+pub(crate) fn default_pieces(material: &Material) -> Vec<u8> {
+    let lead = material.lead_color;
+    let mut pieces = Vec::with_capacity(material.pieces);
+    let mut push = |color: Color, piece_type: PieceType| {
+        for _ in 0..material.key.count(color, piece_type) {
+            pieces.push(piece_code(piece_type, color));
+        }
+    };
+    if material.has_pawns {
+        push(lead, PieceType::Pawn);
+        push(lead.opponent(), PieceType::Pawn);
+    }
+    push(Color::White, PieceType::King);
+    push(Color::Black, PieceType::King);
+    let others = [
+        PieceType::Queen,
+        PieceType::Rook,
+        PieceType::Bishop,
+        PieceType::Knight,
+    ];
+    let unique = others
+        .into_iter()
+        .flat_map(|pt| [(Color::White, pt), (Color::Black, pt)])
+        .find(|&(color, pt)| material.key.count(color, pt) == 1);
+    if let (false, Some((color, piece_type))) = (material.has_pawns, unique) {
+        push(color, piece_type);
+    }
+    for color in [Color::White, Color::Black] {
+        for piece_type in others {
+            if material.has_pawns || unique != Some((color, piece_type)) {
+                push(color, piece_type);
+            }
+        }
+    }
+    pieces
+}
+
+// ---------------------------------------------------------------------
+// Reading a file
+// ---------------------------------------------------------------------
+
+/// One file's contents and what its header says about them.
+pub(crate) struct Table {
+    bytes: Vec<u8>,
+    /// By file (a–d with pawns, one without), then side to move.
+    pairs: Vec<Vec<PairsData>>,
+    /// Start of the DTZ value map.
+    map: usize,
+}
+
+/// One table: the Huffman code and where its blocks are. Positions are
+/// offsets into the file.
+#[derive(Debug, Clone, Default)]
+struct PairsData {
+    layout: Layout,
+    flags: u8,
+    /// The shortest code length, or the value of a single-value table.
+    min_sym_len: u8,
+    block_size: usize,
+    span: u64,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    block_length: usize,
+    block_length_size: usize,
+    num_blocks: usize,
+    data: usize,
+    lowest_sym: usize,
+    btree: usize,
+    /// The smallest code of each length, left-aligned.
+    base64: Vec<u64>,
+    /// How many values each symbol stands for, less one.
+    symlen: Vec<u8>,
+    map_idx: [u16; 4],
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn skip(&mut self, n: usize) -> Result<usize, String> {
+        let start = self.pos;
+        if self.bytes.len() - start < n {
+            return Err("file is truncated".into());
+        }
+        self.pos += n;
+        Ok(start)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        let at = self.skip(1)?;
+        Ok(self.bytes[at])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let at = self.skip(2)?;
+        Ok(u16::from_le_bytes([self.bytes[at], self.bytes[at + 1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let at = self.skip(4)?;
+        Ok(u32::from_le_bytes(
+            self.bytes[at..at + 4].try_into().unwrap(),
+        ))
+    }
+
+    fn align(&mut self, to: usize) -> Result<(), String> {
+        let padding = self.pos.next_multiple_of(to) - self.pos;
+        self.skip(padding).map(|_| ())
+    }
+}
+
+impl Table {
+    fn parse(bytes: Vec<u8>, material: &Material, dtz: bool) -> Result<Self, String> {
+        let magic = if dtz { DTZ_MAGIC } else { WDL_MAGIC };
+        if bytes.len() < 5 || bytes[..4] != magic {
+            return Err("not a Syzygy table".into());
+        }
+        let mut cursor = Cursor {
+            bytes: &bytes,
+            pos: 4,
+        };
+        let flags = cursor.u8()?;
+        if (flags & HAS_PAWNS != 0) != material.has_pawns
+            || (flags & SPLIT != 0) != (material.key != material.key2)
+        {
+            return Err("header doesn't match the material in the name".into());
+        }
+        let sides = material.sides(dtz);
+        let mut expected = default_pieces(material);
+        expected.sort_unstable();
+
+        let mut pairs = Vec::with_capacity(material.files());
+        for file in 0..material.files() {
+            let first = cursor.u8()?;
+            let second = if material.both_pawns() {
+                cursor.u8()?
+            } else {
+                0xFF
+            };
+            let order = [[first & 0xF, second & 0xF], [first >> 4, second >> 4]];
+            let mut pieces = [[0u8; MAX_TB_PIECES]; 2];
+            for k in 0..material.pieces {
+                let byte = cursor.u8()?;
+                pieces[0][k] = byte & 0xF;
+                pieces[1][k] = byte >> 4;
+            }
+            let mut tables = Vec::with_capacity(sides);
+            for side in 0..sides {
+                let pieces = &pieces[side][..material.pieces];
+                let mut sorted = pieces.to_vec();
+                sorted.sort_unstable();
+                if sorted != expected || (material.has_pawns && pieces[0] & 7 != PAWN_CODE) {
+                    return Err("header doesn't match the material in the name".into());
+                }
+                let layout = Layout::new(material, pieces, order[side], file)?;
+                tables.push(PairsData {
+                    layout,
+                    ..PairsData::default()
+                });
+            }
+            pairs.push(tables);
+        }
+        cursor.align(2)?;
+        for tables in &mut pairs {
+            for pairs in tables {
+                pairs.read_sizes(&mut cursor)?;
+            }
+        }
+
+        let map = cursor.pos;
+        if dtz {
+            for tables in &mut pairs {
+                let pairs = &mut tables[0];
+                if pairs.flags & MAPPED == 0 {
+                    continue;
+                }
+                if pairs.flags & WIDE != 0 {
+                    cursor.align(2)?;
+                    for i in 0..4 {
+                        pairs.map_idx[i] = ((cursor.pos - map) / 2 + 1) as u16;
+                        let len = cursor.u16()? as usize;
+                        cursor.skip(2 * len)?;
+                    }
+                } else {
+                    for i in 0..4 {
+                        pairs.map_idx[i] = (cursor.pos - map + 1) as u16;
+                        let len = cursor.u8()? as usize;
+                        cursor.skip(len)?;
+                    }
+                }
+            }
+            cursor.align(2)?;
+        }
+
+        for pairs in pairs.iter_mut().flatten() {
+            pairs.sparse_index = cursor.skip(6 * pairs.sparse_index_size)?;
+        }
+        for pairs in pairs.iter_mut().flatten() {
+            pairs.block_length = cursor.skip(2 * pairs.block_length_size)?;
+        }
+        for pairs in pairs.iter_mut().flatten() {
+            if pairs.num_blocks > 0 {
+                cursor.align(64)?;
+                pairs.data = cursor.skip(pairs.num_blocks * pairs.block_size)?;
+            }
+        }
+        Ok(Table { bytes, pairs, map })
+    }
+
+    fn u16_at(&self, pos: usize) -> Option<u16> {
+        let bytes = self.bytes.get(pos..pos + 2)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Big-endian bytes at `pos`, as zeros past the end of the file.
+    fn be_at(&self, pos: usize, len: usize) -> u64 {
+        (pos..pos + len).fold(0, |acc, at| {
+            acc << 8 | *self.bytes.get(at).unwrap_or(&0) as u64
+        })
+    }
+
+    fn pair(&self, pairs: &PairsData, sym: usize) -> (usize, usize) {
+        pair(&self.bytes, pairs.btree, sym)
+    }
+
+    /// The value stored at `idx`: Stockfish's `decompress_pairs`.
+    fn decompress(&self, pairs: &PairsData, idx: u64) -> Option<u16> {
+        if pairs.flags & SINGLE_VALUE != 0 {
+            return Some(pairs.min_sym_len as u16);
+        }
+        let k = (idx / pairs.span) as usize;
+        if k >= pairs.sparse_index_size {
+            return None;
+        }
+        let entry = pairs.sparse_index + 6 * k;
+        let mut block = u32::from_le_bytes(self.bytes[entry..entry + 4].try_into().ok()?) as usize;
+        let mut offset = self.u16_at(entry + 4)? as i64;
+        offset += (idx % pairs.span) as i64 - (pairs.span / 2) as i64;
+
+        let block_length = |block: usize| -> Option<i64> {
+            if block >= pairs.block_length_size {
+                return None;
+            }
+            self.u16_at(pairs.block_length + 2 * block).map(i64::from)
+        };
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+        if block >= pairs.num_blocks {
+            return None;
+        }
+
+        // Codes are read most significant bit first; a symbol of
+        // length `len` is at least `base64[len - min_sym_len]`.
+        let min = pairs.min_sym_len as usize;
+        let mut ptr = pairs.data + block * pairs.block_size;
+        let mut buf = self.be_at(ptr, 8);
+        ptr += 8;
+        let mut buf_size = 64;
+        let mut sym = loop {
+            let mut len = 0;
+            while buf < pairs.base64[len] {
+                len += 1;
+            }
+            let lowest = self.u16_at(pairs.lowest_sym + 2 * len)? as usize;
+            let sym = ((buf - pairs.base64[len]) >> (64 - len - min)) as usize + lowest;
+            let sym_len = *pairs.symlen.get(sym)? as i64;
+            if offset < sym_len + 1 {
+                break sym;
+            }
+            offset -= sym_len + 1;
+            let len = len + min;
+            buf <<= len;
+            buf_size -= len;
+            if buf_size <= 32 {
+                buf_size += 32;
+                buf |= self.be_at(ptr, 4) << (64 - buf_size);
+                ptr += 4;
+            }
+        };
+        // Down the pairs to the value at `offset`.
+        while pairs.symlen[sym] != 0 {
+            let (left, right) = self.pair(pairs, sym);
+            if offset < pairs.symlen[left] as i64 + 1 {
+                sym = left;
+            } else {
+                offset -= pairs.symlen[left] as i64 + 1;
+                sym = right;
+            }
+        }
+        Some(self.pair(pairs, sym).0 as u16)
+    }
+
+    /// A DTZ table's stored `value` as plies, for a position with
+    /// result `wdl`.
+    fn map_score(&self, file: usize, value: u16, wdl: i32) -> i32 {
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+        let pairs = &self.pairs[file][0];
+        let mut value = value as i32;
+        if pairs.flags & MAPPED != 0 {
+            let idx = pairs.map_idx[WDL_MAP[(wdl + 2) as usize]] as usize + value as usize;
+            value = if pairs.flags & WIDE != 0 {
+                self.u16_at(self.map + 2 * idx).unwrap_or(0) as i32
+            } else {
+                *self.bytes.get(self.map + idx).unwrap_or(&0) as i32
+            };
+        }
+        // Stored in moves unless flagged as plies.
+        if (wdl == WIN && pairs.flags & WIN_PLIES == 0)
+            || (wdl == LOSS && pairs.flags & LOSS_PLIES == 0)
+            || wdl == CURSED_WIN
+            || wdl == BLESSED_LOSS
+        {
+            value *= 2;
+        }
+        value + 1
+    }
+}
+
+impl PairsData {
+    /// Stockfish's `set_sizes`.
+    fn read_sizes(&mut self, cursor: &mut Cursor) -> Result<(), String> {
+        self.flags = cursor.u8()?;
+        if self.flags & SINGLE_VALUE != 0 {
+            self.min_sym_len = cursor.u8()?;
+            return Ok(());
+        }
+        let size = self.layout.size();
+        let log2_block_size = cursor.u8()?;
+        let log2_span = cursor.u8()?;
+        if log2_block_size > 20 || !(1..40).contains(&log2_span) {
+            return Err("bad block size".into());
+        }
+        self.block_size = 1 << log2_block_size;
+        self.span = 1 << log2_span;
+        self.sparse_index_size = size.div_ceil(self.span) as usize;
+        let padding = cursor.u8()? as usize;
+        self.num_blocks = cursor.u32()? as usize;
+        self.block_length_size = self.num_blocks + padding;
+        let max_sym_len = cursor.u8()? as usize;
+        self.min_sym_len = cursor.u8()?;
+        let min_sym_len = self.min_sym_len as usize;
+        if min_sym_len == 0 || max_sym_len < min_sym_len || max_sym_len > 32 {
+            return Err("bad code lengths".into());
+        }
+        let lengths = max_sym_len - min_sym_len + 1;
+        self.lowest_sym = cursor.skip(2 * lengths)?;
+        let lowest = |i: usize| {
+            let at = self.lowest_sym + 2 * i;
+            u16::from_le_bytes([cursor.bytes[at], cursor.bytes[at + 1]]) as u64
+        };
+        self.base64 = vec![0; lengths];
+        for i in (0..lengths - 1).rev() {
+            self.base64[i] = (self.base64[i + 1]
+                .wrapping_add(lowest(i))
+                .wrapping_sub(lowest(i + 1)))
+                / 2;
+        }
+        for (i, base) in self.base64.iter_mut().enumerate() {
+            *base <<= 64 - i - min_sym_len;
+        }
+        let symbols = cursor.u16()? as usize;
+        self.btree = cursor.skip(3 * symbols)?;
+        self.symlen = symbol_lengths(cursor.bytes, self.btree, symbols)?;
+        cursor.skip(symbols & 1)?;
+        Ok(())
+    }
+}
+
+/// Left and right halves of symbol `sym`'s pair, in the tree at
+/// `btree`. A symbol that isn't a pair has right half 0xFFF and its
+/// value on the left.
+fn pair(bytes: &[u8], btree: usize, sym: usize) -> (usize, usize) {
+    let at = btree + 3 * sym;
+    let (b0, b1, b2) = (
+        bytes[at] as usize,
+        bytes[at + 1] as usize,
+        bytes[at + 2] as usize,
+    );
+    ((b1 & 0xF) << 8 | b0, b2 << 4 | b1 >> 4)
+}
+
+/// How many values each of the `symbols` symbols in the tree at
+/// `btree` stands for, less one: Stockfish's `set_symlen`.
+fn symbol_lengths(bytes: &[u8], btree: usize, symbols: usize) -> Result<Vec<u8>, String> {
+    fn visit(
+        bytes: &[u8],
+        btree: usize,
+        sym: usize,
+        visited: &mut [bool],
+        symlen: &mut [u8],
+    ) -> Result<u8, String> {
+        visited[sym] = true;
+        let (left, right) = pair(bytes, btree, sym);
+        if right == 0xFFF {
+            return Ok(0);
+        }
+        if left >= symlen.len() || right >= symlen.len() {
+            return Err("bad symbol tree".into());
+        }
+        for child in [left, right] {
+            if !visited[child] {
+                symlen[child] = visit(bytes, btree, child, visited, symlen)?;
+            }
+        }
+        let len = symlen[left] as usize + symlen[right] as usize + 1;
+        u8::try_from(len).map_err(|_| "bad symbol tree".to_string())
+    }
+
+    let mut symlen = vec![0; symbols];
+    let mut visited = vec![false; symbols];
+    for sym in 0..symbols {
+        if !visited[sym] {
+            symlen[sym] = visit(bytes, btree, sym, &mut visited, &mut symlen)?;
+        }
+    }
+    Ok(symlen)
+}
@@ -0,0 +1,130 @@
+//! Tablebase probing for the search: perfect-play results for
+//! positions with very little material.
+//!
+//! Two kinds of table answer: the Syzygy files under `SyzygyPath`
+//! ([`crate::syzygy`]), when any are loaded, and the built-in KPK
+//! bitbase ([`crate::kpk`]) plus the draws by insufficient material,
+//! which need no table at all. The search probes them in two places:
+//!
+//! - at the root, [`root_moves`] keeps only the moves that hold the
+//!   best result, so a won ending is never traded for a draw and a
+//!   drawn one never thrown away, however the evaluation sees it. With
+//!   Syzygy DTZ tables it keeps only the quickest wins (and slowest
+//!   losses) among them too, so a won ending is also won in time;
+//! - inside the tree, a probed draw ends the search of a node, and so
+//!   does a Syzygy win or loss. KPK wins and losses are left to the
+//!   evaluation, which knows them too (see `Searcher::negamax_ab`).
+//!
+//! A Syzygy WDL result ignores the fifty-move counter, so inside the
+//! tree the files are only asked right after a capture or pawn move,
+//! where a win the rule would spoil shows up as a draw.
+
+use crate::board::Board;
+use crate::kpk;
+use crate::syzygy;
+use crate::types::{Color, Move, PieceType};
+
+/// Pieces in the KPK bitbase, kings included.
+const KPK_PIECES: u32 = 3;
+
+/// Nothing with more pieces than this, kings included, is in a table.
+pub fn max_pieces() -> u32 {
+    KPK_PIECES.max(syzygy::max_pieces())
+}
+
+/// A tablebase result, for the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Wdl {
+    /// The same result for the other side.
+    pub fn flipped(self) -> Self {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+}
+
+/// Which table a result came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Syzygy,
+    Kpk,
+}
+
+/// The result with best play for the side to move, if a table knows
+/// it, and which table that was. Ignores repetitions. The Syzygy files
+/// are asked only with the fifty-move counter at zero, where their
+/// wins spoilt by the rule count as draws.
+pub fn probe(board: &mut Board) -> Option<(Wdl, Source)> {
+    let pieces = (board.colors[0] | board.colors[1]).count_ones();
+    if pieces <= syzygy::max_pieces() && board.halfmove_clock == 0 {
+        if let Some(wdl) = syzygy::probe_wdl(board) {
+            let wdl = match wdl {
+                syzygy::WIN => Wdl::Win,
+                syzygy::LOSS => Wdl::Loss,
+                _ => Wdl::Draw,
+            };
+            return Some((wdl, Source::Syzygy));
+        }
+    }
+    if pieces > KPK_PIECES {
+        return None;
+    }
+    probe_kpk(board).map(|wdl| (wdl, Source::Kpk))
+}
+
+/// The result with best play for the side to move, if a table knows
+/// it. See [`probe`].
+pub fn probe_wdl(board: &mut Board) -> Option<Wdl> {
+    probe(board).map(|(wdl, _)| wdl)
+}
+
+fn probe_kpk(board: &Board) -> Option<Wdl> {
+    if board.is_insufficient_material() {
+        return Some(Wdl::Draw);
+    }
+    let pawn_side_wins = kpk::probe(board)?;
+    let pawn_side = if board.piece_bb(PieceType::Pawn, Color::White) != 0 {
+        Color::White
+    } else {
+        Color::Black
+    };
+    Some(match (pawn_side_wins, board.side_to_move == pawn_side) {
+        (false, _) => Wdl::Draw,
+        (true, true) => Wdl::Win,
+        (true, false) => Wdl::Loss,
+    })
+}
+
+/// The moves among `moves` (legal moves in `board`) that keep the best
+/// result the tables know of, in their original order. `None` unless
+/// every move leads to a position a table knows. The Syzygy tables,
+/// when they cover `board`, rank the moves by DTZ as well
+/// ([`syzygy::root_moves`]).
+pub fn root_moves(board: &mut Board, moves: &[Move]) -> Option<Vec<Move>> {
+    if let Some(keep) = syzygy::root_moves(board, moves) {
+        return Some(keep);
+    }
+    let mut results = Vec::with_capacity(moves.len());
+    for mv in moves {
+        board.apply_move(mv);
+        let result = probe_wdl(board).map(Wdl::flipped);
+        board.undo_last_move();
+        results.push((*mv, result?));
+    }
+    let best = results.iter().map(|&(_, result)| result).max()?;
+    Some(
+        results
+            .into_iter()
+            .filter(|&(_, result)| result == best)
+            .map(|(mv, _)| mv)
+            .collect(),
+    )
+}
@@ -0,0 +1,743 @@
+//! Syzygy table generation: solve an endgame by retrograde analysis and
+//! write its `.rtbw` and `.rtbz` files in the format [`crate::syzygy`]
+//! reads.
+//!
+//! Meant for small tables: test fixtures, and checking the reader
+//! against the move generator. Every position is held in memory with
+//! its moves, so [`MAX_PIECES`] is low. The files use the format's
+//! simplest options rather than the Syzygy generator's:
+//!
+//! - no symbol pairs, just a Huffman code over the values;
+//! - the DTZ table stores the positions with the side named first in
+//!   the file name to move, counted in plies;
+//! - positions no probe reads (illegal ones, and draws in a DTZ table)
+//!   hold the table's commonest value.
+//!
+//! Tables that would need the fifty-move rule's cursed wins or blessed
+//! losses are refused.
+//!
+//! A capture or promotion leaves the table. The result after one comes
+//! from the tables already in the output directory, so the smaller
+//! tables have to be generated first: `KQvK` before `KPvK`.
+
+use crate::board::Board;
+use crate::endgame::MaterialKey;
+use crate::fen::load_fen;
+use crate::syzygy::{
+    default_pieces, Layout, Material, Oriented, Placement, Tablebases, DRAW, DTZ_MAGIC, HAS_PAWNS,
+    LOSS, LOSS_PLIES, MAX_TB_PIECES, SINGLE_VALUE, SPLIT, WDL_MAGIC, WIN, WIN_PLIES,
+};
+use crate::types::Color;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::path::Path;
+
+/// Most pieces, kings included, [`generate`] takes on.
+pub const MAX_PIECES: usize = 4;
+
+const LOG2_BLOCK_SIZE: u8 = 6;
+const BLOCK_SIZE: usize = 1 << LOG2_BLOCK_SIZE;
+const LOG2_SPAN: u8 = 10;
+const SPAN: u64 = 1 << LOG2_SPAN;
+/// Longest Huffman code: the reader refills 32 bits at a time.
+const MAX_CODE_LEN: u8 = 24;
+
+/// What [`generate`] found, over the legal positions of both sides to
+/// move (each counted once, up to symmetry).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub signature: String,
+    pub positions: usize,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    /// The longest distance to zeroing, in plies.
+    pub max_dtz: i32,
+}
+
+/// Solve the endgame `signature` (White's side first, as in `KRvK`) and
+/// write `<signature>.rtbw` and `<signature>.rtbz` into `dir`, which
+/// must already hold the tables its captures and promotions lead to.
+pub fn generate(signature: &str, dir: &Path) -> Result<Summary, String> {
+    let key = MaterialKey::parse(signature)?;
+    let material = Material::new(key);
+    if !(3..=MAX_PIECES).contains(&material.pieces) {
+        return Err(format!(
+            "{signature}: only tables of 3 to {MAX_PIECES} pieces can be generated"
+        ));
+    }
+    let known = Tablebases::load(&dir.to_string_lossy())?;
+    let pieces = default_pieces(&material);
+    let order = [0, if material.pawn_count[1] > 0 { 1 } else { 0xF }];
+    let layouts = (0..material.files())
+        .map(|file| Layout::new(&material, &pieces, order, file))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut solver = Solver::new(&material, &layouts);
+    solver.enumerate(&pieces, &known, signature)?;
+    solver.solve_wdl();
+    solver.solve_dtz()?;
+
+    let summary = solver.summary(signature);
+    let wdl = solver.wdl_values();
+    let dtz = solver.dtz_values();
+    let name = key.to_string();
+    let write = |extension: &str, values: &[Vec<u16>], dtz: bool| {
+        let bytes = write_file(&material, &pieces, order, values, dtz)?;
+        let path = dir.join(format!("{name}.{extension}"));
+        std::fs::write(&path, bytes).map_err(|e| format!("{}: {e}", path.display()))
+    };
+    write("rtbw", &wdl, false)?;
+    write("rtbz", &dtz, true)?;
+    Ok(summary)
+}
+
+// ---------------------------------------------------------------------
+// Solving
+// ---------------------------------------------------------------------
+
+/// Where a move leads: a position in this table, or a result already
+/// known from another one.
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    Slot(usize),
+    Known(i32),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Successor {
+    target: Target,
+    /// A capture or pawn move.
+    zeroing: bool,
+}
+
+struct Node {
+    slot: usize,
+    moves: std::ops::Range<usize>,
+    in_check: bool,
+}
+
+/// Every position of one table, by slot: the table's files and sides
+/// one after another, each indexed as the reader indexes it.
+struct Solver<'a> {
+    material: &'a Material,
+    layouts: &'a [Layout],
+    /// First slot of each (file, side).
+    offsets: Vec<usize>,
+    sides: usize,
+    /// Slots enumerated so far, legal or not.
+    seen: Vec<bool>,
+    nodes: Vec<Node>,
+    successors: Vec<Successor>,
+    /// By slot; `None` for illegal positions.
+    wdl: Vec<Option<i32>>,
+    /// By slot; 0 for draws.
+    dtz: Vec<i32>,
+    mated: Vec<bool>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(material: &'a Material, layouts: &'a [Layout]) -> Self {
+        let sides = material.sides(false);
+        let mut offsets = Vec::new();
+        let mut total = 0;
+        for layout in layouts {
+            for _ in 0..sides {
+                offsets.push(total);
+                total += layout.size() as usize;
+            }
+        }
+        Solver {
+            material,
+            layouts,
+            offsets,
+            sides,
+            seen: vec![false; total],
+            nodes: Vec::new(),
+            successors: Vec::new(),
+            wdl: vec![None; total],
+            dtz: vec![0; total],
+            mated: vec![false; total],
+        }
+    }
+
+    /// The slot of `placement`, whose colours are the table's or, with
+    /// `flip`, swapped.
+    fn slot(&self, placement: &Placement, flip: bool) -> usize {
+        let oriented = Oriented::new(self.material, self.layouts[0].pieces[0], flip, placement);
+        let file = oriented.file;
+        let side = oriented.stm % self.sides;
+        let idx = oriented.index(self.material, &self.layouts[file]);
+        self.offsets[file * self.sides + side] + idx as usize
+    }
+
+    /// Whether the table sees `board` with its colours swapped.
+    fn flip(&self, board: &Board) -> bool {
+        let symmetric = self.material.key == self.material.key2;
+        MaterialKey::of(board) != self.material.key
+            || (symmetric && board.side_to_move == Color::Black)
+    }
+
+    /// Place the pieces every way, and note each new legal position's
+    /// moves.
+    fn enumerate(
+        &mut self,
+        pieces: &[u8],
+        known: &Tablebases,
+        signature: &str,
+    ) -> Result<(), String> {
+        let mut placement = Placement {
+            codes: [0; MAX_TB_PIECES],
+            squares: [0; MAX_TB_PIECES],
+            len: pieces.len(),
+            black_to_move: false,
+        };
+        placement.codes[..pieces.len()].copy_from_slice(pieces);
+        self.place(&mut placement, 0, 0, known, signature)
+    }
+
+    fn place(
+        &mut self,
+        placement: &mut Placement,
+        i: usize,
+        occupied: u64,
+        known: &Tablebases,
+        signature: &str,
+    ) -> Result<(), String> {
+        if i == placement.len {
+            for black_to_move in [false, true] {
+                placement.black_to_move = black_to_move;
+                let flip = black_to_move && self.material.key == self.material.key2;
+                let slot = self.slot(placement, flip);
+                if !self.seen[slot] {
+                    self.seen[slot] = true;
+                    self.add_node(slot, placement, known, signature)?;
+                }
+            }
+            return Ok(());
+        }
+        // Pawns stay off the first and last ranks.
+        let squares = if placement.codes[i] & 7 == 1 {
+            8..56
+        } else {
+            0..64
+        };
+        for sq in squares {
+            if occupied & (1 << sq) != 0 {
+                continue;
+            }
+            placement.squares[i] = sq;
+            self.place(placement, i + 1, occupied | 1 << sq, known, signature)?;
+        }
+        Ok(())
+    }
+
+    fn add_node(
+        &mut self,
+        slot: usize,
+        placement: &Placement,
+        known: &Tablebases,
+        signature: &str,
+    ) -> Result<(), String> {
+        let mut board = board_of(placement);
+        let (in_check, opponent_in_check) = match board.side_to_move {
+            Color::White => (board.white_king_in_check, board.black_king_in_check),
+            Color::Black => (board.black_king_in_check, board.white_king_in_check),
+        };
+        if opponent_in_check {
+            return Ok(());
+        }
+        let mut moves = Vec::new();
+        board.get_all_raw_moves_append(&mut moves);
+        let start = self.successors.len();
+        for mv in &moves {
+            board.apply_move(mv);
+            let zeroing = board.halfmove_clock == 0;
+            let key = MaterialKey::of(&board);
+            let target = if key == self.material.key || key == self.material.key2 {
+                Target::Slot(self.slot(&Placement::of(&board), self.flip(&board)))
+            } else if (board.colors[0] | board.colors[1]).count_ones() == 2 {
+                Target::Known(DRAW)
+            } else {
+                match known.probe_wdl(&mut board) {
+                    Some(wdl @ (LOSS | DRAW | WIN)) => Target::Known(wdl),
+                    Some(_) => return Err(format!("{signature}: {key} has cursed results")),
+                    None => return Err(format!("{signature} needs the {key} table first")),
+                }
+            };
+            board.undo_last_move();
+            self.successors.push(Successor { target, zeroing });
+        }
+        self.nodes.push(Node {
+            slot,
+            moves: start..self.successors.len(),
+            in_check,
+        });
+        Ok(())
+    }
+
+    /// The result of a move's target, for the side to move there.
+    fn wdl_of(&self, target: Target) -> Option<i32> {
+        match target {
+            Target::Slot(slot) => self.wdl[slot],
+            Target::Known(wdl) => Some(wdl),
+        }
+    }
+
+    /// Mates and stalemates first, then repeatedly: a win if some move
+    /// reaches a loss, a loss if every move reaches a win. What's left
+    /// is drawn.
+    fn solve_wdl(&mut self) {
+        let mut open: Vec<usize> = Vec::new();
+        for (n, node) in self.nodes.iter().enumerate() {
+            if node.moves.is_empty() {
+                self.wdl[node.slot] = Some(if node.in_check { LOSS } else { DRAW });
+                self.mated[node.slot] = node.in_check;
+            } else {
+                open.push(n);
+            }
+        }
+        loop {
+            let mut solved = Vec::new();
+            open.retain(|&n| {
+                let node = &self.nodes[n];
+                let mut all_win = true;
+                let mut any_loss = false;
+                for successor in &self.successors[node.moves.clone()] {
+                    match self.wdl_of(successor.target) {
+                        Some(LOSS) => any_loss = true,
+                        Some(WIN) => {}
+                        _ => all_win = false,
+                    }
+                }
+                let result = if any_loss {
+                    WIN
+                } else if all_win {
+                    LOSS
+                } else {
+                    return true;
+                };
+                solved.push((node.slot, result));
+                false
+            });
+            if solved.is_empty() {
+                break;
+            }
+            for (slot, result) in solved {
+                self.wdl[slot] = Some(result);
+            }
+        }
+        for n in open {
+            self.wdl[self.nodes[n].slot] = Some(DRAW);
+        }
+    }
+
+    /// Distances to zeroing, a ply at a time: a win is `d` plies from
+    /// zeroing if it has a capture, pawn move or mate that wins (for
+    /// `d` of 1) or a move to a loss `d - 1` away; a loss is `d` away
+    /// if its longest resistance is, every move being a zeroing one
+    /// (1) or a move to a win `d - 1` away.
+    fn solve_dtz(&mut self) -> Result<(), String> {
+        let mut open: Vec<usize> = Vec::new();
+        for (n, node) in self.nodes.iter().enumerate() {
+            match self.wdl[node.slot] {
+                _ if self.mated[node.slot] => self.dtz[node.slot] = -1,
+                Some(LOSS | WIN) => open.push(n),
+                _ => {}
+            }
+        }
+        let mut d = 1;
+        while !open.is_empty() {
+            if d > 100 {
+                return Err("positions beyond the fifty-move rule aren't supported".into());
+            }
+            let mut solved = Vec::new();
+            for &n in &open {
+                let node = &self.nodes[n];
+                let successors = &self.successors[node.moves.clone()];
+                let dtz = match self.wdl[node.slot] {
+                    Some(WIN) => {
+                        let wins = successors.iter().any(|s| {
+                            self.wdl_of(s.target) == Some(LOSS)
+                                && match s.target {
+                                    _ if s.zeroing => d == 1,
+                                    Target::Slot(slot) if self.mated[slot] => d == 1,
+                                    Target::Slot(slot) => d > 1 && self.dtz[slot] == -(d - 1),
+                                    Target::Known(_) => false,
+                                }
+                        });
+                        if !wins {
+                            continue;
+                        }
+                        d
+                    }
+                    _ => {
+                        let mut longest = 0;
+                        for s in successors {
+                            longest = longest.max(match s.target {
+                                _ if s.zeroing => 1,
+                                Target::Slot(slot) if self.dtz[slot] > 0 => 1 + self.dtz[slot],
+                                _ => i32::MAX,
+                            });
+                        }
+                        if longest != d {
+                            continue;
+                        }
+                        -d
+                    }
+                };
+                solved.push((n, dtz));
+            }
+            if solved.is_empty() {
+                return Err("DTZ doesn't resolve".into());
+            }
+            // Wins found this ply don't count toward losses this ply:
+            // they'd make them a ply longer.
+            for &(n, dtz) in &solved {
+                self.dtz[self.nodes[n].slot] = dtz;
+            }
+            open.retain(|&n| self.dtz[self.nodes[n].slot] == 0);
+            d += 1;
+        }
+        Ok(())
+    }
+
+    fn summary(&self, signature: &str) -> Summary {
+        let mut summary = Summary {
+            signature: signature.to_string(),
+            ..Summary::default()
+        };
+        for node in &self.nodes {
+            summary.positions += 1;
+            match self.wdl[node.slot] {
+                Some(WIN) => summary.wins += 1,
+                Some(LOSS) => summary.losses += 1,
+                _ => summary.draws += 1,
+            }
+            summary.max_dtz = summary.max_dtz.max(self.dtz[node.slot].abs());
+        }
+        summary
+    }
+
+    /// Values for each (file, side), as [`write_file`] takes them.
+    fn wdl_values(&self) -> Vec<Vec<u16>> {
+        self.values(self.sides, |slot| {
+            self.wdl[slot].map(|wdl| (wdl + 2) as u16)
+        })
+    }
+
+    fn dtz_values(&self) -> Vec<Vec<u16>> {
+        // Only the first side to move, and only wins and losses.
+        self.values(1, |slot| match self.dtz[slot] {
+            0 => None,
+            dtz => Some(dtz.unsigned_abs() as u16 - 1),
+        })
+    }
+
+    fn values(&self, sides: usize, value: impl Fn(usize) -> Option<u16>) -> Vec<Vec<u16>> {
+        let mut tables = Vec::new();
+        for (file, layout) in self.layouts.iter().enumerate() {
+            for side in 0..sides {
+                let start = self.offsets[file * self.sides + side];
+                let stored: Vec<Option<u16>> = (start..start + layout.size() as usize)
+                    .map(|slot| value(slot).filter(|_| self.wdl[slot].is_some()))
+                    .collect();
+                let mut counts = BTreeMap::new();
+                for value in stored.iter().flatten() {
+                    *counts.entry(*value).or_insert(0) += 1;
+                }
+                let commonest = counts
+                    .iter()
+                    .max_by_key(|&(&value, &count)| (count, Reverse(value)))
+                    .map_or(0, |(&value, _)| value);
+                tables.push(stored.into_iter().map(|v| v.unwrap_or(commonest)).collect());
+            }
+        }
+        tables
+    }
+}
+
+/// A board with `placement`'s pieces, no castling and no en passant.
+fn board_of(placement: &Placement) -> Board {
+    let mut fen = String::new();
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+        for file in 0..8 {
+            let sq = rank * 8 + file;
+            match placement.squares[..placement.len]
+                .iter()
+                .position(|&s| s == sq)
+            {
+                Some(i) => {
+                    if empty > 0 {
+                        fen.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    let code = placement.codes[i];
+                    let letter = b"PNBRQK"[(code & 7) as usize - 1] as char;
+                    fen.push(if code & 8 != 0 {
+                        letter.to_ascii_lowercase()
+                    } else {
+                        letter
+                    });
+                }
+                None => empty += 1,
+            }
+        }
+        if empty > 0 {
+            fen.push_str(&empty.to_string());
+        }
+        if rank > 0 {
+            fen.push('/');
+        }
+    }
+    fen.push_str(if placement.black_to_move {
+        " b - - 0 1"
+    } else {
+        " w - - 0 1"
+    });
+    load_fen(&fen).expect("generated FEN parses")
+}
+
+// ---------------------------------------------------------------------
+// Writing
+// ---------------------------------------------------------------------
+
+/// One table's values, Huffman-coded into blocks.
+struct Compressed {
+    flags: u8,
+    single_value: Option<u8>,
+    min_len: u8,
+    max_len: u8,
+    /// For each length from `min_len` up, the first symbol of that
+    /// length; longer codes have lower symbols.
+    lowest_sym: Vec<u16>,
+    /// The value of each symbol.
+    symbols: Vec<u16>,
+    /// For each block, the values in it less one.
+    block_lengths: Vec<u16>,
+    /// Every `SPAN` values, the block holding the middle one and its
+    /// offset there.
+    sparse: Vec<(u32, u16)>,
+    blocks: Vec<[u8; BLOCK_SIZE]>,
+}
+
+/// Huffman code lengths for symbols with `freqs`, none past
+/// [`MAX_CODE_LEN`]: rare symbols are made more common until they fit.
+fn code_lengths(freqs: &[u64]) -> Vec<u8> {
+    let mut freqs = freqs.to_vec();
+    loop {
+        let mut parent = vec![usize::MAX; freqs.len()];
+        let mut heap: BinaryHeap<Reverse<(u64, usize)>> = freqs
+            .iter()
+            .enumerate()
+            .map(|(i, &f)| Reverse((f, i)))
+            .collect();
+        while heap.len() > 1 {
+            let Reverse((fa, a)) = heap.pop().unwrap();
+            let Reverse((fb, b)) = heap.pop().unwrap();
+            let node = parent.len();
+            parent.push(usize::MAX);
+            parent[a] = node;
+            parent[b] = node;
+            heap.push(Reverse((fa + fb, node)));
+        }
+        let lengths: Vec<u8> = (0..freqs.len())
+            .map(|mut i| {
+                let mut len = 0;
+                while parent[i] != usize::MAX {
+                    i = parent[i];
+                    len += 1;
+                }
+                len
+            })
+            .collect();
+        if lengths.iter().all(|&len| len <= MAX_CODE_LEN) {
+            return lengths;
+        }
+        freqs.iter_mut().for_each(|f| *f = *f / 2 + 1);
+    }
+}
+
+fn compress(values: &[u16], flags: u8) -> Result<Compressed, String> {
+    let mut counts = BTreeMap::new();
+    for &value in values {
+        *counts.entry(value).or_insert(0u64) += 1;
+    }
+    let mut compressed = Compressed {
+        flags,
+        single_value: None,
+        min_len: 0,
+        max_len: 0,
+        lowest_sym: Vec::new(),
+        symbols: Vec::new(),
+        block_lengths: Vec::new(),
+        sparse: Vec::new(),
+        blocks: Vec::new(),
+    };
+    if counts.len() == 1 {
+        let value = *counts.keys().next().unwrap();
+        compressed.flags |= SINGLE_VALUE;
+        compressed.single_value =
+            Some(u8::try_from(value).map_err(|_| "single value too large".to_string())?);
+        return Ok(compressed);
+    }
+
+    // Canonical code: symbols numbered longest code first, and the
+    // codes of each length consecutive from the value the reader
+    // derives from `lowest_sym`.
+    let values_by_freq: Vec<(u16, u64)> = counts.into_iter().collect();
+    let lengths = code_lengths(&values_by_freq.iter().map(|&(_, f)| f).collect::<Vec<_>>());
+    let mut by_length: Vec<(u8, u16)> = values_by_freq
+        .iter()
+        .zip(&lengths)
+        .map(|(&(value, _), &len)| (len, value))
+        .collect();
+    by_length.sort_by_key(|&(len, value)| (Reverse(len), value));
+    let min_len = *lengths.iter().min().unwrap();
+    let max_len = *lengths.iter().max().unwrap();
+    compressed.min_len = min_len;
+    compressed.max_len = max_len;
+    compressed.symbols = by_length.iter().map(|&(_, value)| value).collect();
+    compressed.lowest_sym = (min_len..=max_len)
+        .map(|len| by_length.iter().filter(|&&(l, _)| l > len).count() as u16)
+        .collect();
+    let lengths_count = compressed.lowest_sym.len();
+    let mut base = vec![0u64; lengths_count];
+    for i in (0..lengths_count - 1).rev() {
+        base[i] = (base[i + 1] + compressed.lowest_sym[i] as u64
+            - compressed.lowest_sym[i + 1] as u64)
+            / 2;
+    }
+    let mut codes: BTreeMap<u16, (u64, u8)> = BTreeMap::new();
+    for (sym, &(len, value)) in by_length.iter().enumerate() {
+        let i = (len - min_len) as usize;
+        let code = base[i] + sym as u64 - compressed.lowest_sym[i] as u64;
+        debug_assert!(code < 1 << len);
+        codes.insert(value, (code, len));
+    }
+
+    // Blocks: as many whole codes as fit.
+    let mut block = [0u8; BLOCK_SIZE];
+    let mut bits = 0;
+    let mut count = 0u32;
+    let mut starts = vec![0u64];
+    for (i, value) in values.iter().enumerate() {
+        let (code, len) = codes[value];
+        if bits + len as usize > 8 * BLOCK_SIZE {
+            compressed.blocks.push(block);
+            compressed.block_lengths.push((count - 1) as u16);
+            starts.push(i as u64);
+            block = [0; BLOCK_SIZE];
+            bits = 0;
+            count = 0;
+        }
+        for b in (0..len).rev() {
+            if code >> b & 1 != 0 {
+                block[bits / 8] |= 0x80 >> (bits % 8);
+            }
+            bits += 1;
+        }
+        count += 1;
+    }
+    compressed.blocks.push(block);
+    compressed.block_lengths.push((count - 1) as u16);
+
+    let size = values.len() as u64;
+    for k in 0..size.div_ceil(SPAN) {
+        let middle = k * SPAN + SPAN / 2;
+        let block = starts.partition_point(|&start| start <= middle) - 1;
+        let offset = u16::try_from(middle - starts[block])
+            .map_err(|_| "sparse index offset out of range".to_string())?;
+        compressed.sparse.push((block as u32, offset));
+    }
+    Ok(compressed)
+}
+
+/// A whole file: `values[file * sides + side]` are the values of each
+/// table the file holds, indexed as the reader indexes them.
+fn write_file(
+    material: &Material,
+    pieces: &[u8],
+    order: [u8; 2],
+    values: &[Vec<u16>],
+    dtz: bool,
+) -> Result<Vec<u8>, String> {
+    let flags = if dtz { WIN_PLIES | LOSS_PLIES } else { 0 };
+    let tables = values
+        .iter()
+        .map(|values| compress(values, flags))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(if dtz { &DTZ_MAGIC } else { &WDL_MAGIC });
+    let mut header = 0;
+    if material.key != material.key2 {
+        header |= SPLIT;
+    }
+    if material.has_pawns {
+        header |= HAS_PAWNS;
+    }
+    out.push(header);
+    // Both sides share the order and the pieces: low nibble and high.
+    for _ in 0..material.files() {
+        out.push(order[0] | order[0] << 4);
+        if material.pawn_count[1] > 0 {
+            out.push(order[1] | order[1] << 4);
+        }
+        out.extend(pieces.iter().map(|&piece| piece | piece << 4));
+    }
+    align(&mut out, 2);
+
+    for table in &tables {
+        out.push(table.flags);
+        if let Some(value) = table.single_value {
+            out.push(value);
+            continue;
+        }
+        out.push(LOG2_BLOCK_SIZE);
+        out.push(LOG2_SPAN);
+        out.push(0);
+        out.extend_from_slice(&(table.blocks.len() as u32).to_le_bytes());
+        out.push(table.max_len);
+        out.push(table.min_len);
+        for &sym in &table.lowest_sym {
+            out.extend_from_slice(&sym.to_le_bytes());
+        }
+        out.extend_from_slice(&(table.symbols.len() as u16).to_le_bytes());
+        // Every symbol is a single value: no right half.
+        for &value in &table.symbols {
+            out.push(value as u8);
+            out.push((value >> 8) as u8 & 0xF | 0xF0);
+            out.push(0xFF);
+        }
+        if table.symbols.len() & 1 != 0 {
+            out.push(0);
+        }
+    }
+    if dtz {
+        align(&mut out, 2);
+    }
+    for table in &tables {
+        for &(block, offset) in &table.sparse {
+            out.extend_from_slice(&block.to_le_bytes());
+            out.extend_from_slice(&offset.to_le_bytes());
+        }
+    }
+    for table in &tables {
+        for &length in &table.block_lengths {
+            out.extend_from_slice(&length.to_le_bytes());
+        }
+    }
+    for table in &tables {
+        if table.single_value.is_none() {
+            align(&mut out, BLOCK_SIZE);
+            out.extend(table.blocks.iter().flatten());
+        }
+    }
+    Ok(out)
+}
+
+fn align(out: &mut Vec<u8>, to: usize) {
+    out.resize(out.len().next_multiple_of(to), 0);
+}
@@ -0,0 +1,216 @@
+//! Transposition table — a fixed-size cache of search results keyed by
+//! Zobrist hash, shared by every search thread.
+//!
+//! ## Lock-free layout
+//!
+//! Each slot is two `AtomicU64`s: the packed entry data, and the
+//! position key XORed with that data. A reader loads both and accepts
+//! the entry only if `check ^ data` reproduces the key it's probing
+//! for. If two threads race on a slot and the reader sees one thread's
+//! `data` with the other thread's `check`, the XOR won't match and the
+//! entry simply reads as a miss. That's the usual "lockless hashing"
+//! trick: no locks, no `unsafe`, and a torn write costs one probe.
+//!
+//! The search never trusts a table move blindly either: a hash move is
+//! only used if it turns up in the node's own legal move list, so even
+//! a genuine 64-bit key collision can't make the engine play an
+//! illegal move.
+//!
+//! ## Packed entry (`data`)
+//!
+//! ```text
+//!  bits  0..16  move      (src | target << 6 | promotion << 12, 0 = none)
+//!  bits 16..32  score     (i16, node-relative mates via score_to_tt)
+//!  bits 32..40  depth     (u8)
+//!  bits 40..42  bound     (exact / lower / upper)
+//!  bits 42..50  generation (which search stored it)
+//! ```
+
+use crate::types::{Move, PieceType, Square};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+/// Default table size in megabytes (the UCI `Hash` option's default).
+pub const DEFAULT_HASH_MB: usize = 16;
+
+/// Each slot is two `u64`s.
+const SLOT_BYTES: usize = 16;
+
+/// What a stored score says about the node's true value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is the node's exact minimax value (a PV node).
+    Exact,
+    /// The node failed high: its value is at least the score.
+    Lower,
+    /// The node failed low: its value is at most the score.
+    Upper,
+}
+
+/// One decoded table entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TtEntry {
+    pub best_move: Option<Move>,
+    /// Node-relative score; pass through `score_from_tt` before use.
+    pub score: i64,
+    pub depth: i32,
+    pub bound: Bound,
+}
+
+pub struct TranspositionTable {
+    /// `[check, data]` pairs, flattened: slot `i` lives at `2i, 2i+1`.
+    slots: Vec<AtomicU64>,
+    /// Bumped once per search so entries from earlier searches lose
+    /// replacement priority to fresh ones.
+    generation: AtomicU8,
+}
+
+impl TranspositionTable {
+    /// A table using (at most) `megabytes` of memory. Always has at
+    /// least one slot.
+    pub fn new(megabytes: usize) -> Self {
+        let num_slots = (megabytes * 1024 * 1024 / SLOT_BYTES).max(1);
+        Self {
+            slots: (0..num_slots * 2).map(|_| AtomicU64::new(0)).collect(),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    /// Number of entries the table can hold.
+    pub fn capacity(&self) -> usize {
+        self.slots.len() / 2
+    }
+
+    /// Forget everything (UCI `ucinewgame`).
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.store(0, Ordering::Relaxed);
+        }
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    /// Start a new search: entries stored from now on outrank older
+    /// ones when the replacement scheme has to choose.
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Look up `key`. Returns `None` on a miss or a torn slot.
+    pub fn probe(&self, key: u64) -> Option<TtEntry> {
+        let idx = self.index(key);
+        let check = self.slots[idx].load(Ordering::Relaxed);
+        let data = self.slots[idx + 1].load(Ordering::Relaxed);
+        if data == 0 || check ^ data != key {
+            return None;
+        }
+        Some(unpack(data))
+    }
+
+    /// Store a search result for `key`, subject to the replacement
+    /// policy: a slot holding a different position, an entry from an
+    /// older search, or a shallower result is overwritten; a deeper
+    /// entry for the same position from this search is kept unless the
+    /// new one is exact.
+    pub fn store(&self, key: u64, best_move: Option<Move>, score: i64, depth: i32, bound: Bound) {
+        let idx = self.index(key);
+        let generation = self.generation.load(Ordering::Relaxed);
+
+        let old_check = self.slots[idx].load(Ordering::Relaxed);
+        let old_data = self.slots[idx + 1].load(Ordering::Relaxed);
+        if old_data != 0 && old_check ^ old_data == key {
+            let old = unpack(old_data);
+            let old_generation = ((old_data >> 42) & 0xFF) as u8;
+            if old_generation == generation && depth < old.depth && bound != Bound::Exact {
+                return;
+            }
+            // Keep a known-good move if this result didn't find one
+            // (fail-low nodes have no best move).
+            if best_move.is_none() && old.best_move.is_some() {
+                let data = pack(old.best_move, score, depth, bound, generation);
+                self.write(idx, key, data);
+                return;
+            }
+        }
+        let data = pack(best_move, score, depth, bound, generation);
+        self.write(idx, key, data);
+    }
+
+    /// Rough fill level in permille, from a sample of the first slots —
+    /// the UCI `hashfull` figure.
+    pub fn hashfull(&self) -> usize {
+        let sample = self.capacity().min(1000);
+        let used = (0..sample)
+            .filter(|&i| self.slots[2 * i + 1].load(Ordering::Relaxed) != 0)
+            .count();
+        used * 1000 / sample
+    }
+
+    fn write(&self, idx: usize, key: u64, data: u64) {
+        self.slots[idx].store(key ^ data, Ordering::Relaxed);
+        self.slots[idx + 1].store(data, Ordering::Relaxed);
+    }
+
+    /// Slot index (into `slots`, so always even) for `key`. Uses the
+    /// high half of a 64×64 multiply rather than `%`, which maps keys
+    /// evenly onto any table size, power of two or not.
+    #[inline]
+    fn index(&self, key: u64) -> usize {
+        let slot = ((key as u128 * self.capacity() as u128) >> 64) as usize;
+        slot * 2
+    }
+}
+
+fn pack(best_move: Option<Move>, score: i64, depth: i32, bound: Bound, generation: u8) -> u64 {
+    let mv = best_move.map_or(0, encode_move) as u64;
+    let score = score.clamp(i16::MIN as i64, i16::MAX as i64) as i16 as u16 as u64;
+    let depth = depth.clamp(0, u8::MAX as i32) as u64;
+    let bound = match bound {
+        Bound::Exact => 1,
+        Bound::Lower => 2,
+        Bound::Upper => 3,
+    };
+    // `bound` is never 0, so a packed entry is never 0 — which is how
+    // an empty slot is told apart from a stored one.
+    mv | (score << 16) | (depth << 32) | (bound << 40) | ((generation as u64) << 42)
+}
+
+fn unpack(data: u64) -> TtEntry {
+    TtEntry {
+        best_move: decode_move((data & 0xFFFF) as u16),
+        score: ((data >> 16) & 0xFFFF) as u16 as i16 as i64,
+        depth: ((data >> 32) & 0xFF) as i32,
+        bound: match (data >> 40) & 0b11 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            _ => Bound::Upper,
+        },
+    }
+}
+
+fn encode_move(mv: Move) -> u16 {
+    let promotion = match mv.promotion {
+        None => 0,
+        Some(PieceType::Knight) => 1,
+        Some(PieceType::Bishop) => 2,
+        Some(PieceType::Rook) => 3,
+        Some(_) => 4,
+    };
+    mv.src.to_bit_index() as u16 | (mv.target.to_bit_index() as u16) << 6 | promotion << 12
+}
+
+fn decode_move(bits: u16) -> Option<Move> {
+    if bits == 0 {
+        return None;
+    }
+    let promotion = match bits >> 12 {
+        0 => None,
+        1 => Some(PieceType::Knight),
+        2 => Some(PieceType::Bishop),
+        3 => Some(PieceType::Rook),
+        _ => Some(PieceType::Queen),
+    };
+    Some(Move {
+        src: Square::from_bit_index((bits & 0x3F) as u8),
+        target: Square::from_bit_index(((bits >> 6) & 0x3F) as u8),
+        promotion,
+    })
+}
@@ -0,0 +1,368 @@
+//! Texel tuning: fit the evaluation weights to game results.
+//!
+//! Each training position carries the result of the game it came from
+//! (1 for a White win, ½ for a draw, 0 for a loss). The evaluation is
+//! mapped to an expected result with a sigmoid,
+//! `1 / (1 + 10^(-K·eval/400))`, and the weights are chosen to minimise
+//! the mean squared error between expectation and result over all
+//! positions. `K` is fitted first, with the starting weights, so the
+//! tuning only has to explain what the current evaluation gets wrong.
+//!
+//! Evaluating every position under every candidate weight vector would
+//! be far too slow, so the evaluation is linearised once up front. For
+//! every weight, [`TuningPosition::extract`] measures how much the
+//! position's untapered middlegame and endgame totals move per unit of
+//! that weight; from then on the evaluation under any weight vector is
+//! a sparse dot product, and the gradient comes for free. Almost every
+//! term *is* linear in its weights, so the model is exact up to
+//! rounding. The exception is `king_safety.attack_weight`, which picks
+//! the safety table entry rather than scaling anything; it's left out
+//! of the tuning.
+//!
+//! The optimiser is plain gradient descent with Adam step sizes over
+//! continuous weights, rounded to integers at the end. The `tune`
+//! binary drives all of this from the command line.
+
+use crate::board::Board;
+use crate::endgame::{SCALE_DRAW, SCALE_NORMAL};
+use crate::eval_params::EvalParams;
+use crate::evaluation::{Tapered, MAX_PHASE};
+use crate::fen::load_fen;
+use crate::types::Color;
+use serde_json::Value;
+use std::path::Path;
+
+/// How far each weight is moved to measure its coefficient. A multiple
+/// of 4, so terms that halve or quarter a weight (blocked passers, the
+/// endgame share of king danger) still measure exactly.
+const PROBE: i64 = 4;
+
+/// Weights the linear model can't represent; see the module docs.
+const UNTUNABLE: &[&str] = &["king_safety.attack_weight."];
+
+/// One labelled position, reduced to what the linear model needs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningPosition {
+    /// White's game result: 1.0, 0.5 or 0.0.
+    pub result: f64,
+    pub phase: i32,
+    /// Endgame scale factor, fixed at extraction.
+    pub scale: i32,
+    /// Untapered middlegame and endgame totals under the weights the
+    /// coefficients were extracted at.
+    pub base: Tapered,
+    /// `(weight index, d mg / d weight, d eg / d weight)` for every
+    /// weight that affects this position.
+    pub coefficients: Vec<(usize, f64, f64)>,
+}
+
+impl TuningPosition {
+    /// Linearise `board`'s evaluation around `params`. `probes` comes
+    /// from [`probes`]`(params)`.
+    pub fn extract(board: &Board, result: f64, params: &EvalParams, probes: &[EvalParams]) -> Self {
+        let trace = board.evaluate_trace_with(params);
+        let base = trace.total();
+        let coefficients = probes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, probe)| {
+                let delta = board.evaluate_trace_with(probe).total() - base;
+                (delta != Tapered::default()).then(|| {
+                    (
+                        i,
+                        delta.mg as f64 / PROBE as f64,
+                        delta.eg as f64 / PROBE as f64,
+                    )
+                })
+            })
+            .collect();
+        Self {
+            result,
+            phase: board.game_phase(),
+            scale: trace.scale,
+            base,
+            coefficients,
+        }
+    }
+
+    /// Evaluation under `weights`, given the weights it was extracted
+    /// at.
+    pub fn evaluate(&self, weights: &[f64], base_weights: &[f64]) -> f64 {
+        let (mut mg, mut eg) = (self.base.mg as f64, self.base.eg as f64);
+        for &(i, d_mg, d_eg) in &self.coefficients {
+            let change = weights[i] - base_weights[i];
+            mg += d_mg * change;
+            eg += d_eg * change;
+        }
+        self.blend(mg, eg)
+    }
+
+    fn blend(&self, mg: f64, eg: f64) -> f64 {
+        if self.scale == SCALE_DRAW {
+            return 0.0;
+        }
+        let phase = self.phase.clamp(0, MAX_PHASE) as f64;
+        let eg = eg * self.scale as f64 / SCALE_NORMAL as f64;
+        (mg * phase + eg * (MAX_PHASE as f64 - phase)) / MAX_PHASE as f64
+    }
+}
+
+/// Parse one training line: an EPD with a `c9` result opcode
+/// (`<fen> c9 "1-0";`), `<fen>;<result>` / `<fen> [<result>]`, or the
+/// `datagen` text format `<fen> | <score> | <result>`.
+/// Results are `1-0`, `0-1`, `1/2-1/2`, or a number from White's point
+/// of view (`1.0`, `0.5`, `0`). Blank lines and `#` comments give
+/// `None`.
+pub fn parse_training_line(line: &str) -> Option<Result<(Board, f64), String>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (fen, result) = if let Some(idx) = line.find(" c9 ") {
+        let result = line[idx + 4..].trim().trim_end_matches(';').trim();
+        (&line[..idx], result.trim_matches('"'))
+    } else if let Some((fen, rest)) = line.split_once(" | ") {
+        (fen, rest.rsplit('|').next().unwrap_or(rest).trim())
+    } else if let Some((fen, result)) = line.rsplit_once(';') {
+        (fen, result.trim())
+    } else if let Some(idx) = line.rfind(" [") {
+        (&line[..idx], line[idx + 2..].trim_end_matches(']').trim())
+    } else {
+        return Some(Err(format!("no result: {line}")));
+    };
+    let result = match parse_result(result) {
+        Some(result) => result,
+        None => return Some(Err(format!("bad result {result:?}: {line}"))),
+    };
+    // EPD has four FEN fields; load_fen wants the move counters too.
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let fen = match fields.len() {
+        4 => format!("{} 0 1", fields.join(" ")),
+        _ => fields.join(" "),
+    };
+    Some(
+        load_fen(&fen)
+            .map(|board| (board, result))
+            .map_err(|e| format!("{e}: {line}")),
+    )
+}
+
+fn parse_result(text: &str) -> Option<f64> {
+    match text {
+        "1-0" => Some(1.0),
+        "0-1" => Some(0.0),
+        "1/2-1/2" => Some(0.5),
+        _ => text.parse().ok().filter(|r| (0.0..=1.0).contains(r)),
+    }
+}
+
+/// Read a training file, one position per line (see
+/// [`parse_training_line`]). Positions with the side to move in check
+/// aren't quiet, so they're dropped; the second value counts them.
+pub fn load_training_file(path: impl AsRef<Path>) -> Result<(Vec<(Board, f64)>, usize), String> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut positions = Vec::new();
+    let mut skipped = 0;
+    for (number, line) in text.lines().enumerate() {
+        let (board, result) = match parse_training_line(line) {
+            None => continue,
+            Some(parsed) => {
+                parsed.map_err(|e| format!("{}:{}: {e}", path.display(), number + 1))?
+            }
+        };
+        let in_check = match board.side_to_move {
+            Color::White => board.white_king_in_check,
+            Color::Black => board.black_king_in_check,
+        };
+        if in_check {
+            skipped += 1;
+        } else {
+            positions.push((board, result));
+        }
+    }
+    Ok((positions, skipped))
+}
+
+/// Every tunable weight of `params` as `(path, value)`, in a fixed
+/// order: the paths [`EvalParams::set`] takes.
+pub fn flatten(params: &EvalParams) -> Vec<(String, i64)> {
+    fn walk(node: &Value, path: &str, out: &mut Vec<(String, i64)>) {
+        let child = |key: &str| {
+            if path.is_empty() {
+                key.to_string()
+            } else {
+                format!("{path}.{key}")
+            }
+        };
+        match node {
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    walk(value, &child(key), out);
+                }
+            }
+            Value::Array(items) => {
+                for (i, value) in items.iter().enumerate() {
+                    walk(value, &child(&i.to_string()), out);
+                }
+            }
+            Value::Number(n) => {
+                let tunable = !UNTUNABLE.iter().any(|prefix| path.starts_with(prefix));
+                if let (Some(n), true) = (n.as_i64(), tunable) {
+                    out.push((path.to_string(), n));
+                }
+            }
+            _ => {}
+        }
+    }
+    let tree = serde_json::to_value(params).expect("EvalParams serializes to JSON");
+    let mut out = Vec::new();
+    walk(&tree, "", &mut out);
+    out
+}
+
+/// `params` with each weight in `names` replaced by the rounded value
+/// from `weights`.
+pub fn unflatten(params: &EvalParams, names: &[String], weights: &[f64]) -> EvalParams {
+    let mut params = params.clone();
+    for (name, weight) in names.iter().zip(weights) {
+        params
+            .set(name, &(weight.round() as i64).to_string())
+            .expect("paths come from flatten");
+    }
+    params
+}
+
+/// The probe parameter sets for [`TuningPosition::extract`]: one per
+/// weight of `flatten(params)`.
+pub fn probes(params: &EvalParams) -> Vec<EvalParams> {
+    flatten(params)
+        .iter()
+        .map(|(name, value)| {
+            let mut probe = params.clone();
+            probe
+                .set(name, &(value + PROBE).to_string())
+                .expect("paths come from flatten");
+            probe
+        })
+        .collect()
+}
+
+/// Expected score for White at evaluation `eval` (centipawns).
+pub fn sigmoid(eval: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
+
+/// Mean squared error of the positions under `weights`.
+pub fn mean_squared_error(
+    positions: &[TuningPosition],
+    weights: &[f64],
+    base_weights: &[f64],
+    k: f64,
+) -> f64 {
+    let total: f64 = positions
+        .iter()
+        .map(|p| {
+            let error = p.result - sigmoid(p.evaluate(weights, base_weights), k);
+            error * error
+        })
+        .sum();
+    total / positions.len().max(1) as f64
+}
+
+/// The `K` minimising the error with the weights as they are, by
+/// golden-section search over `[0, 10]`.
+pub fn fit_k(positions: &[TuningPosition], weights: &[f64]) -> f64 {
+    let error = |k: f64| mean_squared_error(positions, weights, weights, k);
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut lo, mut hi) = (0.0, 10.0);
+    for _ in 0..100 {
+        let a = hi - ratio * (hi - lo);
+        let b = lo + ratio * (hi - lo);
+        if error(a) < error(b) {
+            hi = b;
+        } else {
+            lo = a;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+/// Gradient of the mean squared error with respect to each weight.
+fn gradient(
+    positions: &[TuningPosition],
+    weights: &[f64],
+    base_weights: &[f64],
+    k: f64,
+) -> Vec<f64> {
+    let mut gradient = vec![0.0; weights.len()];
+    let scale = k * std::f64::consts::LN_10 / 400.0;
+    for p in positions {
+        let s = sigmoid(p.evaluate(weights, base_weights), k);
+        // d error / d eval
+        let factor = -2.0 * (p.result - s) * s * (1.0 - s) * scale;
+        for &(i, d_mg, d_eg) in &p.coefficients {
+            gradient[i] += factor * p.blend(d_mg, d_eg);
+        }
+    }
+    let n = positions.len().max(1) as f64;
+    gradient.iter_mut().for_each(|g| *g /= n);
+    gradient
+}
+
+/// Settings for [`tune`].
+#[derive(Debug, Clone, Copy)]
+pub struct TuneOptions {
+    pub iterations: usize,
+    /// Adam step size, in centipawns.
+    pub learning_rate: f64,
+    /// Report progress every this many iterations (0: never).
+    pub report_every: usize,
+}
+
+impl Default for TuneOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 2000,
+            learning_rate: 1.0,
+            report_every: 100,
+        }
+    }
+}
+
+/// Run Adam gradient descent from `base_weights` and return the
+/// tuned (continuous) weights. `report` gets the iteration number and
+/// current error at each report.
+pub fn tune(
+    positions: &[TuningPosition],
+    base_weights: &[f64],
+    k: f64,
+    options: TuneOptions,
+    mut report: impl FnMut(usize, f64),
+) -> Vec<f64> {
+    const BETA1: f64 = 0.9;
+    const BETA2: f64 = 0.999;
+    const EPSILON: f64 = 1e-8;
+
+    let mut weights = base_weights.to_vec();
+    let mut m = vec![0.0; weights.len()];
+    let mut v = vec![0.0; weights.len()];
+    for iteration in 1..=options.iterations {
+        let g = gradient(positions, &weights, base_weights, k);
+        let t = iteration as i32;
+        for i in 0..weights.len() {
+            m[i] = BETA1 * m[i] + (1.0 - BETA1) * g[i];
+            v[i] = BETA2 * v[i] + (1.0 - BETA2) * g[i] * g[i];
+            let m_hat = m[i] / (1.0 - BETA1.powi(t));
+            let v_hat = v[i] / (1.0 - BETA2.powi(t));
+            weights[i] -= options.learning_rate * m_hat / (v_hat.sqrt() + EPSILON);
+        }
+        if options.report_every > 0 && iteration % options.report_every == 0 {
+            report(
+                iteration,
+                mean_squared_error(positions, &weights, base_weights, k),
+            );
+        }
+    }
+    weights
+}
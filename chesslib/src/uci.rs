@@ -1,8 +1,16 @@
+use crate::bench::run_bench_command;
 use crate::board::Board;
+use crate::eval_params::EvalParams;
 use crate::logger::log_to_file;
-use crate::search::Searcher;
-use crate::types::Color;
-use std::sync::Mutex;
+use crate::perft::format_divide;
+use crate::search::{Score, SearchHandle, SearchLimits, SearchResult, Searcher, MAX_THREADS};
+use crate::strength::{Strength, MAX_ELO, MAX_SKILL_LEVEL, MIN_ELO};
+use crate::syzygy;
+use crate::tt::DEFAULT_HASH_MB;
+use crate::types::{Color, Move};
+use std::io::Write;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
 use std::time::Duration;
 
 use crate::board_utils::get_starting_board;
@@ -19,23 +27,523 @@ lazy_static! {
     /// the reason `Searcher` is stateful (see `chesslib::search` for
     /// the design rationale). Reset on `ucinewgame`.
     static ref SEARCHER: Mutex<Searcher> = Mutex::new(Searcher::new());
+
+    /// Values set through `setoption`. Unlike the searcher these
+    /// survive `ucinewgame` — a GUI sets options once per session.
+    static ref OPTIONS: Mutex<EngineOptions> = Mutex::new(EngineOptions::default());
+
+    /// The search started by 'go ponder' / 'go infinite', if one is
+    /// running. Plain 'go' searches synchronously and never sets this.
+    static ref BACKGROUND: Mutex<Option<BackgroundSearch>> = Mutex::new(None);
+}
+
+/// Upper bound for the `MultiPV` option. Anything past the number of
+/// legal moves just reports every move.
+const MAX_MULTI_PV: usize = 256;
+
+/// Upper bound for the `Hash` option, in MB.
+const MAX_HASH_MB: usize = 4096;
+
+/// Bound for the `Contempt` option, either way, in centipawns.
+const MAX_CONTEMPT: i64 = 100;
+
+/// `UCI_Elo` until the GUI sets one.
+const DEFAULT_ELO: u32 = 1500;
+
+/// The NNUE options, listed only when the feature is built in.
+#[cfg(feature = "nnue")]
+const NNUE_OPTIONS: &str = "option name Use NNUE type check default false\n\
+                            option name EvalFile type string default <empty>\n";
+#[cfg(not(feature = "nnue"))]
+const NNUE_OPTIONS: &str = "";
+
+/// The engine's UCI options (the `option name ...` lines answered to
+/// `uci`), as currently set.
+#[derive(Clone)]
+struct EngineOptions {
+    /// Number of principal variations to search and report.
+    multi_pv: usize,
+    /// Search threads (Lazy SMP), main thread included.
+    threads: usize,
+    /// Transposition table size in MB.
+    hash_mb: usize,
+    /// Draw penalty for the engine's side, in centipawns.
+    contempt: i64,
+    /// Scale contempt with the game phase.
+    dynamic_contempt: bool,
+    /// `UCI_LimitStrength`: play at `UCI_Elo` rather than full strength.
+    limit_strength: bool,
+    /// `UCI_Elo`: target rating when `limit_strength` is on.
+    elo: u32,
+    /// `Skill Level`: used when `limit_strength` is off.
+    skill_level: u8,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self {
+            multi_pv: 1,
+            threads: 1,
+            hash_mb: DEFAULT_HASH_MB,
+            contempt: 0,
+            dynamic_contempt: false,
+            limit_strength: false,
+            elo: DEFAULT_ELO,
+            skill_level: MAX_SKILL_LEVEL,
+        }
+    }
+}
+
+impl EngineOptions {
+    /// A fresh Searcher with the searcher-side options applied.
+    fn new_searcher(&self) -> Searcher {
+        let mut searcher = Searcher::new();
+        searcher.set_threads(self.threads);
+        if self.hash_mb != DEFAULT_HASH_MB {
+            searcher.set_hash_size(self.hash_mb);
+        }
+        self.apply_play_style(&mut searcher);
+        searcher
+    }
+
+    /// Push contempt and strength settings to `searcher`.
+    fn apply_play_style(&self, searcher: &mut Searcher) {
+        searcher.set_contempt(self.contempt);
+        searcher.set_dynamic_contempt(self.dynamic_contempt);
+        searcher.set_strength(self.strength());
+    }
+
+    /// The strength limit these options ask for: `UCI_Elo` if
+    /// `UCI_LimitStrength` is on, else `Skill Level` below the maximum,
+    /// else none.
+    fn strength(&self) -> Option<Strength> {
+        if self.limit_strength {
+            Some(Strength::from_elo(self.elo))
+        } else if self.skill_level < MAX_SKILL_LEVEL {
+            Some(Strength::from_level(self.skill_level))
+        } else {
+            None
+        }
+    }
+}
+
+/// Split `setoption name <id> [value <x>]` into its name and value.
+/// The name may contain spaces (`Skill Level`), so everything between
+/// `name` and `value` belongs to it.
+fn parse_setoption(command: &str) -> Option<(String, Option<String>)> {
+    let rest = command.strip_prefix("setoption")?.trim();
+    let rest = rest.strip_prefix("name")?.trim();
+    match rest.find(" value") {
+        Some(idx) => {
+            let name = rest[..idx].trim().to_string();
+            let value = rest[idx + " value".len()..].trim().to_string();
+            Some((name, Some(value)))
+        }
+        None => Some((rest.to_string(), None)),
+    }
+}
+
+/// Apply one `setoption`. Option names are case-insensitive per the
+/// UCI spec; unknown names and unparsable values are logged and
+/// otherwise ignored.
+///
+/// Options that live on the Searcher are pushed to it after the
+/// `OPTIONS` lock is released: no code path holds both locks at once,
+/// so there's no lock order to get wrong.
+fn set_option(name: &str, value: Option<&str>) {
+    let name = name.to_ascii_lowercase();
+    if matches!(
+        name.as_str(),
+        "contempt" | "dynamic contempt" | "uci_limitstrength" | "uci_elo" | "skill level"
+    ) {
+        set_play_style_option(&name, value);
+        return;
+    }
+    if name == "evalparams" || name.contains('.') {
+        set_eval_option(&name, value);
+        return;
+    }
+    if name == "syzygypath" {
+        set_syzygy_path(value.unwrap_or(""));
+        return;
+    }
+    #[cfg(feature = "nnue")]
+    if matches!(name.as_str(), "use nnue" | "evalfile") {
+        set_nnue_option(&name, value);
+        return;
+    }
+    let number = value.and_then(|v| v.parse::<usize>().ok());
+    if number.is_none() && matches!(name.as_str(), "multipv" | "threads" | "hash") {
+        log_to_file(&format!("Bad {name} value: {value:?}"), true);
+        return;
+    }
+    let n = number.unwrap_or_default();
+
+    let mut options = OPTIONS.lock().unwrap();
+    match name.as_str() {
+        "multipv" => options.multi_pv = n.clamp(1, MAX_MULTI_PV),
+        "threads" => {
+            options.threads = n.clamp(1, MAX_THREADS);
+            let threads = options.threads;
+            drop(options);
+            SEARCHER.lock().unwrap().set_threads(threads);
+        }
+        "hash" => {
+            options.hash_mb = n.clamp(1, MAX_HASH_MB);
+            let hash_mb = options.hash_mb;
+            drop(options);
+            SEARCHER.lock().unwrap().set_hash_size(hash_mb);
+        }
+        _ => log_to_file(&format!("Unknown option: {name}"), true),
+    }
+}
+
+/// Evaluation weights: `EvalParams` loads a TOML or JSON file (an
+/// empty value restores the defaults), and a dotted name such as
+/// `pawns.doubled.mg` sets one weight of the active parameters. Not
+/// Searcher state: they take effect for every search from the next
+/// evaluation on.
+fn set_eval_option(name: &str, value: Option<&str>) {
+    let value = value.unwrap_or("").trim();
+    let params = if name == "evalparams" {
+        if value.is_empty() || value == "<empty>" {
+            Ok(EvalParams::default())
+        } else {
+            EvalParams::load(value)
+        }
+    } else {
+        let mut params = (*EvalParams::active()).clone();
+        params.set(name, value).map(|()| params)
+    };
+    match params {
+        Ok(params) => EvalParams::set_active(params),
+        Err(e) => log_to_file(&format!("Bad evaluation parameter: {e}"), true),
+    }
+}
+
+/// `SyzygyPath`: load the tables in the directories for the search
+/// (see [`crate::tablebase`]), replacing any loaded before. An empty
+/// value unloads them.
+fn set_syzygy_path(path: &str) {
+    match syzygy::init(path) {
+        Ok(tablebases) => {
+            for rejected in &tablebases.rejected {
+                log_to_file(&format!("Skipped Syzygy file {rejected}"), true);
+            }
+            log_to_file(
+                &format!(
+                    "Found {} Syzygy tables (up to {} pieces)",
+                    tablebases.len(),
+                    tablebases.max_pieces()
+                ),
+                true,
+            );
+        }
+        Err(e) => log_to_file(&format!("Bad SyzygyPath: {e}"), true),
+    }
+}
+
+/// The NNUE evaluation: `EvalFile` loads a weight file (an empty value
+/// unloads it) and `Use NNUE` switches between it and the classical
+/// evaluation. Like the evaluation weights, not Searcher state.
+#[cfg(feature = "nnue")]
+fn set_nnue_option(name: &str, value: Option<&str>) {
+    use crate::nnue::{self, Evaluator, Network};
+    let value = value.unwrap_or("").trim();
+    match name {
+        "use nnue" => match value.to_ascii_lowercase().as_str() {
+            "true" => nnue::set_evaluator(Evaluator::Nnue),
+            "false" => nnue::set_evaluator(Evaluator::Classical),
+            _ => log_to_file(&format!("Bad Use NNUE value: {value:?}"), true),
+        },
+        _ if value.is_empty() || value == "<empty>" => nnue::set_network(None),
+        _ => match Network::load(value) {
+            Ok(network) => nnue::set_network(Some(network)),
+            Err(e) => log_to_file(&format!("Bad EvalFile: {e}"), true),
+        },
+    }
+}
+
+/// Options that shape how the engine plays rather than how it
+/// searches: contempt and strength limiting. Their values don't fit
+/// `set_option`'s unsigned parse (a signed spin, two checks), and they
+/// all reach the Searcher through `apply_play_style`.
+fn set_play_style_option(name: &str, value: Option<&str>) {
+    let mut options = OPTIONS.lock().unwrap();
+    let value_str = value.map(|v| v.to_ascii_lowercase());
+    let parsed = match (name, value_str.as_deref()) {
+        ("contempt", Some(v)) => v
+            .parse::<i64>()
+            .map(|cp| options.contempt = cp.clamp(-MAX_CONTEMPT, MAX_CONTEMPT))
+            .is_ok(),
+        ("dynamic contempt", Some(v)) => v
+            .parse::<bool>()
+            .map(|on| options.dynamic_contempt = on)
+            .is_ok(),
+        ("uci_limitstrength", Some(v)) => v
+            .parse::<bool>()
+            .map(|on| options.limit_strength = on)
+            .is_ok(),
+        ("uci_elo", Some(v)) => v
+            .parse::<u32>()
+            .map(|elo| options.elo = elo.clamp(MIN_ELO, MAX_ELO))
+            .is_ok(),
+        ("skill level", Some(v)) => v
+            .parse::<u8>()
+            .map(|level| options.skill_level = level.min(MAX_SKILL_LEVEL))
+            .is_ok(),
+        _ => false,
+    };
+    if !parsed {
+        log_to_file(&format!("Bad {name} value: {value:?}"), true);
+        return;
+    }
+    let style = options.clone();
+    drop(options);
+    style.apply_play_style(&mut SEARCHER.lock().unwrap());
+}
+
+/// The parameters of a `go` command this engine understands.
+#[derive(Default)]
+struct GoParams {
+    wtime: Option<u32>,
+    btime: Option<u32>,
+    movetime: Option<u32>,
+    depth: Option<i32>,
+    nodes: Option<u64>,
+    ponder: bool,
+    infinite: bool,
+}
+
+impl GoParams {
+    fn parse(command: &str) -> Self {
+        let mut go = GoParams::default();
+        let params: Vec<&str> = command.split_whitespace().collect();
+        for i in 0..params.len() {
+            match params[i] {
+                "wtime" => go.wtime = params.get(i + 1).and_then(|v| v.parse().ok()),
+                "btime" => go.btime = params.get(i + 1).and_then(|v| v.parse().ok()),
+                "movetime" => go.movetime = params.get(i + 1).and_then(|v| v.parse().ok()),
+                "depth" => go.depth = params.get(i + 1).and_then(|v| v.parse().ok()),
+                "nodes" => go.nodes = params.get(i + 1).and_then(|v| v.parse().ok()),
+                "ponder" => go.ponder = true,
+                "infinite" => go.infinite = true,
+                _ => {}
+            }
+        }
+        go
+    }
+
+    /// Time to spend on this move: movetime, or 1/30th of our remaining
+    /// clock, default 1s if no info.
+    fn time_budget(&self, board: &Board) -> Duration {
+        let budget_ms: u64 = self.movetime.map(|t| t as u64).unwrap_or_else(|| {
+            let our_ms = match board.side_to_move {
+                Color::White => self.wtime,
+                Color::Black => self.btime,
+            };
+            our_ms.map(|t| (t as u64 / 30).max(50)).unwrap_or(1000)
+        });
+        Duration::from_millis(budget_ms)
+    }
+
+    /// 'go depth N' and 'go nodes N' are exact (no clock). Ponder and
+    /// infinite searches start without a clock too; ponder gets one on
+    /// 'ponderhit'.
+    fn limits(&self, board: &Board) -> SearchLimits {
+        let untimed = self.depth.is_some() || self.nodes.is_some() || self.ponder || self.infinite;
+        SearchLimits {
+            depth: self.depth,
+            time: (!untimed).then(|| self.time_budget(board)),
+            nodes: self.nodes,
+        }
+    }
+}
+
+/// A `go ponder` / `go infinite` search running on its own thread.
+struct BackgroundSearch {
+    thread: JoinHandle<Option<String>>,
+    /// Stops the search, or gives it a clock on `ponderhit`.
+    handle: SearchHandle,
+    control: Arc<(Mutex<ReportGate>, Condvar)>,
+    /// The time budget the `go ponder` command's clock implies, which
+    /// starts counting at `ponderhit`.
+    ponderhit_budget: Duration,
+}
+
+/// Decides who reports a background search's `bestmove`, and when.
+/// UCI forbids sending it while pondering or in infinite mode, even if
+/// the search has run out of depth, so the finished thread waits here
+/// until `ponderhit` or `stop`. Both sides decide under the same lock,
+/// so `bestmove` goes out exactly once.
+struct ReportGate {
+    /// Still pondering / infinite: the result must be held back.
+    holding: bool,
+    /// `stop` is waiting to take the result and report it itself.
+    stop_requested: bool,
+}
+
+/// Run `go ponder` / `go infinite` in the background. The thread owns
+/// the SEARCHER lock until the search is over.
+fn start_background_search(mut board: Board, go: &GoParams, multi_pv: usize) {
+    let handle = SEARCHER.lock().unwrap().handle();
+    handle.reset();
+    let control = Arc::new((
+        Mutex::new(ReportGate {
+            holding: true,
+            stop_requested: false,
+        }),
+        Condvar::new(),
+    ));
+    let limits = go.limits(&board);
+    let ponderhit_budget = go.time_budget(&board);
+
+    let thread_control = Arc::clone(&control);
+    let thread = std::thread::spawn(move || {
+        let result = SEARCHER
+            .lock()
+            .unwrap()
+            .search(&mut board, limits, multi_pv);
+        let response = report(&board, &result, multi_pv);
+
+        let (lock, cvar) = &*thread_control;
+        let mut gate = lock.lock().unwrap();
+        while gate.holding && !gate.stop_requested {
+            gate = cvar.wait(gate).unwrap();
+        }
+        if gate.stop_requested {
+            return Some(response);
+        }
+        log_to_file(&format!("Responded: {response}"), true);
+        println!("{response}");
+        let _ = std::io::stdout().flush();
+        None
+    });
+
+    *BACKGROUND.lock().unwrap() = Some(BackgroundSearch {
+        thread,
+        handle,
+        control,
+        ponderhit_budget,
+    });
+}
+
+/// Stop the background search, if any, and wait for it. Returns `None`
+/// if there was none, `Some(Some(bestmove))` if its result hadn't been
+/// reported yet (the caller now owns it), and `Some(None)` if the
+/// search had already reported it.
+fn finish_background_search() -> Option<Option<String>> {
+    let background = BACKGROUND.lock().unwrap().take()?;
+    {
+        let (lock, cvar) = &*background.control;
+        lock.lock().unwrap().stop_requested = true;
+        cvar.notify_all();
+    }
+    background.handle.stop();
+    Some(background.thread.join().unwrap_or(None))
+}
+
+/// Stop the background search, if any, ahead of a command that needs
+/// the board or the searcher, and hand back the `bestmove` it was still
+/// holding: the caller sends it first, as `stop` does, so every `go` is
+/// answered exactly once however its search ends.
+fn preempt_background_search() -> Option<String> {
+    finish_background_search().flatten()
+}
+
+/// `response` with a pre-empted search's `bestmove` in front of it.
+fn after_held_bestmove(held: Option<String>, response: String) -> String {
+    match held {
+        Some(bestmove) if response.is_empty() => bestmove,
+        Some(bestmove) => format!("{bestmove}\n{response}"),
+        None => response,
+    }
+}
+
+/// Print the `info` line(s) for a finished search and build its
+/// `bestmove` response.
+///
+/// UCI: 'score' is from the engine's perspective (side to move),
+/// positive = engine is winning. The searcher returns White's POV, so
+/// flip when Black is to move, then let `Score` pick between 'cp N'
+/// and 'mate N'.
+fn report(board: &Board, result: &SearchResult, multi_pv: usize) -> String {
+    let pov = |score: i64| {
+        if board.side_to_move == Color::Black {
+            -score
+        } else {
+            score
+        }
+    };
+    let depth = result.depth;
+    if multi_pv > 1 {
+        // MultiPV: one 'info multipv k' line per reported move, best
+        // first; the best line's move is the one we play.
+        for (k, (_mv, score, pv)) in result.lines.iter().enumerate() {
+            println!(
+                "info multipv {} depth {depth} score {} pv {}",
+                k + 1,
+                Score::from_raw(pov(*score)),
+                pv_to_string(pv)
+            );
+        }
+    } else if !result.lines.is_empty() {
+        println!(
+            "info depth {depth} score {} pv {}",
+            Score::from_raw(pov(result.score)),
+            pv_to_string(result.pv())
+        );
+    }
+    match (result.best_move(), result.ponder_move()) {
+        (Some(mv), Some(ponder)) => format!("bestmove {mv} ponder {ponder}"),
+        (Some(mv), None) => format!("bestmove {mv}"),
+        (None, _) => "bestmove 0000".to_string(),
+    }
+}
+
+/// Format a principal variation as UCI's space-separated move list.
+fn pv_to_string(pv: &[Move]) -> String {
+    pv.iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 pub fn handle_uci_command(input: &str) -> String {
     match input.trim() {
-        "uci" => "id name ChessEngine\nid author YourName\nuciok".to_string(),
+        "uci" => format!(
+            "id name ChessEngine\nid author YourName\n\
+             option name MultiPV type spin default 1 min 1 max {MAX_MULTI_PV}\n\
+             option name Threads type spin default 1 min 1 max {MAX_THREADS}\n\
+             option name Hash type spin default {DEFAULT_HASH_MB} min 1 max {MAX_HASH_MB}\n\
+             option name Contempt type spin default 0 min -{MAX_CONTEMPT} max {MAX_CONTEMPT}\n\
+             option name Dynamic Contempt type check default false\n\
+             option name UCI_LimitStrength type check default false\n\
+             option name UCI_Elo type spin default {DEFAULT_ELO} min {MIN_ELO} max {MAX_ELO}\n\
+             option name Skill Level type spin default {MAX_SKILL_LEVEL} min 0 max {MAX_SKILL_LEVEL}\n\
+             option name EvalParams type string default <empty>\n\
+             option name SyzygyPath type string default <empty>\n\
+             {NNUE_OPTIONS}\
+             uciok"
+        ),
         "isready" => "readyok".to_string(),
-        "quit" => "".to_string(),
+        "quit" => {
+            finish_background_search();
+            "".to_string()
+        }
         "ucinewgame" => {
+            let held = preempt_background_search();
             let mut board_state = BOARD_STATE.lock().unwrap();
             *board_state = Some(get_starting_board()); // Reset the board state
                                                        // Fresh game → fresh searcher. Drops killer/history from
                                                        // the previous game so stale move-ordering signals don't
                                                        // contaminate the new one.
-            *SEARCHER.lock().unwrap() = Searcher::new();
-            "".to_string()
+            let searcher = OPTIONS.lock().unwrap().new_searcher();
+            *SEARCHER.lock().unwrap() = searcher;
+            after_held_bestmove(held, String::new())
         }
         command if command.starts_with("position") => {
+            let held = preempt_background_search();
             let mut board_state = BOARD_STATE
                 .lock()
                 .unwrap_or_else(|poisoned| poisoned.into_inner());
@@ -111,66 +619,104 @@ pub fn handle_uci_command(input: &str) -> String {
                     }
                 }
             }
-            "position set".to_string()
+            after_held_bestmove(held, "position set".to_string())
         }
         command if command.starts_with("go") => {
-            let mut board_state = BOARD_STATE.lock().unwrap();
-            if let Some(board) = board_state.as_mut() {
-                let mut wtime: Option<u32> = None;
-                let mut btime: Option<u32> = None;
-                let mut movetime: Option<u32> = None;
-                let mut fixed_depth: Option<i32> = None;
-
-                let params: Vec<&str> = command.split_whitespace().collect();
-                for i in 0..params.len() {
-                    match params[i] {
-                        "wtime" => wtime = params.get(i + 1).and_then(|v| v.parse().ok()),
-                        "btime" => btime = params.get(i + 1).and_then(|v| v.parse().ok()),
-                        "movetime" => movetime = params.get(i + 1).and_then(|v| v.parse().ok()),
-                        "depth" => fixed_depth = params.get(i + 1).and_then(|v| v.parse().ok()),
-                        _ => {}
-                    }
-                }
+            // A search still holding its bestmove answers now, before
+            // this one's info lines start.
+            if let Some(bestmove) = preempt_background_search() {
+                log_to_file(&format!("Responded: {bestmove}"), true);
+                println!("{bestmove}");
+                let _ = std::io::stdout().flush();
+            }
+            let board_state = BOARD_STATE.lock().unwrap();
+            let Some(board) = board_state.as_ref() else {
+                return "bestmove e2e4".to_string(); // Default move if no position is set
+            };
+            let mut board = board.clone();
+            drop(board_state);
 
-                // 'go depth N' is exact. Otherwise use a time budget from movetime
-                // or 1/30th of our remaining clock, default 1s if no info.
-                let mut searcher = SEARCHER.lock().unwrap();
-                let result = if let Some(d) = fixed_depth {
-                    let (mv, score) = searcher.find_best_move(board, d);
-                    mv.map(|m| (m.to_string(), score, d))
-                } else {
-                    let budget_ms: u64 = movetime.map(|t| t as u64).unwrap_or_else(|| {
-                        let our_ms = match board.side_to_move {
-                            Color::White => wtime,
-                            Color::Black => btime,
-                        };
-                        our_ms.map(|t| (t as u64 / 30).max(50)).unwrap_or(1000)
-                    });
-                    let (mv, score, depth) =
-                        searcher.find_best_move_within(board, Duration::from_millis(budget_ms));
-                    mv.map(|m| (m.to_string(), score, depth))
+            // Engine extension: 'go perft N' prints a divide (nodes per
+            // root move) instead of searching, using the Threads option.
+            if let Some(depth) = command.strip_prefix("go perft") {
+                let Ok(depth) = depth.trim().parse() else {
+                    return "Usage: go perft <depth>".to_string();
                 };
+                let threads = OPTIONS.lock().unwrap().threads;
+                return format_divide(&board.perft_divide_parallel(depth, threads));
+            }
 
-                match result {
-                    Some((mv, score, depth)) => {
-                        // UCI: 'score cp' is from the engine's perspective (side to
-                        // move), positive = engine is winning. Our find_best_move
-                        // returns it in White's POV, so flip when Black is to move.
-                        let cp = if board.side_to_move == Color::Black {
-                            -score
-                        } else {
-                            score
-                        };
-                        println!("info depth {depth} score cp {cp} pv {mv}");
-                        format!("bestmove {mv}")
-                    }
-                    None => "bestmove 0000".to_string(),
+            let go = GoParams::parse(command);
+            let multi_pv = OPTIONS.lock().unwrap().multi_pv;
+
+            // 'go ponder' and 'go infinite' run on their own thread so
+            // the UCI loop can keep reading 'ponderhit' / 'stop'.
+            if go.ponder || go.infinite {
+                start_background_search(board, &go, multi_pv);
+                return "".to_string();
+            }
+
+            let limits = go.limits(&board);
+            let mut searcher = SEARCHER.lock().unwrap();
+            searcher.handle().reset();
+            let result = searcher.search(&mut board, limits, multi_pv);
+            report(&board, &result, multi_pv)
+        }
+        "ponderhit" => {
+            // The opponent played the move we pondered on: keep
+            // searching, now against the clock from the 'go ponder'.
+            if let Some(background) = BACKGROUND.lock().unwrap().as_ref() {
+                background
+                    .handle
+                    .set_time_budget(background.ponderhit_budget);
+                let (lock, cvar) = &*background.control;
+                lock.lock().unwrap().holding = false;
+                cvar.notify_all();
+            }
+            "".to_string()
+        }
+        command if command.starts_with("setoption") => {
+            // Searcher options need the SEARCHER lock a background
+            // search is holding.
+            let held = preempt_background_search();
+            match parse_setoption(command) {
+                Some((name, value)) => set_option(&name, value.as_deref()),
+                None => log_to_file(&format!("Malformed setoption: {command}"), true),
+            }
+            after_held_bestmove(held, String::new())
+        }
+        command if command == "bench" || command.starts_with("bench ") => {
+            // Engine extension, not UCI: the fixed-position node-count
+            // benchmark. Runs on its own Searcher, so the session's
+            // table and history are untouched.
+            let held = preempt_background_search();
+            let report = run_bench_command(command.split_whitespace().skip(1)).to_string();
+            after_held_bestmove(held, report)
+        }
+        "eval" => {
+            // Engine extension, not UCI: the static evaluation of the
+            // current position, term by term.
+            let board_state = BOARD_STATE.lock().unwrap();
+            let board = board_state.clone().unwrap_or_else(get_starting_board);
+            #[cfg(feature = "nnue")]
+            if crate::nnue::evaluator() == crate::nnue::Evaluator::Nnue {
+                if let Some(network) = crate::nnue::network() {
+                    return format!(
+                        "{}\nNNUE evaluation: {} cp (White's point of view)",
+                        board.evaluate_trace(),
+                        network.evaluate(&board)
+                    );
                 }
-            } else {
-                "bestmove e2e4".to_string() // Default move if no position is set
             }
+            board.evaluate_trace().to_string()
         }
-        "stop" => "calculation stopped".to_string(),
+        "stop" => match finish_background_search() {
+            // The bestmove the background search was holding back.
+            Some(Some(response)) => response,
+            // It had already reported (search ended after ponderhit).
+            Some(None) => "".to_string(),
+            None => "calculation stopped".to_string(),
+        },
         _ => "Unknown command".to_string(),
     }
 }
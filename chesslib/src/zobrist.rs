@@ -0,0 +1,163 @@
+//! Zobrist hashing — a 64-bit key per position, for the transposition
+//! table (and anything else that needs to recognise a position it has
+//! seen before).
+//!
+//! The key is the XOR of one random number per (piece, square) pair on
+//! the board, plus one per castling right, one for the en-passant file
+//! and one for "black to move". XOR is its own inverse, so
+//! `apply_move` keeps the key up to date by toggling just the handful
+//! of terms a move changes instead of rehashing the whole board; see
+//! [`Board::compute_hash`] for the from-scratch version every
+//! incremental update must agree with.
+//!
+//! The random numbers come from a fixed-seed splitmix64 generator run
+//! at compile time, so keys are identical across builds and runs —
+//! a hash printed in a log today means the same position tomorrow.
+
+use crate::board::Board;
+use crate::move_generation::{b_pawn_attacks, w_pawn_attacks};
+use crate::types::{Color, Piece};
+
+/// All the random numbers a position key is built from.
+struct ZobristKeys {
+    /// Indexed by `[Piece as usize][square]`.
+    pieces: [[u64; 64]; 12],
+    /// White kingside, white queenside, black kingside, black queenside.
+    castling: [u64; 4],
+    /// Indexed by the en-passant square's file.
+    en_passant: [u64; 8],
+    /// Toggled in when Black is to move.
+    black_to_move: u64,
+}
+
+/// One step of splitmix64. Good enough statistical quality for hash
+/// keys and simple enough to run in a `const fn`.
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+const fn generate_keys() -> ZobristKeys {
+    let mut keys = ZobristKeys {
+        pieces: [[0; 64]; 12],
+        castling: [0; 4],
+        en_passant: [0; 8],
+        black_to_move: 0,
+    };
+    let mut state = 0x0C0F_FEE0_u64;
+    let mut piece = 0;
+    while piece < 12 {
+        let mut sq = 0;
+        while sq < 64 {
+            let (next, key) = splitmix64(state);
+            state = next;
+            keys.pieces[piece][sq] = key;
+            sq += 1;
+        }
+        piece += 1;
+    }
+    let mut i = 0;
+    while i < 4 {
+        let (next, key) = splitmix64(state);
+        state = next;
+        keys.castling[i] = key;
+        i += 1;
+    }
+    let mut file = 0;
+    while file < 8 {
+        let (next, key) = splitmix64(state);
+        state = next;
+        keys.en_passant[file] = key;
+        file += 1;
+    }
+    keys.black_to_move = splitmix64(state).1;
+    keys
+}
+
+static KEYS: ZobristKeys = generate_keys();
+
+/// The key term for `piece` standing on `square`.
+#[inline]
+pub(crate) fn piece_key(piece: Piece, square: u8) -> u64 {
+    KEYS.pieces[piece as usize][square as usize]
+}
+
+/// XOR of the piece keys for `piece` on every square set in `bits`.
+#[inline]
+pub(crate) fn piece_keys(piece: Piece, mut bits: u64) -> u64 {
+    let mut key = 0;
+    while bits != 0 {
+        key ^= piece_key(piece, bits.trailing_zeros() as u8);
+        bits &= bits - 1;
+    }
+    key
+}
+
+impl Board {
+    /// Hash the position from scratch. `self.hash` must always equal
+    /// this; `apply_move` / `undo_last_move` maintain it incrementally.
+    ///
+    /// Anything that builds or rewrites a board outside those two
+    /// (FEN loading, hand-built test positions) sets
+    /// `board.hash = board.compute_hash()` when it's done.
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = self.state_hash();
+        for (sq, piece) in self.piece_map.iter().enumerate() {
+            if let Some(piece) = piece {
+                hash ^= piece_key(*piece, sq as u8);
+            }
+        }
+        hash
+    }
+
+    /// Hash the pawns alone from scratch: the key the pawn-structure
+    /// cache is indexed by. `self.pawn_hash` must always equal this.
+    pub fn compute_pawn_hash(&self) -> u64 {
+        piece_keys(Piece::WhitePawn, self.white_pawns())
+            ^ piece_keys(Piece::BlackPawn, self.black_pawns())
+    }
+
+    /// The non-piece part of the key: castling rights, en-passant file
+    /// and side to move. `apply_move` XORs out the pre-move value and
+    /// XORs in the post-move one, which is cheaper than tracking each
+    /// right individually.
+    ///
+    /// The en-passant file only counts when the side to move actually
+    /// has a pawn that can capture there. Otherwise the position after
+    /// 1.e4 would hash differently from the same position reached
+    /// without a double push, and the transposition table (and later
+    /// repetition detection) would treat them as different positions.
+    pub(crate) fn state_hash(&self) -> u64 {
+        let mut hash = 0;
+        let rights = [
+            self.white_kingside_castle_rights,
+            self.white_queenside_castle_rights,
+            self.black_kingside_castle_rights,
+            self.black_queenside_castle_rights,
+        ];
+        for (i, &right) in rights.iter().enumerate() {
+            if right {
+                hash ^= KEYS.castling[i];
+            }
+        }
+        if let Some(ep) = self.en_passant_target {
+            // Squares from which a pawn of the side to move attacks
+            // the en-passant square.
+            let ep_bit = ep.to_bitboard();
+            let capturers = match self.side_to_move {
+                Color::White => b_pawn_attacks(ep_bit) & self.white_pawns(),
+                Color::Black => w_pawn_attacks(ep_bit) & self.black_pawns(),
+            };
+            if capturers != 0 {
+                hash ^= KEYS.en_passant[(ep.to_bit_index() % 8) as usize];
+            }
+        }
+        if self.side_to_move == Color::Black {
+            hash ^= KEYS.black_to_move;
+        }
+        hash
+    }
+}
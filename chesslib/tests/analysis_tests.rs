@@ -0,0 +1,93 @@
+use chesslib::board::Board;
+use chesslib::fen::load_fen;
+use chesslib::search::{SearchLimits, Searcher, MATE_SCORE};
+use chesslib::types::Move;
+
+fn depth(depth: i32) -> SearchLimits {
+    SearchLimits {
+        depth: Some(depth),
+        ..SearchLimits::default()
+    }
+}
+
+fn legal_moves(board: &mut Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    board.get_all_raw_moves_append(&mut moves);
+    moves
+}
+
+#[test]
+fn test_analyse_root_covers_every_legal_move_once() {
+    let mut board =
+        load_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+    let infos = Searcher::new_deterministic().analyse_root(&mut board, depth(3));
+    let mut analysed: Vec<Move> = infos.iter().map(|info| info.mv).collect();
+    let mut legal = legal_moves(&mut board);
+    analysed.sort_by_key(|mv| mv.to_string());
+    legal.sort_by_key(|mv| mv.to_string());
+    assert_eq!(analysed, legal);
+}
+
+#[test]
+fn test_analyse_root_sorts_best_first_for_black() {
+    // Black to move, so White-POV scores must be non-decreasing.
+    let mut board =
+        load_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2").unwrap();
+    let infos = Searcher::new_deterministic().analyse_root(&mut board, depth(3));
+    for pair in infos.windows(2) {
+        assert!(pair[0].score <= pair[1].score);
+    }
+}
+
+#[test]
+fn test_analyse_root_scores_are_exact_not_bounds() {
+    // Qa8# mates and Qf7 stalemates. A root alpha-beta search would
+    // only prove the stalemate no better than the mate, but analysis
+    // reports each move's true value.
+    let mut board = load_fen("7k/8/6K1/8/8/8/Q7/8 w - - 0 1").unwrap();
+    let infos = Searcher::new_deterministic().analyse_root(&mut board, depth(3));
+    assert_eq!(infos[0].mv.to_string(), "a2a8");
+    assert_eq!(infos[0].score, MATE_SCORE - 1);
+    let stalemate = infos
+        .iter()
+        .find(|info| info.mv.to_string() == "a2f7")
+        .unwrap();
+    assert_eq!(stalemate.score, 0);
+}
+
+#[test]
+fn test_analyse_root_reports_depth_nodes_and_playable_pvs() {
+    let mut board = Board::new();
+    let infos = Searcher::new_deterministic().analyse_root(&mut board, depth(3));
+    assert_eq!(infos.len(), 20);
+    for info in &infos {
+        assert_eq!(info.depth, 3);
+        assert!(info.nodes > 0);
+        assert_eq!(info.pv.first(), Some(&info.mv));
+        let mut copy = board.clone();
+        for step in &info.pv {
+            assert!(copy.is_legal_move(step), "illegal pv move {step}");
+            copy.apply_move(step);
+        }
+    }
+}
+
+#[test]
+fn test_analyse_root_stopped_early_still_lists_every_move() {
+    let mut board = Board::new();
+    let limits = SearchLimits {
+        nodes: Some(1),
+        ..SearchLimits::default()
+    };
+    let infos = Searcher::new_deterministic().analyse_root(&mut board, limits);
+    assert_eq!(infos.len(), 20);
+    assert!(infos.iter().all(|info| info.depth == 0));
+}
+
+#[test]
+fn test_analyse_root_of_checkmate_is_empty() {
+    let mut board =
+        load_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+    let infos = Searcher::new_deterministic().analyse_root(&mut board, depth(2));
+    assert!(infos.is_empty());
+}
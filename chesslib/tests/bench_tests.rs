@@ -0,0 +1,46 @@
+use chesslib::bench::{run_bench, run_bench_command, BENCH_POSITIONS};
+use chesslib::fen::load_fen;
+use chesslib::tt::DEFAULT_HASH_MB;
+use chesslib::types::Color;
+
+#[test]
+fn test_bench_positions_are_legal() {
+    assert!(BENCH_POSITIONS.len() >= 50);
+    for fen in BENCH_POSITIONS {
+        let board = load_fen(fen).unwrap_or_else(|e| panic!("{fen}: {e:?}"));
+        // The side that just moved can't be left in check.
+        let opponent_in_check = match board.side_to_move {
+            Color::White => board.black_king_in_check,
+            Color::Black => board.white_king_in_check,
+        };
+        assert!(!opponent_in_check, "{fen}");
+    }
+}
+
+#[test]
+fn test_bench_node_count_is_reproducible() {
+    let first = run_bench(1, 1, 1);
+    let second = run_bench(1, 1, 1);
+    assert_eq!(first.position_nodes, second.position_nodes);
+    assert_eq!(first.nodes, second.nodes);
+}
+
+#[test]
+fn test_bench_totals_add_up() {
+    let result = run_bench(1, 1, 1);
+    assert_eq!(result.position_nodes.len(), BENCH_POSITIONS.len());
+    assert_eq!(result.nodes, result.position_nodes.iter().sum::<u64>());
+    assert!(result.nodes > 0);
+    // The stalemate has nothing to search beyond its root node.
+    assert!(result.position_nodes.iter().any(|&nodes| nodes <= 1));
+}
+
+#[test]
+fn test_bench_command_parses_arguments() {
+    let parsed = run_bench_command("1 1 1".split_whitespace());
+    assert_eq!(parsed.nodes, run_bench(1, 1, 1).nodes);
+    // Malformed arguments fall back to the defaults rather than
+    // failing.
+    let fallback = run_bench_command(["1", "x"]);
+    assert_eq!(fallback.nodes, run_bench(1, 1, DEFAULT_HASH_MB).nodes);
+}
@@ -17,22 +17,22 @@ pub fn test_bug_position2() {
     let mut board =
         load_fen("rn2k2r/ppp2ppp/4bn2/q1b1N3/8/2NB4/PPPP1PPP/R1BQR1K1 b kq - 0 1").unwrap();
     let score = board.evaluate();
-    assert_eq!(score, 135);
+    assert_eq!(score, 280); // Re1 bears down the open e-file at the uncastled king
     let board_after_e8f8 =
         load_fen("rn3k1r/ppp2ppp/4bn2/q1b1N3/8/2NB4/PPPP1PPP/R1BQR1K1 w - - 0 1").unwrap();
     let score_after_e8f8 = board_after_e8f8.evaluate();
-    assert_eq!(score_after_e8f8, 180); // how is this better for black?
+    assert_eq!(score_after_e8f8, 310); // off the open file, but castling rights are gone and the h8 rook is shut in
 
     let board_after_e8g8 =
         load_fen("rn3rk1/ppp2ppp/4bn2/q1b1N3/8/2NB4/PPPP1PPP/R1BQR1K1 w - - 0 1").unwrap();
     let score_after_e8g8 = board_after_e8g8.evaluate();
-    assert_eq!(score_after_e8g8, 100); // this is even better for black so why is this not the best move?
+    assert_eq!(score_after_e8g8, 156); // behind an intact f7-g7-h7 shield: best for black
 
     // Moving the queen in line with bishop, attacking the king
     let board_after_a5b6 =
         load_fen("rn2k2r/ppp2pp1/1q2bn1p/2b1N3/8/2NB4/PPPP1PPP/R1BQR1K1 w kq - 0 1").unwrap();
     let score_after_a5b6 = board_after_a5b6.evaluate();
-    assert_eq!(score_after_a5b6, 130);
+    assert_eq!(score_after_a5b6, 267); // h6 is defended by g7 (a connected pawn), but the king still sits on the open e-file
 
     // The engine's preferred move from this position has shifted with every
     // eval / search change (originally e8g8, then a5b6 with quiescence, then
@@ -0,0 +1,166 @@
+use chesslib::datagen::{
+    generate, play_game, read_binary, DatagenConfig, Format, GameResult, Sample, RECORD_BYTES,
+};
+use chesslib::fen::load_fen;
+use chesslib::tuner::parse_training_line;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn sample(fen: &str, score: i16, result: GameResult) -> Sample {
+    Sample {
+        fen: fen.to_string(),
+        score,
+        result,
+    }
+}
+
+/// Small and quick: a couple of hundred nodes a move, short games.
+fn quick_config() -> DatagenConfig {
+    DatagenConfig {
+        nodes: 200,
+        random_plies: 4,
+        max_plies: 30,
+        ..DatagenConfig::default()
+    }
+}
+
+#[test]
+fn test_binary_record_round_trip() {
+    for sample in [
+        sample(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            17,
+            GameResult::Draw,
+        ),
+        sample(
+            "rnbqkbnr/pp1ppppp/8/2pP4/8/8/PPP1PPPP/RNBQKBNR w KQkq c6 0 3",
+            -250,
+            GameResult::BlackWin,
+        ),
+        sample(
+            "r3k2r/8/8/8/8/8/8/4K2R b Kq - 12 40",
+            i16::MIN,
+            GameResult::WhiteWin,
+        ),
+        sample("8/8/8/4k3/8/8/8/4K3 w - - 99 300", 0, GameResult::Draw),
+    ] {
+        let bytes = sample.to_bytes().unwrap();
+        assert_eq!(bytes.len(), RECORD_BYTES);
+        assert_eq!(Sample::from_bytes(&bytes).unwrap(), sample);
+    }
+    assert!(sample("not a fen", 0, GameResult::Draw).to_bytes().is_err());
+
+    let mut bad_result = sample("8/8/8/4k3/8/8/8/4K3 w - - 0 1", 0, GameResult::Draw)
+        .to_bytes()
+        .unwrap();
+    bad_result[26] = 3;
+    assert!(Sample::from_bytes(&bad_result).is_err());
+    let mut bad_piece = bad_result;
+    bad_piece[26] = 1;
+    bad_piece[8] = 0xFF;
+    assert!(Sample::from_bytes(&bad_piece).is_err());
+}
+
+#[test]
+fn test_read_binary_reads_every_record() {
+    let samples = [
+        sample(
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            30,
+            GameResult::WhiteWin,
+        ),
+        sample("8/8/8/4k3/4P3/4K3/8/8 b - - 0 1", 90, GameResult::Draw),
+    ];
+    let mut bytes = Vec::new();
+    for sample in &samples {
+        Format::Binary.write(&mut bytes, sample).unwrap();
+    }
+    assert_eq!(bytes.len(), 2 * RECORD_BYTES);
+    let read: Vec<Sample> = read_binary(bytes.as_slice())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(read, samples);
+    // A torn last record is dropped, not misread.
+    assert_eq!(read_binary(&bytes[..RECORD_BYTES + 5]).count(), 1);
+}
+
+#[test]
+fn test_text_format_feeds_the_tuner() {
+    let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+    let mut text = Vec::new();
+    Format::Text
+        .write(&mut text, &sample(fen, -35, GameResult::BlackWin))
+        .unwrap();
+    let line = String::from_utf8(text).unwrap();
+    assert_eq!(line, format!("{fen} | -35 | 0.0\n"));
+    let (board, result) = parse_training_line(&line).unwrap().unwrap();
+    assert_eq!(board.to_fen(), fen);
+    assert_eq!(result, 0.0);
+}
+
+#[test]
+fn test_self_play_keeps_quiet_positions_labelled_with_the_result() {
+    let config = quick_config();
+    let game = play_game(&mut StdRng::seed_from_u64(1), &config);
+    assert!(game.plies <= config.max_plies);
+    assert!(!game.samples.is_empty());
+    for sample in &game.samples {
+        assert_eq!(sample.result, game.result);
+        let board = load_fen(&sample.fen).unwrap();
+        assert!(
+            !board.white_king_in_check && !board.black_king_in_check,
+            "{}",
+            sample.fen
+        );
+    }
+
+    // The same seed replays the same game.
+    let replay = play_game(&mut StdRng::seed_from_u64(1), &config);
+    assert_eq!(replay, game);
+}
+
+#[test]
+fn test_self_play_adjudicates_a_won_position() {
+    // White has a queen and a rook more: adjudicated long before mate
+    // or the ply limit.
+    let config = DatagenConfig {
+        book: vec!["4k3/8/8/8/8/8/8/QR2K3 w - - 0 1".to_string()],
+        random_plies: 0,
+        max_opening_score: 10_000,
+        max_plies: 100,
+        ..quick_config()
+    };
+    let game = play_game(&mut StdRng::seed_from_u64(2), &config);
+    assert_eq!(game.result, GameResult::WhiteWin);
+    assert!(game.plies < config.win_plies + 2, "{} plies", game.plies);
+}
+
+#[test]
+fn test_generate_on_several_threads() {
+    let config = quick_config();
+    let mut out = Vec::new();
+    let mut reports = 0;
+    let summary = generate(3, 2, &config, Format::Binary, &mut out, |_| reports += 1).unwrap();
+    assert_eq!(summary.games, 3);
+    assert_eq!(reports, 3);
+    assert_eq!(
+        summary.white_wins + summary.draws + summary.black_wins,
+        summary.games
+    );
+    assert_eq!(out.len(), summary.positions * RECORD_BYTES);
+    assert_eq!(read_binary(out.as_slice()).count(), summary.positions);
+    assert!(read_binary(out.as_slice()).all(|sample| sample.is_ok()));
+}
+
+#[test]
+fn test_generate_output_does_not_depend_on_threads() {
+    let config = quick_config();
+    let run = |threads| {
+        let mut out = Vec::new();
+        generate(4, threads, &config, Format::Text, &mut out, |_| {}).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+    let one = run(1);
+    assert!(!one.is_empty());
+    assert_eq!(run(2), one);
+}
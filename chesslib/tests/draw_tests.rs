@@ -0,0 +1,131 @@
+//! Draw detection on the board (repetition, fifty-move rule,
+//! insufficient material) and how the search scores draws under
+//! contempt.
+
+use chesslib::board::Board;
+use chesslib::fen::load_fen;
+use chesslib::search::{Searcher, MATE_SCORE};
+
+#[test]
+fn test_insufficient_material() {
+    let drawn = [
+        "8/8/4k3/8/8/3K4/8/8 w - - 0 1",    // K v K
+        "8/8/4k3/8/8/3K4/5N2/8 w - - 0 1",  // KN v K
+        "8/8/4k3/8/8/3KB3/8/8 w - - 0 1",   // KB v K
+        "8/8/1b2k3/8/8/3KB3/8/8 w - - 0 1", // KB v KB, both on dark squares
+    ];
+    for fen in drawn {
+        assert!(load_fen(fen).unwrap().is_insufficient_material(), "{fen}");
+    }
+    let playable = [
+        "8/8/4k3/8/8/3K4/4P3/8 w - - 0 1",  // a pawn can promote
+        "8/8/4k3/8/8/3KR3/8/8 w - - 0 1",   // KR v K
+        "8/8/4k3/8/8/3KNN2/8/8 w - - 0 1",  // KNN v K: mate exists
+        "8/8/4k3/8/2b5/3KB3/8/8 w - - 0 1", // opposite-coloured bishops
+        "8/8/4k3/4n3/8/3KB3/8/8 w - - 0 1", // KB v KN
+    ];
+    for fen in playable {
+        assert!(!load_fen(fen).unwrap().is_insufficient_material(), "{fen}");
+    }
+}
+
+#[test]
+fn test_repetition_detected_with_same_side_to_move() {
+    let mut board = Board::new();
+    board.apply_moves_from_strings(["g1f3", "g8f6", "f3g1"].map(String::from).into_iter());
+    assert!(!board.is_repetition());
+    board.apply_move_from_string("f6g8");
+    // Back to the starting position, White to move.
+    assert!(board.is_repetition());
+    board.undo_last_move();
+    assert!(!board.is_repetition());
+}
+
+#[test]
+fn test_irreversible_move_ends_repetition_window() {
+    let mut board = Board::new();
+    board.apply_moves_from_strings(
+        ["g1f3", "g8f6", "f3g1", "f6g8", "e2e4"]
+            .map(String::from)
+            .into_iter(),
+    );
+    // The pawn move reset the clock, so the earlier positions can't
+    // come back.
+    assert!(!board.is_repetition());
+}
+
+#[test]
+fn test_fifty_move_rule() {
+    assert!(!load_fen("7k/8/8/8/8/8/R7/K7 w - - 99 80")
+        .unwrap()
+        .is_fifty_move_draw());
+    assert!(load_fen("7k/8/8/8/8/8/R7/K7 w - - 100 80")
+        .unwrap()
+        .is_fifty_move_draw());
+}
+
+#[test]
+fn test_fifty_move_rule_draws_a_won_ending() {
+    // KR v K, but every move White makes hits the hundredth ply
+    // without a capture or pawn move.
+    let mut board = load_fen("7k/8/8/8/8/8/R7/K7 w - - 99 80").unwrap();
+    let (_, score) = Searcher::new_deterministic().find_best_move(&mut board, 2);
+    assert_eq!(score, 0);
+}
+
+#[test]
+fn test_mate_on_the_hundredth_ply_stands() {
+    // Ra8# lands on the hundredth ply: the mate counts, not the draw.
+    let mut board = load_fen("6k1/8/6K1/8/8/8/8/R7 w - - 99 80").unwrap();
+    let (best, score) = Searcher::new_deterministic().find_best_move(&mut board, 4);
+    assert_eq!(best.unwrap().to_string(), "a1a8");
+    assert_eq!(score, MATE_SCORE - 1);
+}
+
+#[test]
+fn test_contempt_counts_against_the_root_side() {
+    // White's only move, Kxb2, leaves bare kings.
+    let mut board = load_fen("k7/8/8/8/8/8/1q6/K7 w - - 0 1").unwrap();
+    let mut searcher = Searcher::new_deterministic();
+    assert_eq!(searcher.find_best_move(&mut board, 2).1, 0);
+
+    searcher.set_contempt(30);
+    assert_eq!(searcher.contempt(), 30);
+    assert_eq!(searcher.find_best_move(&mut board, 2).1, -30);
+
+    // Same thing with Black at the root: the draw is bad for Black,
+    // so good for White.
+    let mut board = load_fen("K7/8/8/8/8/8/1Q6/k7 b - - 0 1").unwrap();
+    assert_eq!(searcher.find_best_move(&mut board, 2).1, 30);
+}
+
+#[test]
+fn test_dynamic_contempt_scales_with_phase() {
+    // One queen on the board: phase 4 of 24.
+    let mut board = load_fen("k7/8/8/8/8/8/1q6/K7 w - - 0 1").unwrap();
+    let mut searcher = Searcher::new_deterministic();
+    searcher.set_contempt(48);
+    searcher.set_dynamic_contempt(true);
+    assert_eq!(searcher.find_best_move(&mut board, 2).1, -8);
+}
+
+#[test]
+fn test_repetition_scored_as_draw() {
+    // After 1.Nf3 Nf6 2.Ng1 Ng8 3.Nf3 Nf6, 4.Ng1 repeats the position
+    // after 2.Ng1. With contempt the engine sees that draw as a loss
+    // of 40 centipawns.
+    let mut board = Board::new();
+    board.apply_moves_from_strings(
+        ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6"]
+            .map(String::from)
+            .into_iter(),
+    );
+    let mut searcher = Searcher::new_deterministic();
+    searcher.set_contempt(40);
+    let lines = searcher.find_best_moves_multipv(&mut board, 1, 64);
+    let (_, score, _) = lines
+        .iter()
+        .find(|(mv, _, _)| mv.to_string() == "f3g1")
+        .expect("Ng1 is legal");
+    assert_eq!(*score, -40);
+}
@@ -0,0 +1,150 @@
+use chesslib::board::Board;
+use chesslib::endgame::{
+    self, Endgame, MaterialKey, SCALE_DRAW, SCALE_NORMAL, SCALE_OPPOSITE_BISHOPS,
+    SCALE_PAWNLESS_MINOR,
+};
+use chesslib::eval_trace::EvalTerm;
+use chesslib::fen::load_fen;
+use chesslib::types::Color;
+
+fn eval(fen: &str) -> i64 {
+    load_fen(fen).unwrap().evaluate()
+}
+
+/// `fen` seen from the other side: board flipped, colours swapped.
+fn mirror(fen: &str) -> String {
+    let parts: Vec<&str> = fen.split_whitespace().collect();
+    let swap_case = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                }
+            })
+            .collect()
+    };
+    let placement: Vec<String> = parts[0].split('/').rev().map(swap_case).collect();
+    let side = if parts[1] == "w" { "b" } else { "w" };
+    format!("{} {side} - - 0 1", placement.join("/"))
+}
+
+#[test]
+fn test_material_key_signatures() {
+    let key = MaterialKey::of(&Board::new());
+    assert_eq!(key.to_string(), "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP");
+    assert_eq!(key.flipped(), key);
+
+    let kbnk = MaterialKey::parse("KBNvK").unwrap();
+    assert_eq!(kbnk.to_string(), "KBNvK");
+    assert_eq!(kbnk.flipped().to_string(), "KvKBN");
+    assert_eq!(
+        MaterialKey::of(&load_fen("8/8/8/8/8/2k5/8/1n2K1b1 w - - 0 1").unwrap()),
+        kbnk.flipped()
+    );
+    assert!(kbnk.is_lone_king(Color::Black));
+    assert!(!kbnk.is_lone_king(Color::White));
+    assert_eq!(kbnk.non_pawn_material(Color::White), 600);
+
+    assert!(MaterialKey::parse("KBNK").is_err());
+    assert!(MaterialKey::parse("BNvK").is_err());
+    assert!(MaterialKey::parse("KXvK").is_err());
+}
+
+#[test]
+fn test_lookup_by_material() {
+    let lookup = |signature: &str| endgame::lookup(MaterialKey::parse(signature).unwrap());
+    assert_eq!(lookup("KQvK"), Some((Endgame::Kxk, Color::White)));
+    assert_eq!(lookup("KvKR"), Some((Endgame::Kxk, Color::Black)));
+    // Not in the table, but still a lone king against a rook.
+    assert_eq!(lookup("KQRPvK"), Some((Endgame::Kxk, Color::White)));
+    assert_eq!(lookup("KBNvK"), Some((Endgame::Kbnk, Color::White)));
+    assert_eq!(lookup("KvKNN"), Some((Endgame::Drawn, Color::Black)));
+    assert_eq!(lookup("KvKP"), Some((Endgame::Kpk, Color::Black)));
+    assert_eq!(lookup("KPvKP"), None);
+    assert_eq!(lookup("KRvKR"), None);
+}
+
+#[test]
+fn test_drawn_material_scores_zero() {
+    for fen in [
+        "4k3/8/8/8/8/8/8/1N2K3 w - - 0 1",   // KNK
+        "4k3/8/8/8/8/8/8/2B1K3 b - - 0 1",   // KBK
+        "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1",  // KNNK
+        "2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1", // same-coloured bishops
+        "k7/8/8/8/P7/8/8/2B1K3 w - - 0 1",   // a-pawn, dark bishop, king on a8
+        "4k3/p7/8/8/8/8/8/1N2K3 w - - 0 1",  // a knight can't win
+    ] {
+        let board = load_fen(fen).unwrap();
+        let trace = board.evaluate_trace();
+        assert_eq!(trace.scale, SCALE_DRAW, "{fen}");
+        assert_eq!(board.evaluate(), 0, "{fen}");
+        assert_eq!(eval(&mirror(fen)), 0, "{fen}");
+    }
+    // KNNK can be mated with help, so it isn't a draw by rule.
+    assert!(!load_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1")
+        .unwrap()
+        .is_insufficient_material());
+
+    // The right bishop, or the king too far from the corner, wins.
+    assert!(eval("k7/8/8/8/P7/8/8/3BK3 w - - 0 1") > 300);
+    assert!(eval("8/8/8/8/P7/8/8/2B1K2k w - - 0 1") > 300);
+}
+
+#[test]
+fn test_mating_net_drives_the_king_to_the_edge() {
+    // KRK and KQK: the lone king in the corner scores better for the
+    // stronger side than the same king in the centre.
+    for (edge, centre) in [
+        (
+            "7k/8/8/8/8/4K3/8/R7 b - - 0 1",
+            "8/8/8/3k4/8/4K3/8/R7 b - - 0 1",
+        ),
+        (
+            "7k/8/8/8/8/4K3/8/Q7 b - - 0 1",
+            "8/8/8/3k4/8/4K3/8/Q7 b - - 0 1",
+        ),
+    ] {
+        assert!(eval(edge) > eval(centre) + 80, "{edge}");
+        assert_eq!(eval(&mirror(edge)), -eval(edge));
+        let trace = load_fen(edge).unwrap().evaluate_trace();
+        assert!(trace.side(EvalTerm::Endgame, Color::White).eg > 0);
+        assert_eq!(trace.scale, SCALE_NORMAL);
+    }
+}
+
+#[test]
+fn test_bishop_and_knight_mate_needs_the_bishops_corner() {
+    // A dark-squared bishop mates on a1 or h8, not on a8 or h1.
+    let right = "8/8/8/8/3K4/8/8/k1B1N3 b - - 0 1";
+    let wrong = "k7/8/8/8/3K4/8/8/2B1N3 b - - 0 1";
+    assert!(eval(right) > eval(wrong) + 200);
+    assert_eq!(eval(&mirror(right)), -eval(right));
+    // With a light-squared bishop it's the other way round.
+    let light_right = "k7/8/8/8/3K4/8/8/3BN3 b - - 0 1";
+    let light_wrong = "8/8/8/8/3K4/8/8/k2BN3 b - - 0 1";
+    assert!(eval(light_right) > eval(light_wrong) + 200);
+}
+
+#[test]
+fn test_drawish_endgames_are_scaled() {
+    // Opposite-coloured bishops, a pawn apart.
+    let ocb = load_fen("4k3/5p2/8/8/3b4/8/4PPB1/4K3 w - - 0 1").unwrap();
+    let trace = ocb.evaluate_trace();
+    assert_eq!(trace.scale, SCALE_OPPOSITE_BISHOPS);
+    assert!(trace.score.abs() < trace.total().taper(trace.phase).abs());
+    assert!(trace.to_string().contains(&format!(
+        "Endgame scale: {SCALE_OPPOSITE_BISHOPS}/{SCALE_NORMAL}"
+    )));
+    // Same-coloured bishops aren't.
+    let same = load_fen("4k3/5p2/8/8/4b3/8/4PPB1/4K3 w - - 0 1").unwrap();
+    assert_eq!(same.evaluate_trace().scale, SCALE_NORMAL);
+
+    // Rook against bishop, no pawns.
+    let krkb = load_fen("1b2k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let trace = krkb.evaluate_trace();
+    assert_eq!(trace.scale, SCALE_PAWNLESS_MINOR);
+    assert!(trace.score > 0 && trace.score < 100, "{}", trace.score);
+    assert_eq!(eval(&mirror(&krkb.to_fen())), -trace.score);
+}
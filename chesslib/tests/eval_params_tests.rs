@@ -0,0 +1,116 @@
+use chesslib::bench::BENCH_POSITIONS;
+use chesslib::board::Board;
+use chesslib::eval_params::EvalParams;
+use chesslib::evaluation::Tapered;
+use chesslib::fen::load_fen;
+use chesslib::types::KNIGHT_VALUE;
+
+const DATA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+
+#[test]
+fn test_default_params_reproduce_evaluate() {
+    // The only test in this file that touches the active parameters.
+    let defaults = EvalParams::default();
+    assert_eq!(*EvalParams::active(), defaults);
+    for fen in BENCH_POSITIONS {
+        let board = load_fen(fen).unwrap();
+        assert_eq!(board.evaluate_with(&defaults), board.evaluate(), "{fen}");
+    }
+
+    // Activating new parameters reaches evaluate(), pawn table included.
+    let doubled = load_fen("4k3/8/8/8/8/4P3/4P3/4K3 w - - 0 1").unwrap();
+    let before = doubled.evaluate();
+    let mut params = defaults.clone();
+    params.set("pawns.doubled.eg", "-200").unwrap();
+    params.set("pawns.doubled.mg", "-200").unwrap();
+    EvalParams::set_active(params.clone());
+    assert_eq!(doubled.evaluate(), doubled.evaluate_with(&params));
+    assert!(doubled.evaluate() < before - 100);
+
+    EvalParams::set_active(defaults);
+    assert_eq!(doubled.evaluate(), before);
+}
+
+#[test]
+fn test_evaluate_with_uses_the_given_params() {
+    // A pawn beside the knight: a lone knight is a dead draw and
+    // scores zero whatever it's worth.
+    let board = load_fen("4k3/8/8/8/8/8/4P3/3NK3 w - - 0 1").unwrap();
+    let defaults = EvalParams::default();
+    let mut heavier = defaults.clone();
+    heavier.material.knight += 100;
+    assert_eq!(
+        board.evaluate_with(&heavier),
+        board.evaluate_with(&defaults) + 100
+    );
+    let trace = board.evaluate_trace_with(&heavier);
+    assert_eq!(trace.score, board.evaluate_with(&heavier));
+}
+
+#[test]
+fn test_toml_and_json_round_trip() {
+    let mut params = EvalParams::default();
+    params.pieces.trapped_rook = Tapered::new(-60, -10);
+    params.king_safety.safety_table[10] = 19;
+    assert_eq!(EvalParams::from_toml(&params.to_toml()).unwrap(), params);
+    assert_eq!(EvalParams::from_json(&params.to_json()).unwrap(), params);
+}
+
+#[test]
+fn test_save_and_load() {
+    let dir = std::env::temp_dir().join(format!("eval_params_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut params = EvalParams::default();
+    params.positional.mobility = 6;
+    for name in ["params.toml", "params.json"] {
+        let path = dir.join(name);
+        params.save(&path).unwrap();
+        assert_eq!(EvalParams::load(&path).unwrap(), params, "{name}");
+    }
+    let json = std::fs::read_to_string(dir.join("params.json")).unwrap();
+    assert!(json.trim_start().starts_with('{'));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_partial_file_keeps_defaults() {
+    let params = EvalParams::load(format!("{DATA}/eval_params.toml")).unwrap();
+    let defaults = EvalParams::default();
+    assert_eq!(params.material.knight, 340);
+    assert_eq!(params.pawns.doubled, Tapered::new(-15, -30));
+    assert_eq!(params.king_safety.shield, [0, 35, 15, 5]);
+    assert_eq!(params.material.bishop, defaults.material.bishop);
+    assert_eq!(params.pawns.isolated, defaults.pawns.isolated);
+    assert_eq!(params.pieces, defaults.pieces);
+}
+
+#[test]
+fn test_bad_input_is_rejected() {
+    assert!(EvalParams::from_toml("[material]\nknigt = 300\n").is_err());
+    assert!(EvalParams::from_json("{\"material\": {\"knight\": \"many\"}}").is_err());
+    assert!(EvalParams::load(format!("{DATA}/no_such_file.toml")).is_err());
+}
+
+#[test]
+fn test_set_by_path() {
+    let mut params = EvalParams::default();
+    params.set("material.knight", "333").unwrap();
+    params.set("pawns.passed.6.eg", "170").unwrap();
+    params.set("king_safety.safety_table.63", "450").unwrap();
+    assert_eq!(params.material.knight, 333);
+    assert_eq!(params.pawns.passed[6], Tapered::new(80, 170));
+    assert_eq!(params.king_safety.safety_table[63], 450);
+
+    assert!(params.set("material.kinght", "1").is_err());
+    assert!(params.set("pawns.passed.8.mg", "1").is_err());
+    assert!(params.set("pawns.doubled", "1").is_err(), "not a leaf");
+    assert!(params.set("material.knight", "lots").is_err());
+    assert_eq!(params.material.knight, 333);
+    assert_ne!(KNIGHT_VALUE, 333);
+}
+
+#[test]
+fn test_starting_position_stays_balanced_under_any_params() {
+    let params = EvalParams::load(format!("{DATA}/eval_params.toml")).unwrap();
+    assert_eq!(Board::new().evaluate_with(&params), 0);
+}
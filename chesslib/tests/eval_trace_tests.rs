@@ -0,0 +1,69 @@
+use chesslib::bench::BENCH_POSITIONS;
+use chesslib::board::Board;
+use chesslib::eval_trace::EvalTerm;
+use chesslib::evaluation::{Tapered, MAX_PHASE};
+use chesslib::fen::load_fen;
+use chesslib::types::{Color, PAWN_VALUE, ROOK_VALUE};
+
+#[test]
+fn test_trace_score_matches_evaluate() {
+    for fen in BENCH_POSITIONS {
+        let board = load_fen(fen).unwrap();
+        let trace = board.evaluate_trace();
+        assert_eq!(trace.score, board.evaluate(), "{fen}");
+        let blended: i64 = EvalTerm::ALL.iter().map(|&t| trace.blended(t)).sum();
+        assert_eq!(blended, trace.score, "{fen}");
+        assert_eq!(trace.phase, board.game_phase(), "{fen}");
+    }
+}
+
+#[test]
+fn test_starting_position_trace_is_symmetric() {
+    let trace = Board::new().evaluate_trace();
+    assert_eq!(trace.phase, MAX_PHASE);
+    for term in EvalTerm::ALL {
+        assert_eq!(
+            trace.side(term, Color::White),
+            trace.side(term, Color::Black),
+            "{}",
+            term.name()
+        );
+    }
+    assert_eq!(trace.total(), Tapered::default());
+}
+
+#[test]
+fn test_trace_attributes_terms_to_the_right_side() {
+    // White has a rook and two pawns against a bare king, and gives check.
+    let board = load_fen("4k3/8/8/8/8/8/5PP1/4RK2 b - - 0 1").unwrap();
+    let trace = board.evaluate_trace();
+    assert_eq!(
+        trace.net(EvalTerm::Material),
+        Tapered::new(2 * PAWN_VALUE + ROOK_VALUE, 2 * PAWN_VALUE + ROOK_VALUE)
+    );
+    assert!(trace.side(EvalTerm::Check, Color::White).mg > 0);
+    assert_eq!(
+        trace.side(EvalTerm::Check, Color::Black),
+        Tapered::default()
+    );
+    assert_eq!(
+        trace.side(EvalTerm::Material, Color::Black),
+        Tapered::default()
+    );
+}
+
+#[test]
+fn test_trace_display_lists_every_term() {
+    let board =
+        load_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+    let trace = board.evaluate_trace();
+    let table = trace.to_string();
+    for term in EvalTerm::ALL {
+        assert!(table.contains(term.name()), "{table}");
+    }
+    assert!(table.contains(&format!("Phase: {}/{MAX_PHASE}", trace.phase)));
+    assert!(table.ends_with(&format!(
+        "Final evaluation: {} cp (White's point of view)",
+        trace.score
+    )));
+}
@@ -0,0 +1,89 @@
+//! Singular extensions and multi-cut only kick in at depth, so these
+//! searches go deeper than most of the suite. The positions are small
+//! enough to keep that cheap.
+
+use chesslib::fen::load_fen;
+use chesslib::search::{SearchFeatures, SearchLimits, SearchResult, Searcher, MATE_SCORE};
+
+/// The a-pawn is one step from queening, and the knight is too far
+/// away to help: the white king has to stay in reach of b2.
+const PAWN_RACE: &str = "8/8/8/8/8/p1K5/8/6Nk w - - 0 1";
+
+fn pawn_race_search(features: SearchFeatures) -> (SearchResult, u64) {
+    let mut board = load_fen(PAWN_RACE).unwrap();
+    let mut searcher = Searcher::new_deterministic();
+    searcher.set_features(features);
+    let limits = SearchLimits {
+        depth: Some(8),
+        ..SearchLimits::default()
+    };
+    let result = searcher.search(&mut board, limits, 1);
+    let nodes = searcher.nodes();
+    (result, nodes)
+}
+
+#[test]
+fn test_only_moves_to_hold_are_found_at_depth() {
+    // Kc2, Kb3 or any knight move answers ...a2 with Kb2 and wins the
+    // pawn for a dead draw. Every other king move loses to ...a2 and
+    // ...a1=Q, so the search has to see past a promotion deep in every
+    // other line.
+    let mut board = load_fen(PAWN_RACE).unwrap();
+    let (best, score) = Searcher::new_deterministic().find_best_move(&mut board, 8);
+    let best = best.unwrap().to_string();
+    assert!(
+        !["c3b4", "c3c4", "c3d2", "c3d3", "c3d4"].contains(&best.as_str()),
+        "unexpected {best}"
+    );
+    assert_eq!(score, 0);
+}
+
+#[test]
+fn test_singular_extensions_change_the_search() {
+    let (with, with_nodes) = pawn_race_search(SearchFeatures::default());
+    let (without, without_nodes) = pawn_race_search(SearchFeatures {
+        singular_extensions: false,
+        ..SearchFeatures::default()
+    });
+    // Both hold the draw; the extended lines cost nodes.
+    assert_eq!(with.score, 0);
+    assert_eq!(without.score, 0);
+    assert!(
+        with_nodes > without_nodes,
+        "{with_nodes} with, {without_nodes} without"
+    );
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_singular_extensions_and_multi_cut_fire() {
+    let (on, _) = pawn_race_search(SearchFeatures::default());
+    assert!(on.stats.singular_extensions > 0);
+    assert!(on.stats.multi_cuts > 0);
+
+    let (off, _) = pawn_race_search(SearchFeatures {
+        singular_extensions: false,
+        ..SearchFeatures::default()
+    });
+    assert_eq!(off.stats.singular_extensions, 0);
+    assert_eq!(off.stats.multi_cuts, 0);
+}
+
+#[test]
+fn test_mate_still_found_with_extensions_active() {
+    // KQ vs K: deep enough that nodes test their hash moves for
+    // singularity, and the forced mate must survive it.
+    let mut board = load_fen("7k/8/5K2/8/8/8/8/1Q6 w - - 0 1").unwrap();
+    let (_, score) = Searcher::new_deterministic().find_best_move(&mut board, 7);
+    assert!(score >= MATE_SCORE - 7, "score {score}");
+}
+
+#[test]
+fn test_deep_search_is_deterministic() {
+    let fen = "8/8/8/4k3/4P3/4K3/8/8 w - - 0 1";
+    let mut a = load_fen(fen).unwrap();
+    let mut b = load_fen(fen).unwrap();
+    let first = Searcher::new_deterministic().find_best_move(&mut a, 10);
+    let second = Searcher::new_deterministic().find_best_move(&mut b, 10);
+    assert_eq!(first, second);
+}
@@ -0,0 +1,85 @@
+use chesslib::fen::load_fen;
+use chesslib::types::Color;
+
+/// Middlegame safety of White's king alone.
+fn white_safety(fen: &str) -> i64 {
+    load_fen(fen)
+        .unwrap_or_else(|e| panic!("{fen}: {e}"))
+        .king_safety(Color::White)
+        .mg
+}
+
+#[test]
+fn test_starting_position_is_balanced() {
+    let board = load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    assert_eq!(board.evaluate_king_safety(), Default::default());
+    assert_eq!(
+        board.king_safety(Color::White),
+        board.king_safety(Color::Black)
+    );
+}
+
+#[test]
+fn test_pawn_shield() {
+    let intact = white_safety("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1");
+    let advanced = white_safety("6k1/5ppp/8/8/8/6P1/5P1P/6K1 w - - 0 1");
+    let far = white_safety("6k1/5ppp/8/8/6P1/8/5P1P/6K1 w - - 0 1");
+    assert!(intact > advanced, "{intact} vs {advanced}");
+    assert!(advanced > far, "{advanced} vs {far}");
+}
+
+#[test]
+fn test_open_file_is_worse_than_semi_open() {
+    let semi_open = white_safety("6k1/5ppp/8/8/8/8/5P1P/6K1 w - - 0 1");
+    let open = white_safety("6k1/5p1p/8/8/8/8/5P1P/6K1 w - - 0 1");
+    assert!(open < semi_open, "{open} vs {semi_open}");
+}
+
+#[test]
+fn test_pawn_storm() {
+    let distant = white_safety("6k1/5p1p/6p1/8/8/8/5PPP/6K1 w - - 0 1");
+    let close = white_safety("6k1/5p1p/8/8/6p1/8/5PPP/6K1 w - - 0 1");
+    assert!(close < distant, "{close} vs {distant}");
+
+    // The same g4 stormer hurts less once a pawn on g3 blocks it.
+    let free_cost = white_safety("6k1/5p1p/8/8/8/8/5PPP/6K1 w - - 0 1") - close;
+    let blocked_cost = white_safety("6k1/5p1p/8/8/8/6P1/5P1P/6K1 w - - 0 1")
+        - white_safety("6k1/5p1p/8/8/6p1/6P1/5P1P/6K1 w - - 0 1");
+    assert!(0 < blocked_cost && blocked_cost < free_cost);
+}
+
+#[test]
+fn test_attack_needs_more_than_one_attacker() {
+    // Queen alone, knight alone, and both together against a castled
+    // king whose pawns are untouched.
+    let none = white_safety("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1");
+    let queen = white_safety("6k1/5ppp/8/8/7q/8/5PPP/6K1 w - - 0 1");
+    let knight = white_safety("6k1/5ppp/8/8/6n1/8/5PPP/6K1 w - - 0 1");
+    let both = white_safety("6k1/5ppp/8/8/6nq/8/5PPP/6K1 w - - 0 1");
+    assert_eq!(queen, none);
+    assert_eq!(knight, none);
+    assert!(both < none, "{both} vs {none}");
+}
+
+#[test]
+fn test_attack_penalty_is_nonlinear() {
+    // The same rook joining an attack costs more the heavier the
+    // attack already is.
+    let light = white_safety("6k1/5ppp/8/2b5/6n1/8/5PPP/6K1 w - - 0 1");
+    let light_with_rook = white_safety("6k1/5ppp/8/2b5/6n1/8/5PPP/4r1K1 w - - 0 1");
+    let heavy = white_safety("6k1/5ppp/8/8/6nq/8/5PPP/6K1 w - - 0 1");
+    let heavy_with_rook = white_safety("6k1/5ppp/8/8/6nq/8/5PPP/4r1K1 w - - 0 1");
+    assert!(light_with_rook < light);
+    assert!(light - light_with_rook < heavy - heavy_with_rook);
+}
+
+#[test]
+fn test_king_safety_fades_in_the_endgame() {
+    let safe = load_fen("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1").unwrap();
+    let attacked = load_fen("6k1/5ppp/8/8/6nq/8/5PPP/6K1 w - - 0 1").unwrap();
+    let safe = safe.king_safety(Color::White);
+    let attacked = attacked.king_safety(Color::White);
+    let mg_cost = safe.mg - attacked.mg;
+    let eg_cost = safe.eg - attacked.eg;
+    assert!(0 < eg_cost && eg_cost < mg_cost, "{mg_cost} vs {eg_cost}");
+}
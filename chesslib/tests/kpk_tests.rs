@@ -0,0 +1,141 @@
+use chesslib::fen::load_fen;
+use chesslib::kpk;
+use chesslib::search::Searcher;
+use chesslib::tablebase::{self, Wdl};
+
+fn probe(fen: &str) -> Option<bool> {
+    kpk::probe(&load_fen(fen).unwrap())
+}
+
+/// `fen` with the board turned over: colours swapped, ranks reversed.
+fn flip(fen: &str) -> String {
+    let parts: Vec<&str> = fen.split_whitespace().collect();
+    let placement: Vec<String> = parts[0]
+        .split('/')
+        .rev()
+        .map(|rank| {
+            rank.chars()
+                .map(|c| {
+                    if c.is_ascii_uppercase() {
+                        c.to_ascii_lowercase()
+                    } else {
+                        c.to_ascii_uppercase()
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let side = if parts[1] == "w" { "b" } else { "w" };
+    format!("{} {side} - - 0 1", placement.join("/"))
+}
+
+/// `fen` reflected left to right: a-file to h-file.
+fn mirror(fen: &str) -> String {
+    let parts: Vec<&str> = fen.split_whitespace().collect();
+    let placement: Vec<String> = parts[0]
+        .split('/')
+        .map(|rank| rank.chars().rev().collect())
+        .collect();
+    format!("{} {} - - 0 1", placement.join("/"), parts[1])
+}
+
+fn root_moves(fen: &str) -> Vec<String> {
+    let mut board = load_fen(fen).unwrap();
+    let mut moves = Vec::new();
+    board.get_all_raw_moves_append(&mut moves);
+    let keep = tablebase::root_moves(&mut board, &moves).unwrap();
+    let mut keep: Vec<String> = keep.iter().map(|m| m.to_string()).collect();
+    keep.sort();
+    keep
+}
+
+const POSITIONS: [(&str, bool); 8] = [
+    // King on the sixth in front of its pawn wins, whoever moves.
+    ("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", true),
+    ("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1", true),
+    // The opposition: it's a draw only if White must give it up.
+    ("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1", false),
+    ("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1", true),
+    // A rook pawn against the king in the corner.
+    ("k7/8/1K6/P7/8/8/8/8 w - - 0 1", false),
+    // Outside the square: the pawn runs.
+    ("7k/8/8/P7/8/8/8/7K b - - 0 1", true),
+    // The undefended pawn is taken.
+    ("8/8/8/8/3kP3/8/8/7K b - - 0 1", false),
+    // A spare pawn move wins the opposition back.
+    ("8/8/4k3/8/4K3/8/4P3/8 w - - 0 1", true),
+];
+
+#[test]
+fn test_kpk_textbook_positions() {
+    for (fen, win) in POSITIONS {
+        assert_eq!(probe(fen), Some(win), "{fen}");
+    }
+}
+
+#[test]
+fn test_kpk_is_colour_and_file_symmetric() {
+    for (fen, win) in POSITIONS {
+        assert_eq!(probe(&flip(fen)), Some(win), "{fen} flipped");
+        assert_eq!(probe(&mirror(fen)), Some(win), "{fen} mirrored");
+    }
+}
+
+#[test]
+fn test_kpk_probe_needs_exactly_king_and_pawn() {
+    assert_eq!(probe("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), None);
+    assert_eq!(probe("4k3/8/8/8/8/8/4P3/3NK3 w - - 0 1"), None);
+    assert_eq!(probe("4k3/4p3/8/8/8/8/4P3/4K3 w - - 0 1"), None);
+}
+
+#[test]
+fn test_probe_wdl_is_for_the_side_to_move() {
+    let wdl = |fen: &str| tablebase::probe_wdl(&mut load_fen(fen).unwrap());
+    assert_eq!(wdl("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1"), Some(Wdl::Win));
+    assert_eq!(wdl("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1"), Some(Wdl::Loss));
+    assert_eq!(
+        wdl(&flip("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1")),
+        Some(Wdl::Loss)
+    );
+    assert_eq!(wdl("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1"), Some(Wdl::Draw));
+    // Insufficient material needs no table.
+    assert_eq!(wdl("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1"), Some(Wdl::Draw));
+    assert_eq!(wdl("4k3/8/8/8/8/8/8/R3K3 w - - 0 1"), None);
+    assert_eq!(wdl("4k3/4p3/8/8/8/8/4P3/4K3 w - - 0 1"), None);
+}
+
+#[test]
+fn test_root_moves_keep_only_the_best_result() {
+    // Only stepping forward keeps the win; Kd5 and Kf5 let the king in.
+    assert_eq!(
+        root_moves("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1"),
+        ["e6d6", "e6f6"]
+    );
+    // The king must take the opposition or spend the pawn's tempo.
+    assert_eq!(
+        root_moves("8/8/4k3/8/4K3/8/4P3/8 w - - 0 1"),
+        ["e2e3", "e4d4", "e4f4"]
+    );
+    // Lost either way: every move is kept.
+    assert_eq!(root_moves("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1").len(), 5);
+
+    // A promotion leaves the table, so nothing is filtered.
+    let mut board = load_fen("8/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+    let mut moves = Vec::new();
+    board.get_all_raw_moves_append(&mut moves);
+    assert_eq!(tablebase::root_moves(&mut board, &moves), None);
+}
+
+#[test]
+fn test_search_keeps_a_won_kpk_and_scores_a_drawn_one_as_drawn() {
+    let mut board = load_fen("8/8/4k3/8/4K3/8/4P3/8 w - - 0 1").unwrap();
+    let (best, score) = Searcher::new().find_best_move(&mut board, 4);
+    let best = best.unwrap().to_string();
+    assert!(["e2e3", "e4d4", "e4f4"].contains(&best.as_str()), "{best}");
+    assert!(score > 500, "{score}");
+
+    // A drawn one is a draw, not a pawn up.
+    let mut board = load_fen("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1").unwrap();
+    let (_, score) = Searcher::new().find_best_move(&mut board, 4);
+    assert_eq!(score, 0);
+}
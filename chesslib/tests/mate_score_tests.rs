@@ -0,0 +1,107 @@
+use chesslib::board::Board;
+use chesslib::fen::load_fen;
+use chesslib::search::{score_from_tt, score_to_tt, Score, Searcher, MATE_SCORE, MATE_THRESHOLD};
+use chesslib::types::Color;
+
+/// Score from the side-to-move's POV, as the UCI layer reports it.
+fn pov(board: &Board, white_pov: i64) -> i64 {
+    if board.side_to_move == Color::Black {
+        -white_pov
+    } else {
+        white_pov
+    }
+}
+
+#[test]
+fn test_score_from_raw_classifies_centipawns() {
+    assert_eq!(Score::from_raw(0), Score::Centipawns(0));
+    assert_eq!(Score::from_raw(-350), Score::Centipawns(-350));
+    assert_eq!(
+        Score::from_raw(MATE_THRESHOLD - 1),
+        Score::Centipawns(MATE_THRESHOLD - 1)
+    );
+}
+
+#[test]
+fn test_score_from_raw_counts_moves_not_plies() {
+    // Mate delivered on ply 1 and ply 3 are mate in 1 and mate in 2.
+    assert_eq!(Score::from_raw(MATE_SCORE - 1), Score::MateIn(1));
+    assert_eq!(Score::from_raw(MATE_SCORE - 3), Score::MateIn(2));
+    // Being mated on ply 2 is "mated in 1"; on the board right now, 0.
+    assert_eq!(Score::from_raw(-MATE_SCORE + 2), Score::MatedIn(1));
+    assert_eq!(Score::from_raw(-MATE_SCORE), Score::MatedIn(0));
+}
+
+#[test]
+fn test_score_display_is_uci_payload() {
+    assert_eq!(Score::Centipawns(35).to_string(), "cp 35");
+    assert_eq!(Score::MateIn(3).to_string(), "mate 3");
+    assert_eq!(Score::MatedIn(2).to_string(), "mate -2");
+}
+
+#[test]
+fn test_tt_mate_adjustment_round_trips() {
+    for &score in &[MATE_SCORE - 5, -MATE_SCORE + 7, 120, -40] {
+        for ply in [0usize, 1, 4, 9] {
+            assert_eq!(score_from_tt(score_to_tt(score, ply), ply), score);
+        }
+    }
+}
+
+#[test]
+fn test_tt_mate_adjustment_reanchors_to_probing_ply() {
+    // A mate 5 plies from the root, found at ply 2, is 3 plies from
+    // that node. Probed again at ply 4 it is 7 plies from the root.
+    let stored = score_to_tt(MATE_SCORE - 5, 2);
+    assert_eq!(stored, MATE_SCORE - 3);
+    assert_eq!(score_from_tt(stored, 4), MATE_SCORE - 7);
+
+    // Centipawn scores are never touched.
+    assert_eq!(score_to_tt(250, 6), 250);
+    assert_eq!(score_from_tt(250, 6), 250);
+}
+
+#[test]
+fn test_mate_in_one_reported_as_mate_in_one() {
+    // Scholar's mate: Qxf7#.
+    let mut board = Board::new();
+    for mv in ["e2e4", "e7e5", "d1h5", "b8c6", "f1c4", "g8f6"] {
+        board.apply_move_from_string(mv);
+    }
+    let (mv, score) = Searcher::new_deterministic().find_best_move(&mut board, 3);
+    assert_eq!(mv.unwrap().to_string(), "h5f7");
+    assert_eq!(Score::from_raw(pov(&board, score)), Score::MateIn(1));
+}
+
+#[test]
+fn test_mate_in_two_for_white() {
+    // 1.Rd8+ Rxd8 2.Rxd8#.
+    let mut board = load_fen("1r4k1/5ppp/8/8/8/8/3R1PPP/3R2K1 w - - 0 1").unwrap();
+    let (mv, score) = Searcher::new_deterministic().find_best_move(&mut board, 4);
+    assert_eq!(mv.unwrap().to_string(), "d2d8");
+    assert_eq!(Score::from_raw(pov(&board, score)), Score::MateIn(2));
+}
+
+#[test]
+fn test_mate_in_two_for_black_is_reported_from_blacks_side() {
+    // Colour-flipped version of the position above: 1...Rd1+ 2.Rxd1 Rxd1#.
+    let mut board = load_fen("3r2k1/3r1ppp/8/8/8/8/5PPP/1R4K1 b - - 0 1").unwrap();
+    let (mv, score) = Searcher::new_deterministic().find_best_move(&mut board, 4);
+    assert_eq!(mv.unwrap().to_string(), "d7d1");
+    // White's POV: black is mating, so the raw score is negative...
+    assert_eq!(Score::from_raw(score), Score::MatedIn(2));
+    // ...and from the side to move (Black) it's a mate for us.
+    assert_eq!(Score::from_raw(pov(&board, score)), Score::MateIn(2));
+}
+
+#[test]
+fn test_checkmated_root_is_mated_in_zero() {
+    // Fool's mate: white is already checkmated.
+    let mut board = Board::new();
+    for mv in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+        board.apply_move_from_string(mv);
+    }
+    let (mv, score) = Searcher::new_deterministic().find_best_move(&mut board, 2);
+    assert!(mv.is_none());
+    assert_eq!(Score::from_raw(pov(&board, score)), Score::MatedIn(0));
+}
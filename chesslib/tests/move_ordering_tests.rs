@@ -1,5 +1,6 @@
 use chesslib::board::Board;
 use chesslib::fen::load_fen;
+use chesslib::search::{SearchFeatures, SearchLimits, SearchResult, Searcher};
 use chesslib::types::Move;
 use chesslib::Square;
 
@@ -153,3 +154,65 @@ fn test_check_ordering() {
         "Should find at least one check-giving move"
     );
 }
+
+/// Search the starting position to depth 5 with `features`; returns
+/// the result and the node count.
+fn startpos_search(features: SearchFeatures) -> (SearchResult, u64) {
+    let mut board = Board::new();
+    let mut searcher = Searcher::new_deterministic();
+    searcher.set_features(features);
+    let limits = SearchLimits {
+        depth: Some(5),
+        ..SearchLimits::default()
+    };
+    let result = searcher.search(&mut board, limits, 1);
+    (result, searcher.nodes())
+}
+
+#[test]
+fn test_countermoves_and_continuation_history_order_the_search() {
+    // Both tables only reorder quiet moves, so every search below
+    // reaches the same depth; the ones without them get there through
+    // more nodes.
+    let (_, with_both) = startpos_search(SearchFeatures::default());
+    let without = |features| startpos_search(features).1;
+    let no_countermoves = without(SearchFeatures {
+        countermoves: false,
+        ..SearchFeatures::default()
+    });
+    let no_continuation = without(SearchFeatures {
+        continuation_history: false,
+        ..SearchFeatures::default()
+    });
+    let neither = without(SearchFeatures {
+        countermoves: false,
+        continuation_history: false,
+        ..SearchFeatures::default()
+    });
+    assert!(
+        with_both < no_countermoves,
+        "{with_both} vs {no_countermoves}"
+    );
+    assert!(
+        with_both < no_continuation,
+        "{with_both} vs {no_continuation}"
+    );
+    assert!(with_both < neither, "{with_both} vs {neither}");
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_countermoves_and_continuation_history_raise_first_move_cutoffs() {
+    let (with_both, _) = startpos_search(SearchFeatures::default());
+    let (neither, _) = startpos_search(SearchFeatures {
+        countermoves: false,
+        continuation_history: false,
+        ..SearchFeatures::default()
+    });
+    assert!(
+        with_both.stats.first_move_cutoff_rate() > neither.stats.first_move_cutoff_rate(),
+        "{} vs {}",
+        with_both.stats.first_move_cutoff_rate(),
+        neither.stats.first_move_cutoff_rate()
+    );
+}
@@ -0,0 +1,83 @@
+use chesslib::board::Board;
+use chesslib::fen::load_fen;
+use chesslib::search::{Score, Searcher};
+use chesslib::types::Color;
+
+/// Score from the side-to-move's POV.
+fn pov(board: &Board, white_pov: i64) -> i64 {
+    if board.side_to_move == Color::Black {
+        -white_pov
+    } else {
+        white_pov
+    }
+}
+
+#[test]
+fn test_multipv_returns_distinct_moves() {
+    let mut board = Board::new();
+    let lines = Searcher::new_deterministic().find_best_moves_multipv(&mut board, 3, 4);
+    assert_eq!(lines.len(), 4);
+    for (i, (a, _, _)) in lines.iter().enumerate() {
+        for (b, _, _) in &lines[i + 1..] {
+            assert_ne!(a, b);
+        }
+    }
+}
+
+#[test]
+fn test_multipv_first_line_matches_single_pv_search() {
+    let mut board =
+        load_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+    let (best, score) = Searcher::new_deterministic().find_best_move(&mut board, 3);
+    let lines = Searcher::new_deterministic().find_best_moves_multipv(&mut board, 3, 3);
+    assert_eq!(Some(lines[0].0), best);
+    assert_eq!(lines[0].1, score);
+}
+
+#[test]
+fn test_multipv_scores_are_ordered_for_side_to_move() {
+    // Black to move, so White-POV scores must be non-decreasing.
+    let mut board =
+        load_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2").unwrap();
+    let lines = Searcher::new_deterministic().find_best_moves_multipv(&mut board, 3, 5);
+    for pair in lines.windows(2) {
+        assert!(pov(&board, pair[0].1) >= pov(&board, pair[1].1));
+    }
+}
+
+#[test]
+fn test_multipv_lines_start_with_their_move_and_are_playable() {
+    let mut board = Board::new();
+    let lines = Searcher::new_deterministic().find_best_moves_multipv(&mut board, 3, 3);
+    for (mv, _, pv) in &lines {
+        assert_eq!(pv.first(), Some(mv));
+        let mut copy = board.clone();
+        for step in pv {
+            assert!(copy.is_legal_move(step), "illegal pv move {step}");
+            copy.apply_move(step);
+        }
+    }
+}
+
+#[test]
+fn test_multipv_larger_than_legal_move_count_returns_every_move() {
+    // The lone white king has three legal moves.
+    let mut board = load_fen("7k/8/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    let lines = Searcher::new_deterministic().find_best_moves_multipv(&mut board, 2, 10);
+    assert_eq!(lines.len(), 3);
+}
+
+#[test]
+fn test_multipv_mate_in_one_leads() {
+    let mut board = Board::new();
+    for mv in ["e2e4", "e7e5", "d1h5", "b8c6", "f1c4", "g8f6"] {
+        board.apply_move_from_string(mv);
+    }
+    let lines = Searcher::new_deterministic().find_best_moves_multipv(&mut board, 3, 3);
+    assert_eq!(lines[0].0.to_string(), "h5f7");
+    assert_eq!(Score::from_raw(pov(&board, lines[0].1)), Score::MateIn(1));
+    assert!(!matches!(
+        Score::from_raw(pov(&board, lines[1].1)),
+        Score::MateIn(_)
+    ));
+}
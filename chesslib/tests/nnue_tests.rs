@@ -0,0 +1,215 @@
+#![cfg(feature = "nnue")]
+
+use chesslib::bench::BENCH_POSITIONS;
+use chesslib::board::Board;
+use chesslib::fen::load_fen;
+use chesslib::handle_uci_command;
+use chesslib::nnue::{
+    self, crelu_dot, crelu_dot_scalar, feature_index, Accumulator, Evaluator, Network, FEATURES,
+};
+use chesslib::types::{Color, Move, Piece};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+
+/// A network with random weights, small enough that the accumulators
+/// stay well inside `i16`.
+fn random_network(hidden: usize, seed: u64) -> Network {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut values = |n: usize, range: i16| -> Vec<i16> {
+        (0..n).map(|_| rng.gen_range(-range..=range)).collect()
+    };
+    Network::new(
+        values(FEATURES * hidden, 20),
+        values(hidden, 60),
+        values(2 * hidden, 60),
+        values(1, 500)[0],
+    )
+    .unwrap()
+}
+
+/// `fen` seen from the other side: board flipped, colours swapped.
+fn mirror(fen: &str) -> String {
+    let parts: Vec<&str> = fen.split_whitespace().collect();
+    let swap_case = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                }
+            })
+            .collect()
+    };
+    let placement: Vec<String> = parts[0].split('/').rev().map(swap_case).collect();
+    let side = if parts[1] == "w" { "b" } else { "w" };
+    format!("{} {side} - - 0 1", placement.join("/"))
+}
+
+fn legal_moves(board: &mut Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    board.get_all_raw_moves_append(&mut moves);
+    moves.retain(|mv| board.is_legal_move(mv));
+    moves
+}
+
+fn assert_accumulator_fresh(board: &Board, network: &Arc<Network>) {
+    let fresh = Accumulator::new(network.clone(), board);
+    for color in [Color::White, Color::Black] {
+        assert_eq!(
+            board.accumulator().values(color),
+            fresh.values(color),
+            "{color:?} accumulator drifted at {}",
+            board.to_fen()
+        );
+    }
+}
+
+#[test]
+fn test_weight_file_round_trip() {
+    let network = random_network(8, 1);
+    let bytes = network.to_bytes();
+    assert_eq!(&bytes[..4], b"NNUE");
+    assert_eq!(bytes.len(), 12 + 2 * ((768 + 3) * 8 + 1));
+    assert_eq!(Network::from_bytes(&bytes).unwrap(), network);
+
+    let path = std::env::temp_dir().join(format!("nnue_round_trip_{}.nnue", std::process::id()));
+    network.save(&path).unwrap();
+    let loaded = Network::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), network);
+
+    assert!(Network::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+    let mut wrong_magic = bytes.clone();
+    wrong_magic[0] = b'X';
+    assert!(Network::from_bytes(&wrong_magic).is_err());
+    let mut wrong_version = bytes;
+    wrong_version[4] = 2;
+    assert!(Network::from_bytes(&wrong_version).is_err());
+    assert!(Network::new(vec![0; 10], vec![0; 8], vec![0; 16], 0).is_err());
+    assert!(Network::load("/nonexistent/net.nnue").is_err());
+}
+
+#[test]
+fn test_feature_index_mirrors_between_perspectives() {
+    // e2 is square 12, e7 square 52.
+    assert_eq!(feature_index(Color::White, Piece::WhitePawn, 12), 12);
+    assert_eq!(
+        feature_index(Color::Black, Piece::BlackPawn, 52),
+        feature_index(Color::White, Piece::WhitePawn, 12)
+    );
+    assert_eq!(
+        feature_index(Color::White, Piece::BlackKing, 63),
+        384 + 5 * 64 + 63
+    );
+    assert_eq!(
+        feature_index(Color::Black, Piece::WhiteKnight, 6),
+        384 + 64 + 62
+    );
+}
+
+#[test]
+fn test_simd_output_matches_scalar() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for len in [0, 1, 15, 16, 17, 64, 100, 256] {
+        let values: Vec<i16> = (0..len).map(|_| rng.gen_range(-600..=600)).collect();
+        let weights: Vec<i16> = (0..len).map(|_| rng.gen()).collect();
+        assert_eq!(
+            crelu_dot(&values, &weights),
+            crelu_dot_scalar(&values, &weights),
+            "length {len}"
+        );
+    }
+}
+
+#[test]
+fn test_network_is_colour_symmetric() {
+    // The same weights see each side's pieces from that side, so a
+    // mirrored position gets the negated score.
+    let network = random_network(16, 2);
+    for fen in BENCH_POSITIONS.iter().take(20) {
+        let board = load_fen(fen).unwrap();
+        let mirrored = load_fen(&mirror(fen)).unwrap();
+        assert_eq!(
+            network.evaluate(&board),
+            -network.evaluate(&mirrored),
+            "{fen}"
+        );
+    }
+}
+
+#[test]
+fn test_accumulators_follow_moves_and_the_evaluator_switch() {
+    // The only test in this file that touches the global network and
+    // evaluator.
+    let classical = load_fen(BENCH_POSITIONS[1]).unwrap().evaluate();
+    nnue::set_network(Some(random_network(32, 3)));
+    let network = nnue::network().unwrap();
+    // Loaded but not selected: still classical.
+    assert_eq!(load_fen(BENCH_POSITIONS[1]).unwrap().evaluate(), classical);
+    nnue::set_evaluator(Evaluator::Nnue);
+
+    // Random games from positions with castling, en passant and
+    // promotions, checked against a from-scratch build after every
+    // move and every undo.
+    let mut rng = StdRng::seed_from_u64(4);
+    for fen in [
+        BENCH_POSITIONS[0],
+        BENCH_POSITIONS[1],
+        "rnbqkbnr/pp1ppppp/8/2pP4/8/8/PPP1PPPP/RNBQKBNR w KQkq c6 0 3",
+        "r3k2r/1P4P1/8/8/8/8/1p4p1/R3K2R w KQkq - 0 1",
+    ] {
+        let mut board = load_fen(fen).unwrap();
+        assert_accumulator_fresh(&board, &network);
+        let mut played = 0;
+        for _ in 0..40 {
+            let moves = legal_moves(&mut board);
+            if moves.is_empty() {
+                break;
+            }
+            board.apply_move(&moves[rng.gen_range(0..moves.len())]);
+            played += 1;
+            assert_accumulator_fresh(&board, &network);
+            assert_eq!(board.evaluate(), network.evaluate(&board));
+        }
+        for _ in 0..played {
+            board.undo_last_move();
+            assert_accumulator_fresh(&board, &network);
+        }
+        assert_eq!(board.to_fen(), load_fen(fen).unwrap().to_fen());
+    }
+
+    // A new network reaches boards built under the old one.
+    let mut board = load_fen(BENCH_POSITIONS[1]).unwrap();
+    nnue::set_network(Some(random_network(16, 5)));
+    let replacement = nnue::network().unwrap();
+    assert_eq!(board.evaluate(), replacement.evaluate(&board));
+    let mv = legal_moves(&mut board)[0];
+    board.apply_move(&mv);
+    assert_accumulator_fresh(&board, &replacement);
+
+    // Back to classical, and classical without a network too.
+    nnue::set_evaluator(Evaluator::Classical);
+    let board = load_fen(BENCH_POSITIONS[1]).unwrap();
+    assert_eq!(board.evaluate(), classical);
+    nnue::set_evaluator(Evaluator::Nnue);
+    nnue::set_network(None);
+    assert_eq!(board.evaluate(), classical);
+    nnue::set_evaluator(Evaluator::Classical);
+
+    // The same switch over UCI.
+    let path = std::env::temp_dir().join(format!("nnue_uci_{}.nnue", std::process::id()));
+    random_network(8, 6).save(&path).unwrap();
+    handle_uci_command(&format!("setoption name EvalFile value {}", path.display()));
+    std::fs::remove_file(&path).unwrap();
+    assert!(nnue::network().is_some());
+    assert!(!handle_uci_command("eval").contains("NNUE evaluation"));
+    handle_uci_command("setoption name Use NNUE value true");
+    assert_eq!(nnue::evaluator(), Evaluator::Nnue);
+    assert!(handle_uci_command("eval").contains("NNUE evaluation"));
+    handle_uci_command("setoption name Use NNUE value false");
+    handle_uci_command("setoption name EvalFile value <empty>");
+    assert_eq!(nnue::evaluator(), Evaluator::Classical);
+    assert!(nnue::network().is_none());
+}
@@ -0,0 +1,158 @@
+use chesslib::board::Board;
+use chesslib::fen::load_fen;
+
+/// `fen` with the board flipped top to bottom and the colours swapped:
+/// the same position from the other side.
+fn mirror(fen: &str) -> String {
+    let parts: Vec<&str> = fen.split_whitespace().collect();
+    let swap_case = |s: &str| {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                }
+            })
+            .collect::<String>()
+    };
+    let placement: Vec<String> = parts[0].split('/').rev().map(swap_case).collect();
+    let side = if parts[1] == "w" { "b" } else { "w" };
+    let castling = if parts[2] == "-" {
+        "-".to_string()
+    } else {
+        let swapped = swap_case(parts[2]);
+        let mut rights: Vec<char> = swapped.chars().collect();
+        rights.sort_by_key(|c| (c.is_ascii_lowercase(), *c != 'K' && *c != 'k'));
+        rights.into_iter().collect()
+    };
+    let en_passant = match parts[3] {
+        "-" => "-".to_string(),
+        ep => {
+            let rank = ep.as_bytes()[1] - b'0';
+            format!("{}{}", &ep[..1], 9 - rank)
+        }
+    };
+    format!("{} {side} {castling} {en_passant} 0 1", placement.join("/"))
+}
+
+fn board(fen: &str) -> Board {
+    load_fen(fen).unwrap_or_else(|e| panic!("{fen}: {e}"))
+}
+
+const POSITIONS: &[&str] = &[
+    "r1bqkbnr/pp1ppppp/2n5/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "4k3/pp4pp/8/2P5/1P1p4/3P4/6PP/4K3 b - - 0 1",
+    "8/5p2/1p4p1/p2P3p/P6P/1P4P1/5P2/3k2K1 w - - 0 1",
+    "7k/8/8/P7/8/8/1p6/K7 w - - 0 1",
+    "2k5/8/3p4/2pPp3/2P1P3/8/8/2K5 w - - 0 1",
+];
+
+#[test]
+fn test_mirror_helper_round_trips() {
+    for fen in POSITIONS {
+        assert_eq!(board(&mirror(&mirror(fen))), board(fen));
+    }
+}
+
+#[test]
+fn test_pawn_score_is_antisymmetric_under_mirroring() {
+    for fen in POSITIONS {
+        let original = board(fen).evaluate_pawns();
+        let mirrored = board(&mirror(fen)).evaluate_pawns();
+        assert_eq!(mirrored, -original, "{fen}");
+    }
+}
+
+#[test]
+fn test_full_evaluation_is_antisymmetric_under_mirroring() {
+    for fen in POSITIONS {
+        assert_eq!(
+            board(&mirror(fen)).evaluate(),
+            -board(fen).evaluate(),
+            "{fen}"
+        );
+    }
+}
+
+#[test]
+fn test_symmetric_pawns_score_zero() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "4k3/pp3ppp/2p5/3p4/3P4/2P5/PP3PPP/4K3 w - - 0 1",
+        "4k3/p6p/8/3p4/3P4/8/P6P/4K3 b - - 0 1",
+    ] {
+        assert_eq!(board(fen).evaluate_pawns().mg, 0, "{fen}");
+        assert_eq!(board(fen).evaluate_pawns().eg, 0, "{fen}");
+    }
+}
+
+#[test]
+fn test_isolated_pawn_is_penalised() {
+    // Black's full rank of pawns keeps every white pawn from being
+    // passed, so only the structure differs.
+    let isolated = board("4k3/pppppppp/8/8/8/8/PP1P4/4K3 w - - 0 1").evaluate_pawns();
+    let connected = board("4k3/pppppppp/8/8/8/8/PPP5/4K3 w - - 0 1").evaluate_pawns();
+    assert!(isolated.mg < connected.mg && isolated.eg < connected.eg);
+}
+
+#[test]
+fn test_doubled_pawn_is_penalised() {
+    // b3 and c3 are both defended by a pawn; only b3 is doubled.
+    let doubled = board("4k3/pppppppp/8/8/8/1P6/PP6/4K3 w - - 0 1").evaluate_pawns();
+    let healthy = board("4k3/pppppppp/8/8/8/2P5/PP6/4K3 w - - 0 1").evaluate_pawns();
+    assert!(doubled.mg < healthy.mg && doubled.eg < healthy.eg);
+}
+
+#[test]
+fn test_passed_pawn_bonus_grows_with_rank() {
+    let mut previous = i64::MIN;
+    for rank in 2..=7 {
+        let mut placement = ["8"; 8];
+        placement[0] = "k7";
+        placement[7] = "K7";
+        let pawn_row = 8 - rank;
+        placement[pawn_row] = "4P3";
+        let fen = format!("{} b - - 0 1", placement.join("/"));
+        // Black to move with the king on a8 always catches the pawn
+        // on the e-file, so no unstoppable bonus muddies the ramp.
+        let score = board(&fen).evaluate_pawns().eg;
+        assert!(score > previous, "rank {rank}: {fen}");
+        previous = score;
+    }
+}
+
+#[test]
+fn test_blocked_passed_pawn_is_worth_less() {
+    let blocked = board("4k3/8/n7/P7/8/8/8/4K3 w - - 0 1").evaluate_pawns();
+    let free = board("4k3/8/2n5/P7/8/8/8/4K3 w - - 0 1").evaluate_pawns();
+    assert!(blocked.eg < free.eg);
+}
+
+#[test]
+fn test_unstoppable_passed_pawn_in_pawn_ending() {
+    // The king on h8 is outside the a-pawn's square; on b8 it's in.
+    let runs = board("7k/8/8/P7/8/8/8/K7 w - - 0 1").evaluate_pawns();
+    let caught = board("1k6/8/8/P7/8/8/8/K7 w - - 0 1").evaluate_pawns();
+    assert!(runs.eg > caught.eg + 200);
+    // With Black to move, the king on e8 steps into the square in time.
+    let in_time = board("4k3/8/8/P7/8/8/8/K7 b - - 0 1").evaluate_pawns();
+    let too_late = board("4k3/8/8/P7/8/8/8/K7 w - - 0 1").evaluate_pawns();
+    assert!(too_late.eg > in_time.eg + 200);
+}
+
+#[test]
+fn test_cached_pawn_score_matches_fresh_board() {
+    // Walk a game so the pawn table fills, then compare every
+    // position against a board loaded from scratch.
+    let mut game = Board::new();
+    for mv in [
+        "e2e4", "d7d5", "e4d5", "c7c6", "d5c6", "b8c6", "d2d4", "e7e5", "d4e5",
+    ] {
+        game.apply_move_from_string(mv);
+        let fresh = board(&game.to_fen());
+        assert_eq!(game.evaluate_pawns(), fresh.evaluate_pawns(), "after {mv}");
+    }
+}
@@ -0,0 +1,127 @@
+use chesslib::board_utils::get_starting_board;
+use chesslib::fen::load_fen;
+use chesslib::perft::{format_divide, PerftCounts, PerftSuiteEntry, PerftTable};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+
+#[test]
+fn test_divide_sums_to_perft() {
+    let mut board = get_starting_board();
+    let divide = board.perft_divide(3);
+    assert_eq!(divide.len(), 20);
+    let total: u64 = divide.iter().map(|(_, nodes)| nodes).sum();
+    assert_eq!(total, 8_902);
+    assert_eq!(total, board.perft(3).0);
+    assert!(board.perft_divide(0).is_empty());
+}
+
+#[test]
+fn test_divide_format_lists_moves_and_total() {
+    let mut board = get_starting_board();
+    let text = format_divide(&board.perft_divide(2));
+    assert!(text.contains("e2e4: 20\n"), "{text}");
+    assert!(text.ends_with("\n\nNodes searched: 400"), "{text}");
+}
+
+#[test]
+fn test_parallel_divide_matches_serial() {
+    let mut board = load_fen(KIWIPETE).unwrap();
+    let serial = board.perft_divide(2);
+    assert_eq!(board.perft_divide_parallel(2, 3), serial);
+    assert_eq!(board.perft_divide_parallel(2, 1), serial);
+}
+
+#[test]
+fn test_hashed_perft_matches_plain() {
+    let mut board = load_fen(KIWIPETE).unwrap();
+    let mut table = PerftTable::new(1);
+    assert_eq!(board.perft_hashed(3, &mut table), 97_862);
+    // Again, now answered largely from the table.
+    assert_eq!(board.perft_hashed(3, &mut table), 97_862);
+    // A one-entry table collides constantly and must still be right.
+    let mut tiny = PerftTable::new(0);
+    assert_eq!(board.perft_hashed(3, &mut tiny), 97_862);
+}
+
+#[test]
+fn test_counts_match_published_tables() {
+    // chessprogramming.org/Perft_Results.
+    let mut board = load_fen(KIWIPETE).unwrap();
+    assert_eq!(
+        board.perft_counts(2),
+        PerftCounts {
+            nodes: 2_039,
+            captures: 351,
+            en_passants: 1,
+            castles: 91,
+            promotions: 0,
+            checks: 3,
+            discovered_checks: 0,
+            double_checks: 0,
+            checkmates: 0,
+        }
+    );
+    let mut board = load_fen(POSITION_3).unwrap();
+    assert_eq!(
+        board.perft_counts(3),
+        PerftCounts {
+            nodes: 2_812,
+            captures: 209,
+            en_passants: 2,
+            castles: 0,
+            promotions: 0,
+            checks: 267,
+            discovered_checks: 3,
+            double_checks: 0,
+            checkmates: 0,
+        }
+    );
+    let mut board = load_fen(POSITION_4).unwrap();
+    assert_eq!(
+        board.perft_counts(3),
+        PerftCounts {
+            nodes: 9_467,
+            captures: 1_021,
+            en_passants: 4,
+            castles: 0,
+            promotions: 120,
+            checks: 38,
+            discovered_checks: 2,
+            double_checks: 0,
+            checkmates: 22,
+        }
+    );
+}
+
+#[test]
+fn test_suite_line_parses() {
+    let entry = PerftSuiteEntry::parse("4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D1 15 ;D2 66").unwrap();
+    assert_eq!(entry.fen, "4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    assert_eq!(entry.expected, vec![(1, 15), (2, 66)]);
+    assert!(PerftSuiteEntry::parse("not a fen ;D1 1").is_err());
+    assert!(PerftSuiteEntry::parse("4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D1 x").is_err());
+}
+
+#[test]
+fn test_suite_check_reports_mismatches() {
+    let entry = PerftSuiteEntry::parse("4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D1 15 ;D2 67").unwrap();
+    let mismatches = entry.check(2, 2);
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].depth, 2);
+    assert_eq!(mismatches[0].expected, 67);
+    assert_eq!(mismatches[0].actual, 66);
+    // Depths beyond the limit aren't counted.
+    assert!(entry.check(1, 2).is_empty());
+}
+
+#[test]
+fn test_bundled_suite_passes_at_shallow_depth() {
+    let text = include_str!("data/perft_suite.epd");
+    let suite = PerftSuiteEntry::parse_suite(text).unwrap();
+    assert!(suite.len() >= 10);
+    for entry in &suite {
+        assert!(entry.check(2, 2).is_empty(), "{}", entry.fen);
+    }
+}
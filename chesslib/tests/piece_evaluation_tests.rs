@@ -0,0 +1,110 @@
+use chesslib::evaluation::Tapered;
+use chesslib::fen::load_fen;
+use chesslib::types::Color;
+
+/// White's piece-specific terms alone.
+fn white_terms(fen: &str) -> Tapered {
+    load_fen(fen)
+        .unwrap_or_else(|e| panic!("{fen}: {e}"))
+        .piece_terms(Color::White)
+}
+
+#[test]
+fn test_starting_position_is_balanced() {
+    let board = load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    assert_eq!(board.evaluate_pieces(), Tapered::default());
+}
+
+#[test]
+fn test_rook_files() {
+    let closed = white_terms("4k3/p7/8/8/8/8/P7/R3K3 w - - 0 1");
+    let semi_open = white_terms("4k3/p7/8/8/8/8/1P6/R3K3 w - - 0 1");
+    let open = white_terms("4k3/1p6/8/8/8/8/1P6/R3K3 w - - 0 1");
+    assert!(semi_open.mg > closed.mg && semi_open.eg > closed.eg);
+    assert!(open.mg > semi_open.mg && open.eg > semi_open.eg);
+}
+
+#[test]
+fn test_rook_on_seventh_needs_targets() {
+    let with_pawns = white_terms("6k1/p4ppp/8/8/8/8/1R6/6K1 w - - 0 1");
+    let seventh = white_terms("6k1/pR3ppp/8/8/8/8/8/6K1 w - - 0 1");
+    assert!(seventh.eg > with_pawns.eg);
+    // Nothing to attack on the seventh and the king off the eighth.
+    let empty = white_terms("8/1R6/6k1/8/8/8/8/6K1 w - - 0 1");
+    let elsewhere = white_terms("8/8/1R4k1/8/8/8/8/6K1 w - - 0 1");
+    assert_eq!(empty, elsewhere);
+}
+
+#[test]
+fn test_knight_outpost() {
+    // d4 defends the knight on e5; no black pawn can ever chase it.
+    let outpost = white_terms("4k3/p7/8/4N3/3P4/8/8/4K3 w - - 0 1");
+    let undefended = white_terms("4k3/p7/8/4N3/8/3P4/8/4K3 w - - 0 1");
+    let chased = white_terms("4k3/5p2/8/4N3/3P4/8/8/4K3 w - - 0 1");
+    assert!(outpost.mg > undefended.mg);
+    assert!(outpost.mg > chased.mg);
+}
+
+#[test]
+fn test_bad_bishop() {
+    // c1, d4 and e3 are dark squares; d3 and e4 are light.
+    let bad = white_terms("4k3/8/8/8/3P4/4P3/8/2B1K3 w - - 0 1");
+    let good = white_terms("4k3/8/8/8/4P3/3P4/8/2B1K3 w - - 0 1");
+    assert!(bad.eg < good.eg);
+    // Blocked pawns on its colour are worse still.
+    let blocked = white_terms("4k3/8/8/3p4/3P4/4P3/8/2B1K3 w - - 0 1");
+    assert!(blocked.eg < bad.eg);
+}
+
+#[test]
+fn test_early_queen() {
+    let home = white_terms("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    let out = white_terms("rnbqkbnr/pppppppp/8/7Q/4P3/8/PPPP1PPP/RNB1KBNR b KQkq - 0 1");
+    let developed = white_terms("rnbqkbnr/pppppppp/8/7Q/2B1P3/5N2/PPPP1PPP/RNB1K2R b KQkq - 0 1");
+    assert!(out.mg < home.mg);
+    assert!(developed.mg > out.mg);
+    assert_eq!(out.eg, home.eg);
+}
+
+#[test]
+fn test_trapped_bishop() {
+    let trapped = white_terms("4k3/B7/1p6/8/8/8/8/4K3 w - - 0 1");
+    let free = white_terms("4k3/B7/8/1p6/8/8/8/4K3 w - - 0 1");
+    assert!(trapped.mg < free.mg - 50);
+}
+
+#[test]
+fn test_rook_trapped_by_uncastled_king() {
+    let trapped = white_terms("4k3/8/8/8/8/8/5PPP/5K1R w - - 0 1");
+    let can_castle = white_terms("4k3/8/8/8/8/8/5PPP/4K2R w K - 0 1");
+    let castled = white_terms("4k3/8/8/8/8/8/5PPP/5RK1 w - - 0 1");
+    assert!(trapped.mg < can_castle.mg);
+    assert!(trapped.mg < castled.mg);
+}
+
+#[test]
+fn test_threats_by_lesser_pieces() {
+    let quiet = white_terms("4k3/8/8/3n4/8/4P3/8/4K3 w - - 0 1");
+    let pawn_hits_knight = white_terms("4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1");
+    assert!(pawn_hits_knight.mg > quiet.mg);
+
+    let knight_hits_rook = white_terms("4k3/8/3r4/8/4N3/8/8/4K3 w - - 0 1");
+    let knight_hits_pawn = white_terms("4k3/8/3p4/8/4N3/8/8/4K3 w - - 0 1");
+    assert!(knight_hits_rook.mg > knight_hits_pawn.mg);
+
+    let rook_hits_queen = white_terms("3qk3/8/8/8/8/8/8/3RK3 w - - 0 1");
+    let rook_hits_rook = white_terms("3rk3/8/8/8/8/8/8/3RK3 w - - 0 1");
+    assert!(rook_hits_queen.mg > rook_hits_rook.mg);
+
+    // The king threatens a piece beside it only if nothing defends it.
+    let king_hits_knight = white_terms("4k3/8/8/8/8/8/3n4/4K3 w - - 0 1");
+    let king_hits_defended = white_terms("4k3/8/8/8/8/2p5/3n4/4K3 w - - 0 1");
+    assert!(king_hits_knight.eg > king_hits_defended.eg);
+}
+
+#[test]
+fn test_piece_terms_are_antisymmetric() {
+    let white = load_fen("r3k2r/pB3ppp/1p6/3nN3/8/8/PPP2PPP/R4K1R w kq - 0 1").unwrap();
+    let black = load_fen("r4k1r/ppp2ppp/8/8/3Nn3/1P6/Pb3PPP/R3K2R b KQ - 0 1").unwrap();
+    assert_eq!(white.evaluate_pieces(), -black.evaluate_pieces());
+}
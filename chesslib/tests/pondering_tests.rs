@@ -0,0 +1,179 @@
+use chesslib::board::Board;
+use chesslib::handle_uci_command;
+use chesslib::search::{SearchLimits, Searcher};
+use chesslib::types::Move;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The UCI handler keeps one global engine; tests that drive it must
+/// not interleave.
+static UCI: Mutex<()> = Mutex::new(());
+
+/// Check a `bestmove X [ponder Y]` response against `board` and return
+/// the two moves.
+fn parse_bestmove(board: &mut Board, response: &str) -> (Move, Option<Move>) {
+    let parts: Vec<&str> = response.split_whitespace().collect();
+    assert_eq!(parts[0], "bestmove", "response was {response:?}");
+    let best = Move::try_from(parts[1]).unwrap();
+    assert!(
+        board.is_legal_move(&best),
+        "illegal bestmove in {response:?}"
+    );
+    let ponder = match parts.get(2) {
+        Some(&"ponder") => {
+            let ponder = Move::try_from(parts[3]).unwrap();
+            board.apply_move(&best);
+            assert!(
+                board.is_legal_move(&ponder),
+                "illegal ponder in {response:?}"
+            );
+            board.undo_last_move();
+            Some(ponder)
+        }
+        _ => None,
+    };
+    (best, ponder)
+}
+
+#[test]
+fn test_bestmove_carries_ponder_move() {
+    let _uci = UCI.lock().unwrap_or_else(|e| e.into_inner());
+    handle_uci_command("position startpos moves e2e4");
+    let response = handle_uci_command("go depth 4");
+    let mut board = Board::new();
+    board.apply_move_from_string("e2e4");
+    let (_, ponder) = parse_bestmove(&mut board, &response);
+    assert!(ponder.is_some(), "no ponder move in {response:?}");
+}
+
+#[test]
+fn test_go_ponder_then_stop_reports_bestmove() {
+    let _uci = UCI.lock().unwrap_or_else(|e| e.into_inner());
+    handle_uci_command("position startpos moves e2e4 e7e5");
+    // Answered later, not now.
+    assert_eq!(handle_uci_command("go ponder wtime 60000 btime 60000"), "");
+    thread::sleep(Duration::from_millis(200));
+    let response = handle_uci_command("stop");
+    let mut board = Board::new();
+    board.apply_moves_from_strings(["e2e4", "e7e5"].iter().map(|s| s.to_string()));
+    parse_bestmove(&mut board, &response);
+}
+
+#[test]
+fn test_stop_right_after_go_ponder_still_answers() {
+    let _uci = UCI.lock().unwrap_or_else(|e| e.into_inner());
+    handle_uci_command("position startpos");
+    assert_eq!(handle_uci_command("go ponder"), "");
+    let response = handle_uci_command("stop");
+    parse_bestmove(&mut Board::new(), &response);
+}
+
+#[test]
+fn test_ponderhit_switches_to_clock_and_reports_once() {
+    let _uci = UCI.lock().unwrap_or_else(|e| e.into_inner());
+    handle_uci_command("position startpos");
+    // 3s on the clock → a 100ms budget once the ponder is a hit.
+    assert_eq!(handle_uci_command("go ponder wtime 3000 btime 3000"), "");
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(handle_uci_command("ponderhit"), "");
+
+    // The search finishes on its own and prints bestmove itself, so a
+    // later stop has nothing left to report.
+    thread::sleep(Duration::from_millis(1500));
+    assert_eq!(handle_uci_command("stop"), "");
+    assert_eq!(handle_uci_command("isready"), "readyok");
+}
+
+#[test]
+fn test_go_infinite_runs_until_stop() {
+    let _uci = UCI.lock().unwrap_or_else(|e| e.into_inner());
+    handle_uci_command("position startpos");
+    assert_eq!(handle_uci_command("go infinite"), "");
+    thread::sleep(Duration::from_millis(100));
+    let response = handle_uci_command("stop");
+    parse_bestmove(&mut Board::new(), &response);
+    // Nothing left running.
+    assert_eq!(handle_uci_command("stop"), "calculation stopped");
+}
+
+#[test]
+fn test_new_go_replaces_a_running_ponder() {
+    let _uci = UCI.lock().unwrap_or_else(|e| e.into_inner());
+    handle_uci_command("position startpos");
+    assert_eq!(handle_uci_command("go ponder"), "");
+    let response = handle_uci_command("go depth 2");
+    parse_bestmove(&mut Board::new(), &response);
+}
+
+#[test]
+fn test_commands_that_cut_a_ponder_short_still_answer_it() {
+    let _uci = UCI.lock().unwrap_or_else(|e| e.into_inner());
+    handle_uci_command("position startpos");
+    assert_eq!(handle_uci_command("go ponder"), "");
+    let response = handle_uci_command("position startpos moves e2e4");
+    let (bestmove, rest) = response.split_once('\n').unwrap();
+    parse_bestmove(&mut Board::new(), bestmove);
+    assert_eq!(rest, "position set");
+
+    assert_eq!(handle_uci_command("go infinite"), "");
+    let response = handle_uci_command("setoption name MultiPV value 1");
+    let mut board = Board::new();
+    board.apply_move_from_string("e2e4");
+    parse_bestmove(&mut board, &response);
+
+    // Answered once: nothing is left for a later stop, and a command
+    // with no search to cut short answers only for itself.
+    assert_eq!(handle_uci_command("stop"), "calculation stopped");
+    assert_eq!(handle_uci_command("position startpos"), "position set");
+    assert_eq!(handle_uci_command("setoption name MultiPV value 1"), "");
+}
+
+#[test]
+fn test_handle_stops_an_unlimited_search() {
+    let mut searcher = Searcher::new_deterministic();
+    let handle = searcher.handle();
+    let stopper = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(200));
+        handle.stop();
+    });
+    let mut board = Board::new();
+    let result = searcher.search(&mut board, SearchLimits::default(), 1);
+    stopper.join().unwrap();
+    assert!(board.is_legal_move(&result.best_move().unwrap()));
+    assert!(result.depth >= 1);
+    assert_eq!(board, Board::new());
+}
+
+#[test]
+fn test_handle_time_budget_ends_an_unlimited_search() {
+    let mut searcher = Searcher::new_deterministic();
+    let handle = searcher.handle();
+    let started = Instant::now();
+    let ponderhit = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        handle.set_time_budget(Duration::from_millis(100));
+    });
+    let mut board = Board::new();
+    let result = searcher.search(&mut board, SearchLimits::default(), 1);
+    ponderhit.join().unwrap();
+    assert!(result.best_move().is_some());
+    // Hard limit is twice the budget; leave generous slack for slow CI.
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_stop_before_search_starts_is_honoured() {
+    let mut searcher = Searcher::new_deterministic();
+    searcher.handle().stop();
+    let mut board = Board::new();
+    let result = searcher.search(&mut board, SearchLimits::default(), 1);
+    // Even a search stopped at once plays a legal move...
+    assert!(board.is_legal_move(&result.best_move().unwrap()));
+    // ...and the request doesn't leak into the next search.
+    let limits = SearchLimits {
+        depth: Some(2),
+        ..SearchLimits::default()
+    };
+    assert_eq!(searcher.search(&mut board, limits, 1).depth, 2);
+}
@@ -0,0 +1,183 @@
+//! Shallow-depth pruning (reverse futility, razoring, futility,
+//! late-move pruning) and reductions must only skip work that can't
+//! matter. The mate positions put the pruning conditions under the
+//! most pressure: lopsided material, so the static eval says
+//! "hopeless", with a mate that only a quiet checking move near the
+//! horizon finds.
+
+use chesslib::fen::load_fen;
+use chesslib::search::{SearchFeatures, SearchLimits, SearchResult, Searcher, MATE_SCORE};
+
+#[test]
+fn test_smothered_mate_found_despite_material_deficit() {
+    // White is two queens and a rook down, but 1.Qg8+ Rxg8 2.Nf7# is
+    // mate. 2.Nf7 is a quiet move at a node whose static eval is far
+    // below alpha — exactly what futility pruning skips, unless it
+    // respects the "gives check" exemption.
+    let mut board = load_fen("3r3k/6pp/7N/qq6/2Q5/8/8/6K1 w - - 0 1").unwrap();
+    let (best, score) = Searcher::new_deterministic().find_best_move(&mut board, 3);
+    assert_eq!(best.unwrap().to_string(), "c4g8");
+    assert_eq!(score, MATE_SCORE - 3);
+}
+
+#[test]
+fn test_quiet_mate_in_two_found() {
+    // Rook ladder: 1.Rb7 (quiet, no check) and 2.Ra8#. Neither the
+    // quiet key move nor the mate may be pruned away.
+    let mut board = load_fen("7k/8/8/8/8/8/1R6/R5K1 w - - 0 1").unwrap();
+    let (_, score) = Searcher::new_deterministic().find_best_move(&mut board, 3);
+    assert_eq!(score, MATE_SCORE - 3);
+}
+
+#[test]
+fn test_node_count_is_per_search_and_reproducible() {
+    let fen = "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+    let mut board = load_fen(fen).unwrap();
+    let mut searcher = Searcher::new_deterministic();
+    searcher.find_best_move(&mut board, 3);
+    let deep = searcher.nodes();
+    assert!(deep > 0);
+
+    // The next search starts counting from zero.
+    searcher.find_best_move(&mut board, 1);
+    assert!(searcher.nodes() < deep);
+
+    // Deterministic searches from the same starting state agree.
+    let mut fresh = Searcher::new_deterministic();
+    fresh.find_best_move(&mut board, 3);
+    assert_eq!(fresh.nodes(), deep);
+}
+
+#[test]
+fn test_reductions_without_hash_move_keep_iterative_result() {
+    // A fresh table means no node below the root has a hash move on
+    // the first visit, so internal iterative reduction fires all over
+    // the tree. Iterative deepening must still land on the winning
+    // capture of the undefended queen.
+    let mut board = load_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+    let limits = SearchLimits {
+        depth: Some(5),
+        ..SearchLimits::default()
+    };
+    let result = Searcher::new_deterministic().search(&mut board, limits, 1);
+    assert_eq!(result.best_move().unwrap().to_string(), "d2d5");
+}
+
+/// An endgame with rooks and pawns on both sides, where every pruning
+/// fires and each one pays for itself, searched to depth 5.
+fn endgame_search(features: SearchFeatures) -> (SearchResult, u64) {
+    let mut board = load_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 11").unwrap();
+    let mut searcher = Searcher::new_deterministic();
+    searcher.set_features(features);
+    let limits = SearchLimits {
+        depth: Some(5),
+        ..SearchLimits::default()
+    };
+    let result = searcher.search(&mut board, limits, 1);
+    let nodes = searcher.nodes();
+    (result, nodes)
+}
+
+#[test]
+fn test_each_pruning_saves_nodes() {
+    let (_, with_all) = endgame_search(SearchFeatures::default());
+    let without = |features| endgame_search(features).1;
+    let cases = [
+        (
+            "reverse futility",
+            SearchFeatures {
+                reverse_futility: false,
+                ..SearchFeatures::default()
+            },
+        ),
+        (
+            "razoring",
+            SearchFeatures {
+                razoring: false,
+                ..SearchFeatures::default()
+            },
+        ),
+        (
+            "futility",
+            SearchFeatures {
+                futility: false,
+                ..SearchFeatures::default()
+            },
+        ),
+        (
+            "late-move pruning",
+            SearchFeatures {
+                late_move_pruning: false,
+                ..SearchFeatures::default()
+            },
+        ),
+    ];
+    for (name, features) in cases {
+        let nodes = without(features);
+        assert!(with_all < nodes, "{name}: {with_all} with, {nodes} without");
+    }
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_pruning_counters_follow_the_switches() {
+    let (on, _) = endgame_search(SearchFeatures::default());
+    assert!(on.stats.rfp_cutoffs > 0);
+    assert!(on.stats.razor_cutoffs > 0);
+    assert!(on.stats.pruned_moves > 0);
+
+    let (off, _) = endgame_search(SearchFeatures {
+        reverse_futility: false,
+        razoring: false,
+        ..SearchFeatures::default()
+    });
+    assert_eq!(off.stats.rfp_cutoffs, 0);
+    assert_eq!(off.stats.razor_cutoffs, 0);
+
+    // Futility and late-move pruning share a counter.
+    let (off, _) = endgame_search(SearchFeatures {
+        futility: false,
+        late_move_pruning: false,
+        ..SearchFeatures::default()
+    });
+    assert_eq!(off.stats.pruned_moves, 0);
+}
+
+#[test]
+fn test_iir_reduces_only_iterative_searches() {
+    // A rook race against a pawn about to queen: enough nodes without
+    // a hash move at depth 6 for the reduction to show.
+    let fen = "8/R7/2q5/8/6k1/8/1P5p/K6R w - - 0 124";
+    let no_iir = SearchFeatures {
+        internal_iterative_reduction: false,
+        ..SearchFeatures::default()
+    };
+    let iterative = |features| {
+        let mut board = load_fen(fen).unwrap();
+        let mut searcher = Searcher::new_deterministic();
+        searcher.set_features(features);
+        let limits = SearchLimits {
+            depth: Some(6),
+            ..SearchLimits::default()
+        };
+        searcher.search(&mut board, limits, 1);
+        searcher.nodes()
+    };
+    let with_iir = iterative(SearchFeatures::default());
+    let without_iir = iterative(no_iir);
+    assert!(
+        with_iir < without_iir,
+        "{with_iir} with, {without_iir} without"
+    );
+
+    // A single fixed-depth search has no next iteration to come back
+    // to a reduced node, so it searches the same tree either way.
+    let fixed_depth = |features| {
+        let mut board = load_fen(fen).unwrap();
+        let mut searcher = Searcher::new_deterministic();
+        searcher.set_features(features);
+        let found = searcher.find_best_move(&mut board, 6);
+        (found, searcher.nodes())
+    };
+    assert_eq!(fixed_depth(SearchFeatures::default()), fixed_depth(no_iir));
+}
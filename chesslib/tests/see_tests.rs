@@ -0,0 +1,108 @@
+use chesslib::fen::load_fen;
+use chesslib::types::{Color, Move, KNIGHT_VALUE, PAWN_VALUE, QUEEN_VALUE, ROOK_VALUE};
+use chesslib::Square;
+
+fn mv(s: &str) -> Move {
+    Move::try_from(s).unwrap()
+}
+
+#[test]
+fn test_attackers_to_reports_both_colours() {
+    // The black pawn on e4 is attacked by the white pawn on d3 and the
+    // white knight on f2, and defended by the black pawn on d5.
+    let board = load_fen("4k3/8/8/3p4/4p3/3P4/5N2/4K3 w - - 0 1").unwrap();
+    let occupied = board.any_white() | board.any_black();
+    let attackers = board.attackers_to(Square::E4.to_bit_index(), occupied);
+    assert_eq!(
+        attackers,
+        Square::D3.to_bitboard() | Square::F2.to_bitboard() | Square::D5.to_bitboard()
+    );
+}
+
+#[test]
+fn test_attackers_to_respects_reduced_occupancy() {
+    // Doubled white rooks on the e-file: only the front one attacks e5
+    // until it is removed from the occupancy, then the x-ray appears.
+    let board = load_fen("4k3/8/8/4p3/8/8/4R3/4RK2 w - - 0 1").unwrap();
+    let occupied = board.any_white() | board.any_black();
+    let e5 = Square::E5.to_bit_index();
+    assert_eq!(board.attackers_to(e5, occupied), Square::E2.to_bitboard());
+    assert_eq!(
+        board.attackers_to(e5, occupied ^ Square::E2.to_bitboard()),
+        Square::E1.to_bitboard()
+    );
+}
+
+#[test]
+fn test_see_undefended_capture_wins_the_victim() {
+    let board = load_fen("4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.see(&mv("e4d5")), KNIGHT_VALUE);
+}
+
+#[test]
+fn test_see_queen_takes_pawn_defended_by_pawn_loses() {
+    // QxP, PxQ: white wins a pawn and gives up the queen.
+    let board = load_fen("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.see(&mv("d2d5")), PAWN_VALUE - QUEEN_VALUE);
+    assert!(!board.see_ge(&mv("d2d5"), 0));
+}
+
+#[test]
+fn test_see_counts_xray_attackers() {
+    // RxP, RxR, RxR. Without the x-ray through e2 black's recapture
+    // would look like it wins a rook.
+    let board = load_fen("4r1k1/8/8/4p3/8/8/4R3/4RK2 w - - 0 1").unwrap();
+    assert_eq!(board.see(&mv("e2e5")), PAWN_VALUE);
+}
+
+#[test]
+fn test_see_side_may_decline_to_recapture() {
+    // PxP, and black's only recapture is QxP which would lose the
+    // queen to the rook behind. Black stops, white is a pawn up.
+    let board = load_fen("4k3/8/8/3q4/4p3/3P4/8/4RK2 w - - 0 1").unwrap();
+    assert_eq!(board.see(&mv("d3e4")), PAWN_VALUE);
+}
+
+#[test]
+fn test_see_king_does_not_recapture_into_defended_square() {
+    // RxP next to the black king, but the square is covered by the
+    // bishop on b3, so KxR is illegal and the rook survives.
+    let board = load_fen("6k1/5p2/8/8/8/1B6/8/5RK1 w - - 0 1").unwrap();
+    assert_eq!(board.see(&mv("f1f7")), PAWN_VALUE);
+
+    // Same capture without the bishop: the king takes the rook back.
+    let board = load_fen("6k1/5p2/8/8/8/8/8/5RK1 w - - 0 1").unwrap();
+    assert_eq!(board.see(&mv("f1f7")), PAWN_VALUE - ROOK_VALUE);
+}
+
+#[test]
+fn test_see_en_passant_capture() {
+    let board = load_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    assert_eq!(board.see(&mv("e5d6")), PAWN_VALUE);
+}
+
+#[test]
+fn test_see_quiet_move_onto_attacked_square() {
+    // Nc3-d5 walks into a pawn capture; Nc3-b5 is safe.
+    let board = load_fen("4k3/8/4p3/8/8/2N5/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.see(&mv("c3d5")), -KNIGHT_VALUE);
+    assert_eq!(board.see(&mv("c3b5")), 0);
+}
+
+#[test]
+fn test_see_ge_thresholds() {
+    let board = load_fen("4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1").unwrap();
+    assert!(board.see_ge(&mv("e4d5"), 0));
+    assert!(board.see_ge(&mv("e4d5"), KNIGHT_VALUE));
+    assert!(!board.see_ge(&mv("e4d5"), KNIGHT_VALUE + 1));
+}
+
+#[test]
+fn test_hanging_pieces() {
+    // The white knight on d4 is attacked by the e5 pawn; c3 recaptures
+    // but PxN, PxP still nets black two pawns' worth. The c3 pawn is
+    // attacked by the bishop on a5 but BxP, PxB loses, so it's safe.
+    let board = load_fen("4k3/8/8/b3p3/3N4/2P5/1P6/4K3 b - - 0 1").unwrap();
+    assert_eq!(board.hanging_pieces(Color::White), Square::D4.to_bitboard());
+    assert_eq!(board.hanging_pieces(Color::Black), 0);
+}
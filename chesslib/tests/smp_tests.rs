@@ -0,0 +1,81 @@
+use chesslib::board::Board;
+use chesslib::fen::load_fen;
+use chesslib::search::{Score, Searcher, MAX_THREADS};
+use chesslib::types::Color;
+use std::time::Duration;
+
+fn pov(board: &Board, white_pov: i64) -> i64 {
+    if board.side_to_move == Color::Black {
+        -white_pov
+    } else {
+        white_pov
+    }
+}
+
+#[test]
+fn test_deterministic_mode_forces_one_thread() {
+    let mut searcher = Searcher::new_deterministic();
+    searcher.set_threads(4);
+    assert_eq!(searcher.threads(), 1);
+}
+
+#[test]
+fn test_set_threads_clamps() {
+    // Searcher::new() honours CHESS_DETERMINISTIC, which pins threads
+    // to one; nothing to check in that configuration.
+    if std::env::var("CHESS_DETERMINISTIC").is_ok() {
+        return;
+    }
+    let mut searcher = Searcher::new();
+    searcher.set_threads(0);
+    assert_eq!(searcher.threads(), 1);
+    searcher.set_threads(MAX_THREADS + 10);
+    assert_eq!(searcher.threads(), MAX_THREADS);
+}
+
+#[test]
+fn test_multithreaded_search_finds_mate_in_two() {
+    let mut board = load_fen("1r4k1/5ppp/8/8/8/8/3R1PPP/3R2K1 w - - 0 1").unwrap();
+    let mut searcher = Searcher::new();
+    searcher.set_threads(4);
+    let (mv, score) = searcher.find_best_move(&mut board, 4);
+    assert_eq!(mv.unwrap().to_string(), "d2d8");
+    assert_eq!(Score::from_raw(pov(&board, score)), Score::MateIn(2));
+}
+
+#[test]
+fn test_multithreaded_search_leaves_board_untouched() {
+    let mut board = Board::new();
+    board.apply_move_from_string("e2e4");
+    let before = board.clone();
+    let mut searcher = Searcher::new();
+    searcher.set_threads(3);
+    let (mv, _score, depth) =
+        searcher.find_best_move_within(&mut board, Duration::from_millis(300));
+    assert!(depth >= 1);
+    assert!(board.clone().is_legal_move(&mv.unwrap()));
+    assert_eq!(board, before);
+    assert_eq!(board.hash, before.hash);
+}
+
+#[test]
+fn test_helpers_are_reused_across_searches() {
+    // Two back-to-back searches on the same Searcher: the second runs
+    // with the helpers (and table) from the first.
+    let mut searcher = Searcher::new();
+    searcher.set_threads(2);
+    let mut board = Board::new();
+    let first = searcher.find_best_move(&mut board, 3).0.unwrap();
+    board.apply_move(&first);
+    let second = searcher.find_best_move(&mut board, 3).0.unwrap();
+    assert!(board.is_legal_move(&second));
+}
+
+#[test]
+fn test_multithreaded_multipv() {
+    let mut board = Board::new();
+    let mut searcher = Searcher::new();
+    searcher.set_threads(2);
+    let lines = searcher.find_best_moves_multipv(&mut board, 3, 3);
+    assert_eq!(lines.len(), 3);
+}
@@ -0,0 +1,108 @@
+use chesslib::board::Board;
+use chesslib::search::{SearchLimits, Searcher};
+use chesslib::stats::SearchStats;
+use std::time::Duration;
+
+fn search_startpos(depth: i32) -> (Searcher, chesslib::search::SearchResult) {
+    let mut board = Board::new();
+    let mut searcher = Searcher::new_deterministic();
+    let limits = SearchLimits {
+        depth: Some(depth),
+        ..SearchLimits::default()
+    };
+    let result = searcher.search(&mut board, limits, 1);
+    (searcher, result)
+}
+
+#[test]
+fn test_node_count_always_reported() {
+    let (searcher, result) = search_startpos(3);
+    assert_eq!(result.stats.nodes, searcher.nodes());
+    assert_eq!(searcher.stats().nodes, searcher.nodes());
+    assert!(result.stats.nodes > 0);
+}
+
+#[test]
+fn test_with_stats_variants_return_the_searchs_stats() {
+    let mut board = Board::new();
+    let mut searcher = Searcher::new_deterministic();
+    let (best, score, stats) = searcher.find_best_move_with_stats(&mut board, 3);
+    assert_eq!(stats, searcher.stats());
+    assert_eq!(stats.nodes, searcher.nodes());
+    let plain = Searcher::new_deterministic().find_best_move(&mut board, 3);
+    assert_eq!((best, score), plain);
+
+    let (lines, stats) = searcher.find_best_moves_multipv_with_stats(&mut board, 2, 3);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(stats, searcher.stats());
+
+    let budget = Duration::from_millis(50);
+    let (best, _, depth, stats) = searcher.find_best_move_within_with_stats(&mut board, budget);
+    assert!(best.is_some() && depth >= 1);
+    assert_eq!(stats, searcher.stats());
+
+    let (lines, _, stats) =
+        searcher.find_best_moves_multipv_within_with_stats(&mut board, budget, 2);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(stats, searcher.stats());
+    assert!(stats.nodes > 0);
+}
+
+#[test]
+fn test_stats_match_feature_flag() {
+    assert_eq!(SearchStats::enabled(), cfg!(feature = "stats"));
+}
+
+#[cfg(not(feature = "stats"))]
+#[test]
+fn test_counters_stay_zero_without_feature() {
+    let (_, result) = search_startpos(3);
+    let stats = result.stats;
+    assert_eq!(stats.qnodes, 0);
+    assert_eq!(stats.tt_probes, 0);
+    assert_eq!(stats.beta_cutoffs, 0);
+    assert!(stats.depths.is_empty());
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_counters_are_consistent() {
+    let (_, result) = search_startpos(4);
+    let stats = result.stats;
+    assert!(stats.qnodes > 0 && stats.qnodes < stats.nodes);
+    assert!(stats.tt_hits > 0 && stats.tt_hits <= stats.tt_probes);
+    assert!(stats.first_move_cutoffs > 0 && stats.first_move_cutoffs <= stats.beta_cutoffs);
+    assert!(stats.researches <= stats.null_window_searches);
+    assert!((0.0..=1.0).contains(&stats.first_move_cutoff_rate()));
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_one_depth_entry_per_iteration() {
+    let (_, result) = search_startpos(4);
+    let depths = &result.stats.depths;
+    assert_eq!(depths.len(), 4);
+    for (i, d) in depths.iter().enumerate() {
+        assert_eq!(d.depth, i as i32 + 1);
+        assert!(d.nodes > 0);
+    }
+    assert_eq!(depths[0].branching_factor, 0.0);
+    let ebf = depths[3].nodes as f64 / depths[2].nodes as f64;
+    assert_eq!(depths[3].branching_factor, ebf);
+    // Every node belongs to some iteration.
+    let per_depth: u64 = depths.iter().map(|d| d.nodes).sum();
+    assert_eq!(per_depth, result.stats.nodes);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_stats_reset_between_searches() {
+    let mut board = Board::new();
+    let mut searcher = Searcher::new_deterministic();
+    searcher.find_best_move(&mut board, 4);
+    let first = searcher.stats();
+    searcher.find_best_move(&mut board, 1);
+    let second = searcher.stats();
+    assert!(second.nodes < first.nodes);
+    assert_eq!(second.depths.len(), 1);
+}
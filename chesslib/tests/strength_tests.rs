@@ -0,0 +1,134 @@
+use chesslib::board::Board;
+use chesslib::fen::load_fen;
+use chesslib::search::{SearchLimits, Searcher};
+use chesslib::strength::{Strength, MAX_ELO, MAX_SKILL_LEVEL, MIN_ELO};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn test_elo_maps_onto_skill_levels() {
+    assert_eq!(Strength::from_elo(MIN_ELO).level(), 0);
+    assert_eq!(Strength::from_elo(0).level(), 0);
+    assert_eq!(Strength::from_elo(1500).level(), 10);
+    assert_eq!(Strength::from_elo(MAX_ELO).level(), MAX_SKILL_LEVEL);
+    assert_eq!(Strength::from_elo(9999).level(), MAX_SKILL_LEVEL);
+    assert_eq!(Strength::from_level(99).level(), MAX_SKILL_LEVEL);
+}
+
+#[test]
+fn test_weaker_levels_search_less() {
+    let weak = Strength::from_level(0);
+    let strong = Strength::from_level(16);
+    assert!(weak.max_depth() < strong.max_depth());
+    assert!(weak.max_nodes() < strong.max_nodes());
+    assert!(weak.temperature() > strong.temperature());
+    assert!(weak.max_loss() > strong.max_loss());
+}
+
+#[test]
+fn test_limit_only_tightens() {
+    let strength = Strength::from_level(6); // depth 4
+    let unlimited = strength.limit(SearchLimits::default());
+    assert_eq!(unlimited.depth, Some(4));
+    assert_eq!(unlimited.nodes, Some(strength.max_nodes()));
+
+    let shallow = SearchLimits {
+        depth: Some(2),
+        nodes: Some(10),
+        ..SearchLimits::default()
+    };
+    assert_eq!(strength.limit(shallow), shallow);
+}
+
+#[test]
+fn test_top_level_always_plays_the_best_move() {
+    let strength = Strength::from_level(MAX_SKILL_LEVEL);
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..100 {
+        assert_eq!(strength.choose(&[50, 45, 40, 35], &mut rng), 0);
+    }
+}
+
+#[test]
+fn test_choice_is_weighted_toward_better_moves() {
+    let strength = Strength::from_level(0);
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut counts = [0; 4];
+    for _ in 0..2000 {
+        counts[strength.choose(&[100, 60, 0, -200], &mut rng)] += 1;
+    }
+    // Every candidate within reach gets played sometimes, and a
+    // smaller loss is always likelier than a bigger one.
+    assert!(counts.iter().all(|&c| c > 0), "{counts:?}");
+    assert!(counts[0] > counts[1] && counts[1] > counts[2] && counts[2] > counts[3]);
+}
+
+#[test]
+fn test_choice_never_exceeds_the_loss_cap() {
+    // Level 15 gives up at most 150 cp: the 300 cp blunder is out.
+    let strength = Strength::from_level(15);
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..1000 {
+        assert!(strength.choose(&[100, 20, -200], &mut rng) < 2);
+    }
+}
+
+#[test]
+fn test_limited_search_is_reproducible_when_deterministic() {
+    let mut board = Board::new();
+    board.apply_move_from_string("e2e4");
+    let moves: Vec<_> = (0..2)
+        .map(|_| {
+            let mut searcher = Searcher::new_deterministic();
+            searcher.set_strength(Some(Strength::from_level(2)));
+            searcher
+                .search(&mut board, SearchLimits::default(), 1)
+                .best_move()
+        })
+        .collect();
+    assert_eq!(moves[0], moves[1]);
+    assert!(board.is_legal_move(&moves[0].unwrap()));
+}
+
+#[test]
+fn test_limited_search_respects_its_caps() {
+    let mut board =
+        load_fen("r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4").unwrap();
+    let strength = Strength::from_level(4);
+    let mut searcher = Searcher::new_deterministic();
+    searcher.set_strength(Some(strength));
+    let result = searcher.search(&mut board, SearchLimits::default(), 1);
+    // One line reported, however many candidates were searched.
+    assert_eq!(result.lines.len(), 1);
+    assert!(result.depth <= strength.max_depth());
+    // Quiescence finishes the node it's in, so allow some slack.
+    assert!(searcher.nodes() < 2 * strength.max_nodes());
+
+    searcher.set_strength(None);
+    assert_eq!(searcher.strength(), None);
+}
+
+#[test]
+fn test_limited_search_of_a_mated_root_has_no_move() {
+    // Fool's mate: White is checkmated, so there's nothing to pick.
+    let mut board =
+        load_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+    let mut searcher = Searcher::new_deterministic();
+    searcher.set_strength(Some(Strength::from_level(5)));
+    let result = searcher.search(&mut board, SearchLimits::default(), 1);
+    assert!(result.lines.is_empty());
+    assert_eq!(result.best_move(), None);
+}
+
+#[test]
+fn test_node_limit_stops_the_search() {
+    let mut board = Board::new();
+    let mut searcher = Searcher::new_deterministic();
+    let limits = SearchLimits {
+        nodes: Some(3000),
+        ..SearchLimits::default()
+    };
+    let result = searcher.search(&mut board, limits, 1);
+    assert!(result.best_move().is_some());
+    assert!(searcher.nodes() < 6000);
+}
@@ -0,0 +1,344 @@
+use chesslib::board::Board;
+use chesslib::fen::load_fen;
+use chesslib::kpk;
+use chesslib::search::{Searcher, TB_WIN_SCORE};
+use chesslib::syzygy::{self, Tablebases, DRAW, LOSS, WIN};
+use chesslib::tablebase;
+use chesslib::types::{Color, Move};
+use std::path::PathBuf;
+
+/// Tables written by `tbgen KQvK KRvK KBvK KNvK KPvK`.
+const TABLES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/syzygy");
+
+fn tables() -> Tablebases {
+    Tablebases::load(TABLES).unwrap()
+}
+
+fn moves(board: &mut Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    board.get_all_raw_moves_append(&mut moves);
+    moves
+}
+
+fn in_check(board: &Board, color: Color) -> bool {
+    match color {
+        Color::White => board.white_king_in_check,
+        Color::Black => board.black_king_in_check,
+    }
+}
+
+/// Legal positions with `pieces` (FEN letters) on random squares, the
+/// same ones every run.
+fn positions(pieces: &str, count: usize) -> Vec<Board> {
+    let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut next = move |n: u64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed % n
+    };
+    let mut boards = Vec::new();
+    while boards.len() < count {
+        let mut squares = [None; 64];
+        for piece in pieces.chars() {
+            loop {
+                let sq = next(64) as usize;
+                let back_rank = !(8..56).contains(&sq);
+                if squares[sq].is_none() && !(piece.eq_ignore_ascii_case(&'p') && back_rank) {
+                    squares[sq] = Some(piece);
+                    break;
+                }
+            }
+        }
+        let mut fen = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match squares[rank * 8 + file] {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece);
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+        fen.push_str(if next(2) == 0 {
+            " w - - 0 1"
+        } else {
+            " b - - 0 1"
+        });
+        let board = load_fen(&fen).unwrap();
+        if !in_check(&board, board.side_to_move.opponent()) {
+            boards.push(board);
+        }
+    }
+    boards
+}
+
+/// The WDL result one move should have, from the moves themselves.
+fn wdl_by_search(tb: &Tablebases, board: &mut Board) -> i32 {
+    let moves = moves(board);
+    if moves.is_empty() {
+        return if in_check(board, board.side_to_move) {
+            LOSS
+        } else {
+            DRAW
+        };
+    }
+    let mut best = LOSS;
+    for mv in &moves {
+        board.apply_move(mv);
+        best = best.max(-tb.probe_wdl(board).unwrap());
+        board.undo_last_move();
+    }
+    best
+}
+
+/// DTZ from the root of playing `mv`: 1 or -1 for a capture, pawn
+/// move or mate that keeps the result, else one more ply than the
+/// DTZ after it.
+fn dtz_of_move(tb: &Tablebases, board: &mut Board, mv: &Move) -> i32 {
+    board.apply_move(mv);
+    let wdl = -tb.probe_wdl(board).unwrap();
+    // The boards start with a clock of 0: only zeroing moves leave it
+    // there.
+    let dtz = if board.halfmove_clock == 0 || moves(board).is_empty() {
+        wdl.signum()
+    } else {
+        let dtz = -tb.probe_dtz(board).unwrap();
+        dtz + dtz.signum()
+    };
+    board.undo_last_move();
+    dtz
+}
+
+/// The DTZ one move should have, from the moves themselves: the
+/// quickest win, or the slowest loss.
+fn dtz_by_search(tb: &Tablebases, board: &mut Board) -> i32 {
+    let wdl = tb.probe_wdl(board).unwrap();
+    moves(board)
+        .iter()
+        .map(|mv| dtz_of_move(tb, board, mv))
+        .filter(|dtz| dtz.signum() == wdl.signum())
+        .min()
+        // No moves: mated.
+        .unwrap_or(-1)
+}
+
+#[test]
+fn test_load_finds_the_tables_and_rejects_impostors() {
+    let tb = tables();
+    assert_eq!(tb.len(), 5);
+    assert_eq!(
+        tb.signatures().collect::<Vec<_>>(),
+        ["KBvK", "KNvK", "KPvK", "KQvK", "KRvK"]
+    );
+    assert_eq!(tb.max_pieces(), 3);
+    assert!(tb.rejected.is_empty(), "{:?}", tb.rejected);
+
+    assert!(Tablebases::load("").unwrap().is_empty());
+    assert!(Tablebases::load("<empty>").unwrap().is_empty());
+    assert!(Tablebases::load("/no/such/syzygy/dir").is_err());
+
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("syzygy_impostors_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("KQvK.rtbw"), b"not a table").unwrap();
+    std::fs::write(dir.join("KXvK.rtbw"), b"not a table").unwrap();
+    std::fs::write(dir.join("notes.txt"), b"ignored").unwrap();
+    std::fs::copy(format!("{TABLES}/KRvK.rtbw"), dir.join("KRvK.rtbw")).unwrap();
+    let tb = Tablebases::load(&dir.to_string_lossy()).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(tb.signatures().collect::<Vec<_>>(), ["KRvK"]);
+    assert_eq!(tb.rejected.len(), 2, "{:?}", tb.rejected);
+    assert!(tb.rejected.iter().any(|r| r.contains("KQvK.rtbw")));
+    assert!(tb.rejected.iter().any(|r| r.contains("KXvK.rtbw")));
+}
+
+#[test]
+fn test_textbook_results() {
+    let tb = tables();
+    let wdl = |fen: &str| tb.probe_wdl(&mut load_fen(fen).unwrap());
+    let dtz = |fen: &str| tb.probe_dtz(&mut load_fen(fen).unwrap());
+
+    // Mate in one, and mated.
+    assert_eq!(wdl("k7/8/1K6/8/8/8/8/3Q4 w - - 0 1"), Some(WIN));
+    assert_eq!(dtz("k7/8/1K6/8/8/8/8/3Q4 w - - 0 1"), Some(1));
+    assert_eq!(wdl("k7/1Q6/1K6/8/8/8/8/8 b - - 0 1"), Some(LOSS));
+    assert_eq!(dtz("k7/1Q6/1K6/8/8/8/8/8 b - - 0 1"), Some(-1));
+    // Stalemate.
+    assert_eq!(wdl("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1"), Some(DRAW));
+    // The rook hangs.
+    assert_eq!(wdl("8/8/8/8/8/2k5/2R5/7K b - - 0 1"), Some(DRAW));
+    assert_eq!(dtz("8/8/8/8/8/2k5/2R5/7K b - - 0 1"), Some(0));
+    // Colours reversed: the tables store White as the stronger side.
+    assert_eq!(wdl("4q3/8/8/8/8/1k6/8/K7 b - - 0 1"), Some(WIN));
+    assert_eq!(dtz("4q3/8/8/8/8/1k6/8/K7 b - - 0 1"), Some(1));
+    // Minor pieces alone can't win.
+    assert_eq!(wdl("8/8/3k4/8/8/2B5/8/4K3 w - - 0 1"), Some(DRAW));
+    assert_eq!(wdl("8/8/3k4/8/8/2n5/8/4K3 b - - 0 1"), Some(DRAW));
+    // A pawn move is a zeroing move of its own.
+    assert_eq!(dtz("7k/8/8/P7/8/8/8/7K w - - 0 1"), Some(1));
+    assert_eq!(dtz("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1"), Some(0));
+}
+
+#[test]
+fn test_results_agree_with_the_moves() {
+    let tb = tables();
+    for pieces in ["KQk", "KRk", "kqK", "KPk", "kpK", "KBk"] {
+        for mut board in positions(pieces, 300) {
+            let wdl = tb.probe_wdl(&mut board).unwrap();
+            assert_eq!(wdl, wdl_by_search(&tb, &mut board), "{}", board.to_fen());
+            let dtz = tb.probe_dtz(&mut board).unwrap();
+            assert_eq!(dtz.signum(), wdl.signum(), "{}", board.to_fen());
+            if wdl != DRAW {
+                assert_eq!(dtz, dtz_by_search(&tb, &mut board), "{}", board.to_fen());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_longest_wins() {
+    let tb = tables();
+    let longest = |pieces: &str| {
+        positions(pieces, 3000)
+            .iter_mut()
+            .map(|board| tb.probe_dtz(board).unwrap().abs())
+            .max()
+            .unwrap()
+    };
+    // Mates in ten and in sixteen moves: 19 and 31 plies for the
+    // winner, a ply more for the loser to move.
+    let queen = longest("KQk");
+    assert!((15..=20).contains(&queen), "{queen}");
+    let rook = longest("KRk");
+    assert!((25..=32).contains(&rook), "{rook}");
+}
+
+#[test]
+fn test_kpk_agrees_with_the_bitbase() {
+    let tb = tables();
+    for pieces in ["KPk", "kpK"] {
+        for mut board in positions(pieces, 2000) {
+            let pawn_side = if pieces.starts_with('K') {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let wdl = tb.probe_wdl(&mut board).unwrap();
+            let pawn_side_wins = if board.side_to_move == pawn_side {
+                wdl == WIN
+            } else {
+                wdl == LOSS
+            };
+            assert_eq!(
+                Some(pawn_side_wins),
+                kpk::probe(&board),
+                "{}",
+                board.to_fen()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_root_moves_are_the_quickest_wins_and_slowest_losses() {
+    let tb = tables();
+    for mut board in positions("KRk", 200)
+        .into_iter()
+        .chain(positions("KPk", 200))
+    {
+        let moves = moves(&mut board);
+        if moves.is_empty() {
+            continue;
+        }
+        let dtz = tb.probe_dtz(&mut board).unwrap();
+        let keep = tb.root_moves(&mut board, &moves).unwrap();
+        assert!(!keep.is_empty(), "{}", board.to_fen());
+        for mv in &moves {
+            let optimal = dtz_of_move(&tb, &mut board, mv) == dtz;
+            assert_eq!(keep.contains(mv), optimal, "{} {mv}", board.to_fen());
+        }
+    }
+}
+
+#[test]
+fn test_missing_tables_give_no_answer() {
+    let tb = tables();
+    // More pieces than any table, or castling rights.
+    assert_eq!(
+        tb.probe_wdl(&mut load_fen("4k3/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap()),
+        None
+    );
+    assert_eq!(
+        tb.probe_wdl(&mut load_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap()),
+        None
+    );
+
+    // KPvK without KQvK: the pawn's own table knows its promotions,
+    // but the queen ending can't be probed, nor the promotion ranked.
+    let dir = std::env::temp_dir().join(format!("syzygy_kpk_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for ext in ["rtbw", "rtbz"] {
+        std::fs::copy(
+            format!("{TABLES}/KPvK.{ext}"),
+            dir.join(format!("KPvK.{ext}")),
+        )
+        .unwrap();
+    }
+    let tb = Tablebases::load(&dir.to_string_lossy()).unwrap();
+    let mut board = load_fen("8/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+    assert_eq!(tb.probe_wdl(&mut board), Some(WIN));
+    let moves = moves(&mut board);
+    assert_eq!(tb.root_moves(&mut board, &moves), None);
+    let mut board = load_fen("4Q3/8/8/8/8/8/k7/4K3 b - - 0 1").unwrap();
+    assert_eq!(tb.probe_wdl(&mut board), None);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// The search reads the installed tables, which every test here would
+/// share: this is the one test that installs any.
+#[test]
+fn test_installed_tables_steer_the_search() {
+    assert_eq!(syzygy::init(TABLES).unwrap().len(), 5);
+    assert_eq!(syzygy::max_pieces(), 3);
+    assert_eq!(tablebase::max_pieces(), 3);
+    let mut board = load_fen("k7/8/1K6/8/8/8/8/3Q4 w - - 0 1").unwrap();
+    assert_eq!(syzygy::probe_dtz(&mut board), Some(1));
+
+    // Taking the knight leaves a won KRvK: a table win, not a rook up.
+    let capture = "4k3/8/8/8/3n4/8/8/3RK3 w - - 0 1";
+    let (best, score) = Searcher::new().find_best_move(&mut load_fen(capture).unwrap(), 3);
+    assert_eq!(best.unwrap().to_string(), "d1d4");
+    assert_eq!(score, TB_WIN_SCORE - 1);
+
+    // In the table, only the quickest wins are searched.
+    let tb = tables();
+    for mut board in positions("KRk", 20) {
+        let moves = moves(&mut board);
+        if tb.probe_wdl(&mut board) != Some(WIN) {
+            continue;
+        }
+        let keep = tb.root_moves(&mut board, &moves).unwrap();
+        let (best, _) = Searcher::new().find_best_move(&mut board, 3);
+        let best = best.unwrap();
+        assert!(keep.contains(&best), "{} {best}", board.to_fen());
+    }
+
+    syzygy::init("").unwrap();
+    assert_eq!(syzygy::max_pieces(), 0);
+    assert_eq!(syzygy::probe_wdl(&mut board), None);
+    let (_, score) = Searcher::new().find_best_move(&mut load_fen(capture).unwrap(), 3);
+    assert!(score < 2000, "{score}");
+}
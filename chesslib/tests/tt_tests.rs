@@ -0,0 +1,73 @@
+use chesslib::tt::{Bound, TranspositionTable};
+use chesslib::types::{Move, PieceType};
+
+fn mv(s: &str) -> Move {
+    Move::try_from(s).unwrap()
+}
+
+#[test]
+fn test_store_then_probe_round_trips() {
+    let tt = TranspositionTable::new(1);
+    let key = 0xDEAD_BEEF_1234_5678;
+    tt.store(key, Some(mv("e2e4")), -137, 7, Bound::Lower);
+    let entry = tt.probe(key).unwrap();
+    assert_eq!(entry.best_move, Some(mv("e2e4")));
+    assert_eq!(entry.score, -137);
+    assert_eq!(entry.depth, 7);
+    assert_eq!(entry.bound, Bound::Lower);
+}
+
+#[test]
+fn test_probe_misses_for_other_keys() {
+    let tt = TranspositionTable::new(1);
+    assert!(tt.probe(42).is_none());
+    tt.store(42, None, 10, 3, Bound::Upper);
+    assert!(tt.probe(43).is_none());
+}
+
+#[test]
+fn test_promotion_moves_and_mate_scores_survive_packing() {
+    let tt = TranspositionTable::new(1);
+    let promo = Move {
+        promotion: Some(PieceType::Knight),
+        ..mv("a7a8")
+    };
+    tt.store(7, Some(promo), 29_990, 12, Bound::Exact);
+    let entry = tt.probe(7).unwrap();
+    assert_eq!(entry.best_move, Some(promo));
+    assert_eq!(entry.score, 29_990);
+}
+
+#[test]
+fn test_deeper_entry_from_same_search_is_kept() {
+    let tt = TranspositionTable::new(1);
+    tt.new_search();
+    tt.store(99, Some(mv("d2d4")), 50, 8, Bound::Lower);
+    tt.store(99, Some(mv("c2c4")), 20, 3, Bound::Upper);
+    assert_eq!(tt.probe(99).unwrap().depth, 8);
+
+    // A new search may overwrite it.
+    tt.new_search();
+    tt.store(99, Some(mv("c2c4")), 20, 3, Bound::Upper);
+    assert_eq!(tt.probe(99).unwrap().depth, 3);
+}
+
+#[test]
+fn test_fail_low_store_keeps_previous_move() {
+    let tt = TranspositionTable::new(1);
+    tt.store(5, Some(mv("g1f3")), 30, 2, Bound::Exact);
+    tt.store(5, None, -10, 4, Bound::Upper);
+    let entry = tt.probe(5).unwrap();
+    assert_eq!(entry.best_move, Some(mv("g1f3")));
+    assert_eq!(entry.depth, 4);
+}
+
+#[test]
+fn test_clear_and_capacity() {
+    let tt = TranspositionTable::new(1);
+    assert_eq!(tt.capacity(), 1024 * 1024 / 16);
+    tt.store(1, None, 0, 1, Bound::Exact);
+    assert!(tt.hashfull() <= 1000);
+    tt.clear();
+    assert!(tt.probe(1).is_none());
+}
@@ -0,0 +1,177 @@
+use chesslib::eval_params::EvalParams;
+use chesslib::eval_trace::EvalTerm;
+use chesslib::fen::load_fen;
+use chesslib::tuner::{
+    self, fit_k, flatten, load_training_file, mean_squared_error, parse_training_line, probes,
+    unflatten, TuneOptions, TuningPosition,
+};
+
+const DATA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+
+/// The fixture, linearised at the default weights.
+fn fixture() -> (Vec<String>, Vec<f64>, Vec<TuningPosition>) {
+    let params = EvalParams::default();
+    let (boards, _) = load_training_file(format!("{DATA}/tuning.epd")).unwrap();
+    let (names, values): (Vec<String>, Vec<i64>) = flatten(&params).into_iter().unzip();
+    let probes = probes(&params);
+    let positions = boards
+        .iter()
+        .map(|(board, result)| TuningPosition::extract(board, *result, &params, &probes))
+        .collect();
+    (names, values.iter().map(|&v| v as f64).collect(), positions)
+}
+
+#[test]
+fn test_parse_training_line_formats() {
+    let fen = "4k3/8/8/8/8/8/PPPPPPPP/4K3 w - -";
+    let epd = parse_training_line(&format!("{fen} c9 \"1-0\";"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(epd.1, 1.0);
+    assert_eq!(epd.0.to_fen(), format!("{fen} 0 1"));
+
+    for (line, expected) in [
+        (format!("{fen} 0 1;0-1"), 0.0),
+        (format!("{fen} 0 1; 1/2-1/2"), 0.5),
+        (format!("{fen} 0 1;0.5"), 0.5),
+        (format!("{fen} 0 1 [1.0]"), 1.0),
+        (format!("{fen} c9 \"1/2-1/2\";"), 0.5),
+    ] {
+        assert_eq!(
+            parse_training_line(&line).unwrap().unwrap().1,
+            expected,
+            "{line}"
+        );
+    }
+
+    assert!(parse_training_line("").is_none());
+    assert!(parse_training_line("# comment").is_none());
+    assert!(parse_training_line(&format!("{fen} 0 1")).unwrap().is_err());
+    assert!(parse_training_line(&format!("{fen} 0 1;2-0"))
+        .unwrap()
+        .is_err());
+    assert!(parse_training_line("not a fen;1-0").unwrap().is_err());
+}
+
+#[test]
+fn test_load_training_file_skips_positions_in_check() {
+    let (positions, skipped) = load_training_file(format!("{DATA}/tuning.epd")).unwrap();
+    assert_eq!(positions.len(), 21);
+    assert_eq!(skipped, 1);
+    assert!(load_training_file(format!("{DATA}/missing.epd")).is_err());
+}
+
+#[test]
+fn test_flatten_round_trips_through_unflatten() {
+    let params = EvalParams::default();
+    let flat = flatten(&params);
+    assert!(flat.contains(&("material.knight".to_string(), params.material.knight)));
+    assert!(flat.contains(&("pawns.passed.6.eg".to_string(), params.pawns.passed[6].eg)));
+    assert!(flat
+        .iter()
+        .all(|(name, _)| !name.starts_with("king_safety.attack_weight")));
+
+    let (names, values): (Vec<String>, Vec<i64>) = flat.into_iter().unzip();
+    let mut weights: Vec<f64> = values.iter().map(|&v| v as f64).collect();
+    assert_eq!(unflatten(&params, &names, &weights), params);
+
+    let knight = names.iter().position(|n| n == "material.knight").unwrap();
+    weights[knight] = 312.4;
+    assert_eq!(unflatten(&params, &names, &weights).material.knight, 312);
+}
+
+#[test]
+fn test_linear_model_matches_evaluate() {
+    let (names, base, positions) = fixture();
+    let (boards, _) = load_training_file(format!("{DATA}/tuning.epd")).unwrap();
+
+    // Unchanged weights give the evaluation exactly, before rounding.
+    for ((board, _), position) in boards.iter().zip(&positions) {
+        let eval = position.evaluate(&base, &base);
+        let trace = board.evaluate_trace();
+        assert_eq!(position.phase, trace.phase);
+        assert!((eval - trace.score as f64).abs() <= EvalTerm::ALL.len() as f64);
+    }
+
+    // So do changed ones: the model is the evaluation, linearised.
+    let mut weights = base.clone();
+    for (name, change) in [
+        ("material.knight", 37.0),
+        ("pawns.doubled.mg", -9.0),
+        ("pieces.rook_open_file.eg", 12.0),
+        ("king_safety.shield.1", 8.0),
+        ("positional.mobility", 3.0),
+    ] {
+        weights[names.iter().position(|n| n == name).unwrap()] += change;
+    }
+    let changed = unflatten(&EvalParams::default(), &names, &weights);
+    for ((board, _), position) in boards.iter().zip(&positions) {
+        let eval = position.evaluate(&weights, &base);
+        let expected = board.evaluate_with(&changed) as f64;
+        assert!(
+            (eval - expected).abs() <= EvalTerm::ALL.len() as f64,
+            "{}: model {eval}, evaluate {expected}",
+            board.to_fen()
+        );
+    }
+}
+
+#[test]
+fn test_material_coefficient_is_the_piece_count_difference() {
+    let params = EvalParams::default();
+    let names: Vec<String> = flatten(&params).into_iter().map(|(n, _)| n).collect();
+    let knight = names.iter().position(|n| n == "material.knight").unwrap();
+    let probes = probes(&params);
+
+    let board = load_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+    let position = TuningPosition::extract(&board, 1.0, &params, &probes);
+    let &(_, mg, eg) = position
+        .coefficients
+        .iter()
+        .find(|(i, _, _)| *i == knight)
+        .unwrap();
+    assert_eq!((mg, eg), (2.0, 2.0));
+
+    // Weights that don't affect a position aren't stored for it.
+    let bare = load_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let position = TuningPosition::extract(&bare, 0.5, &params, &probes);
+    assert!(position.coefficients.iter().all(|(i, _, _)| *i != knight));
+}
+
+#[test]
+fn test_fit_k_minimises_error() {
+    let (_, base, positions) = fixture();
+    let k = fit_k(&positions, &base);
+    assert!(k > 0.0 && k < 10.0, "K = {k}");
+    let error = |k: f64| mean_squared_error(&positions, &base, &base, k);
+    assert!(error(k) <= error(k * 0.8));
+    assert!(error(k) <= error(k * 1.25));
+    assert!((tuner::sigmoid(0.0, k) - 0.5).abs() < 1e-12);
+}
+
+#[test]
+fn test_tuning_reduces_error() {
+    let (names, base, positions) = fixture();
+    let k = fit_k(&positions, &base);
+    let before = mean_squared_error(&positions, &base, &base, k);
+
+    let mut reports = Vec::new();
+    let options = TuneOptions {
+        iterations: 200,
+        learning_rate: 2.0,
+        report_every: 50,
+    };
+    let tuned = tuner::tune(&positions, &base, k, options, |iteration, error| {
+        reports.push((iteration, error));
+    });
+    assert_eq!(
+        reports.iter().map(|r| r.0).collect::<Vec<_>>(),
+        [50, 100, 150, 200]
+    );
+    let after = mean_squared_error(&positions, &tuned, &base, k);
+    assert!(after < before, "{before} -> {after}");
+
+    // The tuned weights survive being written out and read back.
+    let params = unflatten(&EvalParams::default(), &names, &tuned);
+    assert_eq!(EvalParams::from_toml(&params.to_toml()).unwrap(), params);
+}
@@ -4,9 +4,28 @@ mod tests {
 
     #[test]
     fn test_handle_uci_command() {
+        let nnue_options = if cfg!(feature = "nnue") {
+            "option name Use NNUE type check default false\n\
+             option name EvalFile type string default <empty>\n"
+        } else {
+            ""
+        };
         assert_eq!(
             handle_uci_command("uci"),
-            "id name ChessEngine\nid author YourName\nuciok"
+            format!(
+                "id name ChessEngine\nid author YourName\n\
+             option name MultiPV type spin default 1 min 1 max 256\n\
+             option name Threads type spin default 1 min 1 max 256\n\
+             option name Hash type spin default 16 min 1 max 4096\n\
+             option name Contempt type spin default 0 min -100 max 100\n\
+             option name Dynamic Contempt type check default false\n\
+             option name UCI_LimitStrength type check default false\n\
+             option name UCI_Elo type spin default 1500 min 600 max 2400\n\
+             option name Skill Level type spin default 20 min 0 max 20\n\
+             option name EvalParams type string default <empty>\n\
+             option name SyzygyPath type string default <empty>\n\
+             {nnue_options}uciok"
+            )
         );
         assert_eq!(handle_uci_command("isready"), "readyok");
         assert_eq!(handle_uci_command("quit"), "");
@@ -124,4 +143,113 @@ mod tests {
         let move_response = handle_uci_command("go");
         assert!(move_response.starts_with("bestmove"));
     }
+
+    #[test]
+    fn test_setoption_multipv() {
+        // Option names are case-insensitive and setoption has no reply.
+        assert_eq!(handle_uci_command("setoption name multipv value 3"), "");
+        handle_uci_command("position startpos");
+        let response = handle_uci_command("go depth 2");
+        assert!(response.starts_with("bestmove"));
+        assert_eq!(handle_uci_command("setoption name MultiPV value 1"), "");
+    }
+
+    #[test]
+    fn test_setoption_unknown_is_ignored() {
+        assert_eq!(
+            handle_uci_command("setoption name NoSuchOption value 7"),
+            ""
+        );
+        assert_eq!(handle_uci_command("isready"), "readyok");
+    }
+
+    #[test]
+    fn test_setoption_threads_and_hash() {
+        assert_eq!(handle_uci_command("setoption name Threads value 2"), "");
+        assert_eq!(handle_uci_command("setoption name Hash value 8"), "");
+        handle_uci_command("position startpos moves e2e4");
+        assert!(handle_uci_command("go depth 3").starts_with("bestmove"));
+        assert_eq!(handle_uci_command("setoption name Threads value 1"), "");
+        assert_eq!(handle_uci_command("setoption name Hash value 16"), "");
+    }
+
+    #[test]
+    fn test_setoption_contempt() {
+        assert_eq!(handle_uci_command("setoption name Contempt value -25"), "");
+        assert_eq!(
+            handle_uci_command("setoption name Dynamic Contempt value true"),
+            ""
+        );
+        // Out-of-range values clamp; unparsable ones are ignored.
+        assert_eq!(handle_uci_command("setoption name Contempt value 900"), "");
+        assert_eq!(handle_uci_command("setoption name Contempt value lots"), "");
+        handle_uci_command("position startpos moves g1f3 g8f6 f3g1 f6g8");
+        assert!(handle_uci_command("go depth 2").starts_with("bestmove"));
+        assert_eq!(handle_uci_command("setoption name Contempt value 0"), "");
+        assert_eq!(
+            handle_uci_command("setoption name Dynamic Contempt value false"),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_setoption_strength() {
+        assert_eq!(
+            handle_uci_command("setoption name UCI_LimitStrength value true"),
+            ""
+        );
+        assert_eq!(handle_uci_command("setoption name UCI_Elo value 800"), "");
+        handle_uci_command("position startpos");
+        assert!(handle_uci_command("go movetime 100").starts_with("bestmove"));
+        assert_eq!(
+            handle_uci_command("setoption name UCI_LimitStrength value false"),
+            ""
+        );
+        assert_eq!(handle_uci_command("setoption name Skill Level value 3"), "");
+        assert!(handle_uci_command("go nodes 2000").starts_with("bestmove"));
+        assert_eq!(
+            handle_uci_command("setoption name Skill Level value 20"),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_bench_command_reports_nodes_and_nps() {
+        let response = handle_uci_command("bench 1");
+        assert!(response.contains("Nodes searched  : "), "{response}");
+        assert!(response.contains("Nodes/second    : "), "{response}");
+        assert!(response.contains("Position 50/50"), "{response}");
+    }
+
+    #[test]
+    fn test_go_perft_prints_divide() {
+        handle_uci_command("position startpos");
+        let response = handle_uci_command("go perft 1");
+        assert!(response.contains("Nodes searched: "), "{response}");
+        assert_eq!(handle_uci_command("go perft x"), "Usage: go perft <depth>");
+    }
+
+    #[test]
+    fn test_eval_command_prints_trace() {
+        handle_uci_command("position startpos moves e2e4");
+        let response = handle_uci_command("eval");
+        for term in ["Material", "Pawns", "King safety", "Mobility", "Total"] {
+            assert!(response.contains(term), "{response}");
+        }
+        assert!(response.contains("Final evaluation: "), "{response}");
+    }
+
+    #[test]
+    fn test_setoption_eval_parameters() {
+        use chesslib::eval_params::EvalParams;
+
+        handle_uci_command("setoption name positional.check value 51");
+        assert_eq!(EvalParams::active().positional.check, 51);
+        // Bad paths and values leave the parameters alone.
+        handle_uci_command("setoption name positional.nonsense value 1");
+        handle_uci_command("setoption name positional.check value x");
+        assert_eq!(EvalParams::active().positional.check, 51);
+        handle_uci_command("setoption name EvalParams value");
+        assert_eq!(*EvalParams::active(), EvalParams::default());
+    }
 }
@@ -0,0 +1,108 @@
+use chesslib::board::Board;
+use chesslib::fen::load_fen;
+use chesslib::types::Move;
+
+fn play(board: &mut Board, moves: &[&str]) {
+    for mv in moves {
+        board.apply_move_from_string(mv);
+    }
+}
+
+#[test]
+fn test_incremental_hash_matches_full_recompute_along_a_game() {
+    // Exercises captures, castling both ways, en passant and promotion.
+    let mut board = Board::new();
+    let moves = [
+        "e2e4", "d7d5", "e4d5", "c7c5", "d5c6", "g8f6", "c6b7", "c8d7", "b7a8q", "e7e6", "g1f3",
+        "f8e7", "f1e2", "e8g8", "e1g1", "b8c6", "d2d4", "d8b6", "c1e3", "d7c8",
+    ];
+    for mv in moves {
+        board.apply_move_from_string(mv);
+        assert_eq!(board.hash, board.compute_hash(), "after {mv}");
+    }
+    for _ in moves {
+        board.undo_last_move();
+        assert_eq!(board.hash, board.compute_hash());
+    }
+    assert_eq!(board.hash, Board::new().hash);
+}
+
+#[test]
+fn test_en_passant_capture_keeps_hash_in_sync() {
+    let mut board = load_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    board.apply_move(&Move::try_from("e5d6").unwrap());
+    assert_eq!(board.hash, board.compute_hash());
+    board.undo_last_move();
+    assert_eq!(board.hash, board.compute_hash());
+}
+
+#[test]
+fn test_transpositions_share_a_hash() {
+    let mut a = Board::new();
+    play(&mut a, &["g1f3", "b8c6", "b1c3"]);
+    let mut b = Board::new();
+    play(&mut b, &["b1c3", "b8c6", "g1f3"]);
+    assert_eq!(a.hash, b.hash);
+}
+
+#[test]
+fn test_side_to_move_changes_hash() {
+    let white = load_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1").unwrap();
+    let black = load_fen("4k3/8/8/8/8/8/8/4K2R b - - 0 1").unwrap();
+    assert_ne!(white.hash, black.hash);
+}
+
+#[test]
+fn test_castling_rights_change_hash() {
+    let with = load_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    let without = load_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1").unwrap();
+    assert_ne!(with.hash, without.hash);
+}
+
+#[test]
+fn test_fen_and_move_sequence_agree() {
+    let mut board = Board::new();
+    play(&mut board, &["e2e4", "c7c5", "g1f3"]);
+    let fen = load_fen("rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2").unwrap();
+    assert_eq!(board.hash, fen.hash);
+}
+
+#[test]
+fn test_uncapturable_en_passant_square_is_not_hashed() {
+    // After 1.e4 no black pawn can take on e3, so the position is the
+    // same as the one a FEN without the ep square describes.
+    let mut board = Board::new();
+    board.apply_move_from_string("e2e4");
+    let no_ep = load_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert_eq!(board.hash, no_ep.hash);
+
+    // A capturable en-passant square is part of the position.
+    let ep = load_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    let no_ep = load_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_ne!(ep.hash, no_ep.hash);
+}
+
+#[test]
+fn test_pawn_hash_follows_pawns_only() {
+    let mut board = Board::new();
+    let start = board.pawn_hash;
+    assert_eq!(start, board.compute_pawn_hash());
+    play(&mut board, &["g1f3", "g8f6"]);
+    assert_eq!(
+        board.pawn_hash, start,
+        "piece moves leave the pawn key alone"
+    );
+    // Pawn pushes, a capture of a pawn, en passant and a promotion.
+    let moves = [
+        "e2e4", "d7d5", "e4d5", "c7c5", "d5c6", "e7e6", "c6b7", "f8e7", "b7a8q",
+    ];
+    for mv in moves {
+        board.apply_move_from_string(mv);
+        assert_eq!(board.pawn_hash, board.compute_pawn_hash(), "after {mv}");
+    }
+    for _ in moves {
+        board.undo_last_move();
+        assert_eq!(board.pawn_hash, board.compute_pawn_hash());
+    }
+    assert_eq!(board.pawn_hash, start);
+}